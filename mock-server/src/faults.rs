@@ -0,0 +1,141 @@
+//! Failures a test can put in front of an endpoint, and a log of what was
+//! requested, to drive the client's retry, resume and offline paths.
+
+use std::collections::HashMap;
+use std::io;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use hyper::body::{Body, Frame, SizeHint};
+
+/// What to do instead of, or on top of, the normal answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Answers with this status and an error body; the data stays untouched.
+    Status(u16),
+    /// 429 with a `Retry-After` of so many seconds.
+    RateLimited(u64),
+    /// Answers normally, after this long.
+    Delay(Duration),
+    /// Sends the headers of the normal answer but only this many bytes of
+    /// its body, then drops the connection.
+    Truncate(usize),
+    /// Answers normally with a byte of the body changed, headers included.
+    Corrupt,
+}
+
+#[derive(Default)]
+pub(crate) struct Faults {
+    /// By endpoint, with how many more requests get it.
+    armed: Mutex<HashMap<String, (Fault, usize)>>,
+    log: Mutex<Vec<(String, u16)>>,
+}
+
+impl Faults {
+    pub fn arm(&self, endpoint: &str, fault: Fault, times: usize) {
+        let mut armed = self.armed.lock().unwrap();
+        if times == 0 {
+            armed.remove(endpoint);
+        } else {
+            armed.insert(endpoint.to_string(), (fault, times));
+        }
+    }
+
+    pub fn disarm(&self, endpoint: &str) {
+        self.armed.lock().unwrap().remove(endpoint);
+    }
+
+    /// The fault for a request to `endpoint`, counting it as used.
+    pub fn take(&self, endpoint: &str) -> Option<Fault> {
+        let mut armed = self.armed.lock().unwrap();
+        let (fault, times) = armed.get_mut(endpoint)?;
+        let fault = fault.clone();
+        *times -= 1;
+        if *times == 0 {
+            armed.remove(endpoint);
+        }
+        Some(fault)
+    }
+
+    pub fn record(&self, endpoint: &str, status: u16) {
+        self.log
+            .lock()
+            .unwrap()
+            .push((endpoint.to_string(), status));
+    }
+
+    pub fn log(&self) -> Vec<(String, u16)> {
+        self.log.lock().unwrap().clone()
+    }
+}
+
+/// A response body that can break off after its data, so the connection
+/// drops before the promised `Content-Length` arrived.
+pub(crate) struct Served {
+    data: Option<Bytes>,
+    cut: bool,
+    flushed: bool,
+}
+
+impl Served {
+    pub fn full(data: Bytes) -> Self {
+        Self {
+            data: Some(data),
+            cut: false,
+            flushed: false,
+        }
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.data.unwrap_or_default()
+    }
+
+    pub fn cut(data: Bytes) -> Self {
+        Self {
+            data: Some(data),
+            cut: true,
+            flushed: false,
+        }
+    }
+}
+
+impl Body for Served {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        if let Some(data) = self.data.take().filter(|data| !data.is_empty()) {
+            return Poll::Ready(Some(Ok(Frame::data(data))));
+        }
+        if self.cut && !self.flushed {
+            // Lets hyper send what it has before the connection goes
+            self.flushed = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        if std::mem::take(&mut self.cut) {
+            let error = io::Error::new(io::ErrorKind::ConnectionAborted, "cut off");
+            return Poll::Ready(Some(Err(error)));
+        }
+        Poll::Ready(None)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.as_ref().is_none_or(Bytes::is_empty) && !self.cut
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.data {
+            // Left open, or hyper would insist on the length of what is sent
+            _ if self.cut => SizeHint::default(),
+            Some(data) => SizeHint::with_exact(data.len() as u64),
+            None => SizeHint::with_exact(0),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! A stand-in for the Ticketbase API with in-memory data, so the desktop app
+//! can be developed offline and tested end to end without credentials.
+//!
+//! It serves the endpoints `ApiClient` uses under `/api`, answers in the
+//! same shapes (quirks included) and keeps every change until it stops.
+
+mod faults;
+mod multipart;
+mod routes;
+mod store;
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+pub use faults::Fault;
+pub use store::{Fixtures, StoredFile};
+
+/// A running server. It stops when dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    faults: Arc<faults::Faults>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Listens on `address` (port 0 picks a free one) and serves from the
+    /// current tokio runtime.
+    pub async fn start(address: impl ToSocketAddrs, fixtures: Fixtures) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let store = Arc::new(Mutex::new(store::Store::new(fixtures)));
+        let faults = Arc::new(faults::Faults::default());
+        let server_faults = faults.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        eprintln!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+                let (store, faults) = (store.clone(), server_faults.clone());
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        routes::handle(store.clone(), faults.clone(), request)
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        eprintln!("Connection failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            address,
+            faults,
+            task,
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The base URL to give a server profile.
+    pub fn url(&self) -> String {
+        format!("http://{}/api", self.address)
+    }
+
+    /// Answers the next `times` requests to `endpoint` (like `getTickets`)
+    /// with `fault`, replacing the one set before.
+    pub fn fail(&self, endpoint: &str, fault: Fault, times: usize) {
+        self.faults.arm(endpoint, fault, times);
+    }
+
+    /// Answers requests to `endpoint` normally again.
+    pub fn recover(&self, endpoint: &str) {
+        self.faults.disarm(endpoint);
+    }
+
+    /// Endpoints requested so far with the status they were answered with,
+    /// oldest first; failed requests included.
+    pub fn requests(&self) -> Vec<(String, u16)> {
+        self.faults.log()
+    }
+
+    /// How often `endpoint` was requested.
+    pub fn request_count(&self, endpoint: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|(requested, _)| requested == endpoint)
+            .count()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
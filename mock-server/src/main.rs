@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ticketbase_mock::{Fixtures, MockServer};
+
+const USAGE: &str = "Usage: ticketbase-mock [--port PORT] [--host HOST] [--fixtures FILE]";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut host = "127.0.0.1".to_string();
+    let mut port = 8787;
+    let mut fixtures_path: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            "--port" | "--host" | "--fixtures" => args.next(),
+            _ => None,
+        };
+        let Some(value) = value else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        };
+        match arg.as_str() {
+            "--port" => match value.parse() {
+                Ok(value) => port = value,
+                Err(_) => {
+                    eprintln!("Not a port: {}", value);
+                    return ExitCode::FAILURE;
+                }
+            },
+            "--host" => host = value,
+            _ => fixtures_path = Some(PathBuf::from(value)),
+        }
+    }
+
+    let fixtures = match &fixtures_path {
+        Some(path) => match Fixtures::load(path) {
+            Ok(fixtures) => fixtures,
+            Err(e) => {
+                eprintln!("Failed to load fixtures: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Fixtures::bundled(),
+    };
+
+    let server = match MockServer::start((host.as_str(), port), fixtures).await {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to listen on {}:{}: {}", host, port, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Ticketbase mock serving {}", server.url());
+    if fixtures_path.is_none() {
+        println!("Sign in as anna@example.com, jonas@example.com or maria@muster.example with password \"secret\"");
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,80 @@
+/// One part of a `multipart/form-data` body.
+pub struct Field {
+    pub name: String,
+    pub filename: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// The boundary from a `multipart/form-data` content type.
+pub fn boundary(content_type: &str) -> Option<&str> {
+    let (kind, params) = content_type.split_once(';')?;
+    if !kind.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Splits a whole multipart body. Enough for what reqwest and browsers
+/// send; malformed parts are skipped rather than reported.
+pub fn parse(body: &[u8], boundary: &str) -> Vec<Field> {
+    let delimiter = format!("--{}", boundary);
+    let mut fields = Vec::new();
+
+    for part in split(body, delimiter.as_bytes()).into_iter().skip(1) {
+        // The closing delimiter is followed by `--`
+        if part.starts_with(b"--") {
+            break;
+        }
+        let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+        let Some(end) = find(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&part[..end]);
+        let Some(disposition) = headers.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("content-disposition")
+                .then_some(value)
+        }) else {
+            continue;
+        };
+        let Some(name) = disposition_param(disposition, "name") else {
+            continue;
+        };
+        fields.push(Field {
+            name,
+            filename: disposition_param(disposition, "filename"),
+            data: part[end + 4..].to_vec(),
+        });
+    }
+    fields
+}
+
+fn disposition_param(disposition: &str, key: &str) -> Option<String> {
+    disposition.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        (name.trim() == key).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn split<'a>(mut data: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    while let Some(at) = find(data, delimiter) {
+        parts.push(&data[..at]);
+        data = &data[at + delimiter.len()..];
+    }
+    parts.push(data);
+    parts
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,1026 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderMap};
+use hyper::http::request::Parts;
+use hyper::{Request, Response};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::faults::{Fault, Faults, Served};
+use crate::multipart::{self, Field};
+use crate::store::{
+    id_of, next_id, now, status_name, unix_now, without, Player, Store, StoredFile,
+};
+
+pub type Reply = Response<Served>;
+type Answer = (u16, Value);
+
+// Player codes, as the PHP backend defines them
+const PLAY: u8 = 1;
+const PAUSE: u8 = 2;
+const RESUME: u8 = 3;
+const STOP: u8 = 4;
+
+pub async fn handle(
+    store: Arc<Mutex<Store>>,
+    faults: Arc<Faults>,
+    request: Request<Incoming>,
+) -> Result<Reply, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => return Ok(json(400, &json!({ "message": e.to_string() }))),
+    };
+
+    let endpoint = parts.uri.path().strip_prefix("/api/").unwrap_or_default();
+    let reply = match faults.take(endpoint) {
+        None => respond(&store, &parts, &body),
+        Some(fault) => with_fault(fault, || respond(&store, &parts, &body)).await,
+    };
+    faults.record(endpoint, reply.status().as_u16());
+    Ok(reply)
+}
+
+async fn with_fault(fault: Fault, respond: impl FnOnce() -> Reply) -> Reply {
+    match fault {
+        Fault::Status(status) => json(status, &error(status, "Injected failure").1),
+        Fault::RateLimited(seconds) => {
+            let mut reply = json(429, &json!({ "message": "Too Many Attempts." }));
+            reply
+                .headers_mut()
+                .insert(header::RETRY_AFTER, seconds.into());
+            reply
+        }
+        Fault::Delay(delay) => {
+            tokio::time::sleep(delay).await;
+            respond()
+        }
+        Fault::Truncate(length) => {
+            let (mut parts, body) = respond().into_parts();
+            let data = body.into_bytes();
+            // Promises the whole body, so the client sees the connection drop
+            parts
+                .headers
+                .insert(header::CONTENT_LENGTH, data.len().into());
+            Response::from_parts(parts, Served::cut(data.slice(..length.min(data.len()))))
+        }
+        Fault::Corrupt => {
+            let (parts, body) = respond().into_parts();
+            let mut data = body.into_bytes().to_vec();
+            if let Some(byte) = data.last_mut() {
+                *byte ^= 0xff;
+            }
+            Response::from_parts(parts, Served::full(Bytes::from(data)))
+        }
+    }
+}
+
+fn respond(store: &Mutex<Store>, parts: &Parts, body: &[u8]) -> Reply {
+    let Some(endpoint) = parts.uri.path().strip_prefix("/api/") else {
+        return not_found(parts.uri.path());
+    };
+    let (params, files) = read_params(parts, body);
+    let mut store = store.lock().unwrap();
+
+    if endpoint == "login" {
+        let (status, value) = login(&mut store, &params);
+        return json(status, &value);
+    }
+
+    // Laravel's answer to a missing or unknown token
+    let Some(user) = bearer(&parts.headers).and_then(|token| store.sessions.get(token).copied())
+    else {
+        return json(401, &json!({ "message": "Unauthenticated." }));
+    };
+
+    if endpoint == "getDataUrl" {
+        return download(&store, &params, &parts.headers);
+    }
+
+    let key = parts
+        .headers
+        .get("idempotency-key")
+        .and_then(|key| key.to_str().ok());
+    if let Some((status, value)) = key.and_then(|key| store.replies.get(key)) {
+        return json(*status, value);
+    }
+
+    let Some((status, value)) = route(&mut store, endpoint, &params, files, user) else {
+        return not_found(parts.uri.path());
+    };
+    if let Some(key) = key {
+        store
+            .replies
+            .insert(key.to_string(), (status, value.clone()));
+    }
+    json(status, &value)
+}
+
+fn route(
+    store: &mut Store,
+    endpoint: &str,
+    params: &Params,
+    files: Vec<Field>,
+    user: u32,
+) -> Option<Answer> {
+    let answer = match endpoint {
+        "getTickets" | "testGetTickets" => tickets(store, params, user),
+        "getTicketsToday" => tickets_today(store, params, user),
+        "getTicketData" => ticket_data(store, params),
+        "getTicketById" => ticket_by_id(store, params),
+        "createTicket" => create_ticket(store, params, files, user),
+        "TicketTerminieren" | "ticketTerminierenApi" => schedule(store, params, user),
+        "saveVerlaufApi" => save_history(store, params, user),
+        "correctWatch" => correct_watch(store, params, user),
+        "getUserStatus" | "changeUserStatus" => user_status(store, endpoint, params, user),
+        "getCustomers" => ok(json!({ "customers": store.data.companies })),
+        "getCustomerLocations" => customer_locations(store, params),
+        "getLocationUsers" => location_users(store, params),
+        "getCheckList" => check_list(store, params),
+        "newTodo" => new_todo(store, params, user),
+        "checkTodo" => check_todo(store, params),
+        "play" | "pause" | "resume" | "stop" => player(store, endpoint, params, user),
+        "getPlayerStatus" => player_status(store, params, user),
+        "editProfile" => edit_profile(store, params, user),
+        "changePassword" => change_password(store, params, user),
+        "getUsersMailSettings" => {
+            ok(json!({ "data": { "user_mail_settings_arr": store.data.mail_settings } }))
+        }
+        "userMailSettings" => mail_settings(store, params),
+        "getTicketMessages" => messages(store, params),
+        "sendMessage" => send_message(store, params, user),
+        "getWikiData" => ok(json!({ "wikiData": store.data.wiki })),
+        "getClosedConfirmedTickets" => closed_tickets(store, params, user),
+        "rateTicket" => rate_ticket(store, params, user),
+        "Report4" => report4(store, params),
+        "Report5" => report5(store, params),
+        "getTopUsers" => ok(json!({ "result": "success", "top_users": store.data.top_users })),
+        "getCorrectWatchHistoriesForPeriod" => work_histories(store, params, user),
+        _ => match endpoint.strip_prefix("getTemplates") {
+            Some("") => templates(store, params.u32("company_id")),
+            Some(company) => templates(store, company.strip_prefix('/')?.parse().ok()),
+            None => return None,
+        },
+    };
+    Some(answer)
+}
+
+// Authentication
+
+fn login(store: &mut Store, params: &Params) -> Answer {
+    let (email, password) = (params.str("email"), params.str("password"));
+    let Some(id) = store
+        .data
+        .users
+        .iter()
+        .find(|user| user["email"].as_str() == email && user["password"].as_str() == password)
+        .map(id_of)
+    else {
+        return (401, json!({ "status": "error", "message": "Unauthorized" }));
+    };
+
+    let token = format!("mock-{}-{}", id, store.sessions.len() + 1);
+    store.sessions.insert(token.clone(), id);
+    (
+        200,
+        json!({
+            "status": "success",
+            "user": store.public_user(id),
+            "authorisation": { "token": token, "type": "bearer" },
+        }),
+    )
+}
+
+// Tickets
+
+/// Customers only see their own company's tickets, technicians all of them.
+fn visible_tickets(store: &Store, user: u32) -> Vec<&Value> {
+    let account = store.user(user).cloned().unwrap_or_default();
+    let customer = account["role"]["name"] == "Customer";
+    store
+        .data
+        .tickets
+        .iter()
+        .filter(|ticket| !customer || ticket["company_id"] == account["company_id"])
+        .collect()
+}
+
+fn tickets(store: &Store, params: &Params, user: u32) -> Answer {
+    let owner = params.u32("for_user_id").unwrap_or(user);
+    let visible = visible_tickets(store, user);
+    let views = |keep: &dyn Fn(&Value) -> bool| -> Vec<Value> {
+        visible
+            .iter()
+            .filter(|ticket| keep(ticket))
+            .map(|ticket| store.ticket_view(ticket, user))
+            .collect()
+    };
+
+    ok(json!({
+        "new_tickets": views(&|ticket| ticket["status_id"] == 1 && ticket["technician_id"].is_null()),
+        "my_tickets": views(&|ticket| ticket["technician_id"] == owner && ticket["status_id"] != 4),
+        "all_tickets": views(&|_| true),
+    }))
+}
+
+fn tickets_today(store: &Store, params: &Params, user: u32) -> Answer {
+    let owner = params.u32("user_id").unwrap_or(user);
+    let date = params.str("datum").unwrap_or_default();
+    let today: Vec<Value> = store
+        .data
+        .tickets
+        .iter()
+        .filter(|ticket| {
+            ticket["technician_id"] == owner
+                && ticket["ticket_start"]
+                    .as_str()
+                    .is_some_and(|start| start.starts_with(date))
+        })
+        .map(|ticket| store.ticket_view(ticket, user))
+        .collect();
+    ok(json!({ "todayTickets": today }))
+}
+
+fn ticket_data(store: &Store, params: &Params) -> Answer {
+    let ticket_id = params.u32("ticket_id").unwrap_or(0);
+    let entries: Vec<Value> = store
+        .data
+        .histories
+        .iter()
+        .filter(|entry| entry["ticket_id"] == ticket_id)
+        .map(|entry| {
+            let mut entry = entry.clone();
+            let technician = entry["technician_id"].as_u64().unwrap_or(0) as u32;
+            entry["user"] = store.public_user(technician);
+            entry["status_name"] =
+                json!(status_name(entry["status_id"].as_u64().unwrap_or(0) as u32));
+            entry
+        })
+        .collect();
+    ok(json!({ "ticket_data": entries }))
+}
+
+fn ticket_by_id(store: &Store, params: &Params) -> Answer {
+    match params.u32("ticket_id").and_then(|id| store.ticket(id)) {
+        Some(ticket) => ok(json!({ "tickets": store.raw_ticket(ticket) })),
+        None => error(404, "Ticket not found"),
+    }
+}
+
+/// JSON or `multipart/form-data` with the files under `ticket_image`.
+fn create_ticket(store: &mut Store, params: &Params, files: Vec<Field>, user: u32) -> Answer {
+    let Some(description) = params
+        .str("description")
+        .filter(|text| !text.trim().is_empty())
+    else {
+        return error(422, "The description field is required.");
+    };
+    let Some(company_id) = params
+        .u32("company_id")
+        .filter(|id| store.company(*id).is_some())
+    else {
+        return error(422, "The selected company id is invalid.");
+    };
+    let for_user = params.u32("for_user_id").unwrap_or(user);
+    let template_id = params.u32("dyn_template_id");
+
+    let id = next_id(&store.data.tickets);
+    let mut attachments = Vec::new();
+    for file in files {
+        let Some(filename) = file.filename.filter(|_| file.name == "ticket_image") else {
+            continue;
+        };
+        attachments.push(filename.clone());
+        store.data.attachments.push(StoredFile {
+            ticket_id: id,
+            filename,
+            bytes: Some(file.data),
+            ..Default::default()
+        });
+    }
+
+    let subject = template_id.and_then(|template| {
+        store
+            .data
+            .templates
+            .iter()
+            .find(|candidate| id_of(candidate) == template)
+            .map(|template| template["name"].clone())
+    });
+    let my_ticket_id = store
+        .data
+        .tickets
+        .iter()
+        .filter(|ticket| ticket["company_id"] == company_id)
+        .count()
+        + 1;
+    let for_user_record = store.user(for_user).cloned().unwrap_or_default();
+    let ticket = json!({
+        "id": id,
+        "description": description,
+        "status": status_name(1),
+        "status_id": 1,
+        "summary": null,
+        "ticketCreator": store.user_name(user),
+        "ticketUser": for_user_record["name"],
+        "ticketUserPhone": for_user_record["phone"],
+        "ticketTerminatedUser": null,
+        "attachments": attachments,
+        "subject": subject,
+        "priority": params.str("priority").unwrap_or("NORMAL"),
+        "index": 1,
+        "my_ticket_id": my_ticket_id,
+        "location_id": params.u32("location_id"),
+        "company_id": company_id,
+        "dyn_template_id": template_id,
+        "created_at": now(),
+        "ticket_start": null,
+        "template_data": null,
+        "pool_name": null,
+        "technician_id": null,
+    });
+    store.data.tickets.push(ticket);
+
+    ok(json!({
+        "message": "Ticket created successfully",
+        "ticket_id": id,
+        "attachments": attachments,
+    }))
+}
+
+/// Assigns the ticket to the user for the given start.
+fn schedule(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let technician = params.u32("user_id").unwrap_or(user);
+    let start = params
+        .str("ticket_start")
+        .or(params.str("date"))
+        .map(str::to_string);
+    let name = store.user_name(technician);
+    let Some(ticket) = params.u32("ticket_id").and_then(|id| store.ticket_mut(id)) else {
+        return error(404, "Ticket not found");
+    };
+    ticket["ticket_start"] = json!(start);
+    ticket["technician_id"] = json!(technician);
+    ticket["ticketTerminatedUser"] = json!(name);
+    ticket["status_id"] = json!(2);
+    ticket["status"] = json!(status_name(2));
+    ok(json!({ "message": "Ticket scheduled" }))
+}
+
+/// A history entry books the time on the user's timer for the ticket.
+fn save_history(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let Some(ticket_id) = params
+        .u32("ticket_id")
+        .filter(|id| store.ticket(*id).is_some())
+    else {
+        return error(404, "Ticket not found");
+    };
+    let technician = params.u32("user_id").unwrap_or(user);
+    let status_id = params.u32("status_id").unwrap_or(13);
+    let total_time = store
+        .players
+        .remove(&(ticket_id, technician))
+        .map_or(0, |player| player.total_seconds());
+    // The booked time, as if it was worked in one go up to now
+    let service_end = (total_time > 0).then(unix_now);
+    let service_start = service_end.map(|end| end - total_time);
+
+    let id = next_id(&store.data.histories);
+    let created_at = now();
+    store.data.histories.push(json!({
+        "id": id,
+        "ticket_id": ticket_id,
+        "technician_id": technician,
+        "status_id": status_id,
+        "technician_reply": params.str("verlauf_text"),
+        "created_at": created_at,
+        "updated_at": created_at,
+        "service_start": service_start,
+        "service_end": service_end,
+        "total_time": total_time,
+    }));
+    store.set_status(ticket_id, status_id);
+
+    if let Some(date) = params.str("retermDate") {
+        let technician = params.u32("retermUserId").unwrap_or(technician);
+        if let Some(ticket) = store.ticket_mut(ticket_id) {
+            ticket["ticket_start"] = json!(date);
+            ticket["technician_id"] = json!(technician);
+        }
+    }
+    ok(json!({ "message": "History saved" }))
+}
+
+/// Times are in minutes, like `getPlayerStatus` reports them.
+fn correct_watch(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let (Some(ticket_id), Some(new_time)) = (params.u32("ticket_id"), params.i64("new_time"))
+    else {
+        return error(422, "ticket_id and new_time are required.");
+    };
+    let technician = params.u32("user_id").unwrap_or(user);
+    let Some(player) = store.players.get_mut(&(ticket_id, technician)) else {
+        return error(422, "No time recorded for this ticket.");
+    };
+    player.seconds = new_time * 60;
+    if player.running_since.is_some() {
+        player.running_since = Some(Instant::now());
+    }
+
+    let id = next_id(&store.corrections);
+    store.corrections.push(json!({
+        "id": id,
+        "ticket_id": ticket_id,
+        "user_id": technician,
+        "old_time": params.i64("old_time"),
+        "new_time": new_time,
+        "created_at": now(),
+    }));
+    ok(json!({ "message": "Time corrected" }))
+}
+
+// User Status
+
+/// `type` 1 signs the user in, anything else marks them away.
+fn user_status(store: &mut Store, endpoint: &str, params: &Params, user: u32) -> Answer {
+    let user = params.u32("user_id").unwrap_or(user);
+    if endpoint == "changeUserStatus" {
+        store.active.insert(user, params.u32("type") == Some(1));
+    }
+    let active = store.active.get(&user).copied().unwrap_or(true);
+    ok(json!({
+        "data": {
+            "activity": {
+                "activeStatus": active,
+                "message": if active { "Aktiv" } else { "Abwesend" },
+            }
+        }
+    }))
+}
+
+// Templates and Data
+
+fn templates(store: &Store, company_id: Option<u32>) -> Answer {
+    let templates: Vec<Value> = store
+        .data
+        .templates
+        .iter()
+        .filter(|template| {
+            template["company_id"].is_null()
+                || company_id.is_some_and(|id| template["company_id"] == id)
+        })
+        .map(|template| without(template.clone(), &["company_id"]))
+        .collect();
+    ok(json!({ "data": { "templates": templates } }))
+}
+
+fn customer_locations(store: &Store, params: &Params) -> Answer {
+    let company_id = params.u32("customer_id").unwrap_or(0);
+    let locations: Vec<Value> = store
+        .company(company_id)
+        .and_then(|company| company["locations"].as_array())
+        .into_iter()
+        .flatten()
+        .map(|location| json!({ "id": location["id"], "name": location["name"], "company_id": company_id }))
+        .collect();
+    ok(json!({ "data": { "locations": locations } }))
+}
+
+fn location_users(store: &Store, params: &Params) -> Answer {
+    let location_id = params.u32("location_id").unwrap_or(0);
+    let users: Vec<Value> = store
+        .data
+        .users
+        .iter()
+        .filter(|user| user["location_id"] == location_id)
+        .map(|user| store.public_user(id_of(user)))
+        .collect();
+    ok(json!({ "data": { "users": users } }))
+}
+
+// Todo List
+
+fn todos(store: &Store, ticket_id: u32) -> Vec<Value> {
+    store
+        .data
+        .todos
+        .iter()
+        .filter(|todo| todo["ticket_id"] == ticket_id)
+        .cloned()
+        .collect()
+}
+
+fn check_list(store: &Store, params: &Params) -> Answer {
+    let ticket_id = params.u32("ticket_id").unwrap_or(0);
+    ok(json!({ "check_list": todos(store, ticket_id) }))
+}
+
+/// Answers with the backend's own spelling of "success".
+fn new_todo(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let (Some(ticket_id), Some(text)) = (params.u32("ticket_id"), params.str("todo")) else {
+        return error(422, "ticket_id and todo are required.");
+    };
+    let id = next_id(&store.data.todos);
+    store.data.todos.push(json!({
+        "id": id,
+        "ticket_id": ticket_id,
+        "user_id": params.u32("user_id").unwrap_or(user),
+        "to_do": text,
+        "checked": 0,
+        "created_at": now(),
+    }));
+    (
+        200,
+        json!({ "status": "sucess", "check_list": todos(store, ticket_id) }),
+    )
+}
+
+fn check_todo(store: &mut Store, params: &Params) -> Answer {
+    let todo_id = params.u32("todo_id").unwrap_or(0);
+    let Some(todo) = store
+        .data
+        .todos
+        .iter_mut()
+        .find(|todo| id_of(todo) == todo_id)
+    else {
+        return error(404, "Todo not found");
+    };
+    todo["checked"] = json!(params.u32("type").unwrap_or(0));
+    ok(json!({}))
+}
+
+// Ticket Player
+
+fn player(store: &mut Store, action: &str, params: &Params, user: u32) -> Answer {
+    let Some(ticket_id) = params
+        .u32("ticket_id")
+        .filter(|id| store.ticket(*id).is_some())
+    else {
+        return error(404, "Ticket not found");
+    };
+    let key = (ticket_id, params.u32("user_id").unwrap_or(user));
+    let current = store.players.get(&key).map(|player| player.play_status);
+    let running = matches!(current, Some(PLAY | RESUME));
+
+    let player = store.players.entry(key).or_insert(Player {
+        play_status: STOP,
+        seconds: 0,
+        running_since: None,
+    });
+    match action {
+        "play" if !running => {
+            player.play_status = PLAY;
+            player.running_since = Some(Instant::now());
+        }
+        "resume" if current == Some(PAUSE) => {
+            player.play_status = RESUME;
+            player.running_since = Some(Instant::now());
+        }
+        "pause" | "stop" if running || (action == "stop" && current == Some(PAUSE)) => {
+            player.seconds = player.total_seconds();
+            player.running_since = None;
+            player.play_status = if action == "pause" { PAUSE } else { STOP };
+        }
+        _ => return error(422, &format!("Cannot {} from the current state", action)),
+    }
+    ok(json!({ "message": format!("{} ok", action) }))
+}
+
+fn player_status(store: &Store, params: &Params, user: u32) -> Answer {
+    let ticket_id = params.u32("ticket_id").unwrap_or(0);
+    let key = (ticket_id, params.u32("user_id").unwrap_or(user));
+    let ticket_status = store
+        .ticket(ticket_id)
+        .map(|ticket| ticket["status_id"].clone());
+    let status = store.players.get(&key).map(|player| {
+        let seconds = player.total_seconds();
+        json!({
+            "id": ticket_id,
+            "play_status": player.play_status,
+            "status_id": player.play_status,
+            "total_time": seconds / 60,
+            "total_time_raw": format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60),
+            "tmp_description": null,
+            "ticket_status_id": ticket_status,
+        })
+    });
+    ok(json!({ "playerStatus": status }))
+}
+
+// Profile and Mail Settings
+
+fn edit_profile(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let id = params.u32("user_id").unwrap_or(user);
+    let Some(record) = store.user_mut(id) else {
+        return error(404, "User not found");
+    };
+    if let Some(name) = params.str("name") {
+        record["name"] = json!(name);
+    }
+    if let Some(phone) = params.str("phone") {
+        record["phone"] = json!(phone);
+    }
+    ok(json!({ "data": { "user": store.public_user(id) } }))
+}
+
+fn change_password(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let Some(password) = params
+        .str("new_password")
+        .filter(|password| password.len() >= 6)
+    else {
+        return error(422, "The new password must be at least 6 characters.");
+    };
+    let id = params.u32("user_id").unwrap_or(user);
+    match store.user_mut(id) {
+        Some(record) => {
+            record["password"] = json!(password);
+            ok(json!({ "message": "Password changed" }))
+        }
+        None => error(404, "User not found"),
+    }
+}
+
+/// `type` is the number the settings page sends for each switch.
+fn mail_settings(store: &mut Store, params: &Params) -> Answer {
+    let key = match params.u32("type") {
+        Some(2) => "new_ticket_pool_mail",
+        Some(3) => "new_help_mail",
+        Some(4) => "new_message_mail",
+        Some(5) => "new_forward_mail",
+        _ => return error(422, "Unknown mail setting"),
+    };
+    store.data.mail_settings[key] = json!(params.u32("value") == Some(1));
+    ok(json!({}))
+}
+
+// Messages
+
+fn messages(store: &Store, params: &Params) -> Answer {
+    let ticket_id = params.u32("ticket_id").unwrap_or(0);
+    let messages: Vec<Value> = store
+        .data
+        .messages
+        .iter()
+        .filter(|message| message["ticket_id"] == ticket_id)
+        .map(|message| {
+            let author = store
+                .user(message["user_id"].as_u64().unwrap_or(0) as u32)
+                .map(|user| json!({ "id": user["id"], "name": user["name"], "email": user["email"] }));
+            let mut message = message.clone();
+            message["user"] = json!(author);
+            message
+        })
+        .collect();
+    ok(json!({ "messages": messages }))
+}
+
+fn send_message(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let (Some(ticket_id), Some(text)) = (params.u32("ticket_id"), params.str("message")) else {
+        return error(422, "ticket_id and message are required.");
+    };
+    let id = next_id(&store.data.messages);
+    store.data.messages.push(json!({
+        "id": id,
+        "ticket_id": ticket_id,
+        "user_id": params.u32("user_id").unwrap_or(user),
+        "message": text,
+        "created_at": now(),
+    }));
+    ok(json!({ "message": "Message sent" }))
+}
+
+// Ratings and Reports
+
+fn closed_tickets(store: &Store, params: &Params, user: u32) -> Answer {
+    let rater = params.u32("user_id").unwrap_or(user);
+    let tickets: Vec<Value> = visible_tickets(store, rater)
+        .into_iter()
+        .filter(|ticket| ticket["status_id"] == 4)
+        .filter(|ticket| {
+            !store
+                .data
+                .ratings
+                .iter()
+                .any(|rating| rating["ticket_id"] == ticket["id"] && rating["user_id"] == rater)
+        })
+        .map(|ticket| store.ticket_view(ticket, user))
+        .collect();
+    ok(json!({ "tickets": tickets }))
+}
+
+fn rate_ticket(store: &mut Store, params: &Params, user: u32) -> Answer {
+    let Some(rating) = params
+        .u32("rating")
+        .filter(|rating| (1..=5).contains(rating))
+    else {
+        return error(422, "The rating must be between 1 and 5.");
+    };
+    let Some(ticket) = params.u32("ticket_id").and_then(|id| store.ticket(id)) else {
+        return error(404, "Ticket not found");
+    };
+    let rating = json!({
+        "ticket_id": ticket["id"],
+        "user_id": params.u32("user_id").unwrap_or(user),
+        "technician_id": ticket["technician_id"],
+        "rating": rating,
+        "feedback": params.str("feedback"),
+        "created_at": now(),
+    });
+    store.data.ratings.push(rating);
+    ok(json!({ "message": "Thank you for your feedback" }))
+}
+
+/// Customer reviews in the period, followed by their average.
+fn report4(store: &Store, params: &Params) -> Answer {
+    let period = Period::from(params);
+    let mut rows: Vec<Value> = Vec::new();
+    let mut sum = 0;
+    for rating in store
+        .data
+        .ratings
+        .iter()
+        .filter(|rating| period.contains(&rating["created_at"]))
+    {
+        let ticket = rating["ticket_id"]
+            .as_u64()
+            .and_then(|id| store.ticket(id as u32))
+            .cloned()
+            .unwrap_or_default();
+        let technician = rating["technician_id"].as_u64().unwrap_or(0) as u32;
+        let company = ticket["company_id"]
+            .as_u64()
+            .and_then(|id| store.company(id as u32))
+            .map(|company| company["name"].clone());
+        sum += rating["rating"].as_u64().unwrap_or(0);
+        rows.push(json!({
+            "Techniker": store.user_name(technician),
+            "Ticket-ID": rating["ticket_id"],
+            "Kunde": company,
+            "Note": rating["rating"],
+            "Feedback": rating["feedback"],
+        }));
+    }
+    let average = if rows.is_empty() {
+        0.0
+    } else {
+        sum as f64 / rows.len() as f64
+    };
+    rows.push(json!({ "average note": format!("{:.2}", average) }));
+    ok(json!({ "result": "success", "report": rows }))
+}
+
+/// Per technician: tickets created in the period, how many were closed,
+/// reopened and reviewed.
+fn report5(store: &Store, params: &Params) -> Answer {
+    let period = Period::from(params);
+    let percent = |part: usize, whole: usize| {
+        format!(
+            "{:.2}%",
+            if whole == 0 {
+                0.0
+            } else {
+                part as f64 * 100.0 / whole as f64
+            }
+        )
+    };
+
+    let rows: Vec<Value> = store
+        .data
+        .users
+        .iter()
+        .filter(|user| user["role"]["name"] == "Technician")
+        .map(|technician| {
+            let tickets: Vec<&Value> = store
+                .data
+                .tickets
+                .iter()
+                .filter(|ticket| {
+                    ticket["technician_id"] == technician["id"]
+                        && period.contains(&ticket["created_at"])
+                })
+                .collect();
+            let count = |status: u32| {
+                tickets
+                    .iter()
+                    .filter(|ticket| ticket["status_id"] == status)
+                    .count()
+            };
+            let (closed, reopened) = (count(4), count(8));
+            let reviewed = tickets
+                .iter()
+                .filter(|ticket| {
+                    store
+                        .data
+                        .ratings
+                        .iter()
+                        .any(|rating| rating["ticket_id"] == ticket["id"])
+                })
+                .count();
+            json!({
+                "Techniker": technician["name"],
+                "All tickets": tickets.len(),
+                "All closed tickets": closed,
+                "All reopened tickets": reopened,
+                "All reviewed tickets": reviewed,
+                "Percentage 1": percent(reopened, tickets.len()),
+                "Percentage 2": percent(reviewed, closed),
+            })
+        })
+        .collect();
+    ok(json!({ "result": "success", "report": rows }))
+}
+
+/// The user's work histories in the period, with the time they booked.
+fn work_histories(store: &Store, params: &Params, user: u32) -> Answer {
+    let period = Period::from(params);
+    let owner = params.u32("user_id").unwrap_or(user);
+    let histories: Vec<&Value> = store
+        .data
+        .histories
+        .iter()
+        .filter(|history| {
+            history["technician_id"] == owner && period.contains(&history["created_at"])
+        })
+        .collect();
+    ok(json!({ "histories": histories }))
+}
+
+/// `start_date` to `end_date`, both inclusive; open ends match everything.
+struct Period {
+    start: String,
+    end: String,
+}
+
+impl Period {
+    fn from(params: &Params) -> Self {
+        Self {
+            start: params.str("start_date").unwrap_or_default().to_string(),
+            end: params.str("end_date").unwrap_or("9999-12-31").to_string(),
+        }
+    }
+
+    fn contains(&self, timestamp: &Value) -> bool {
+        let date = timestamp.as_str().unwrap_or_default();
+        let date = date.get(..10).unwrap_or(date);
+        self.start.as_str() <= date && date <= self.end.as_str()
+    }
+}
+
+// Attachments
+
+/// Serves a stored file with the headers the download manager resumes and
+/// verifies with: `ETag`, `Accept-Ranges` and `Repr-Digest`.
+fn download(store: &Store, params: &Params, headers: &HeaderMap) -> Reply {
+    let ticket_id = params.u32("ticket_id").unwrap_or(0);
+    let filename = params.str("filename").unwrap_or_default();
+    let Some(file) = store
+        .data
+        .attachments
+        .iter()
+        .find(|file| file.ticket_id == ticket_id && file.filename == filename)
+    else {
+        return json(
+            404,
+            &json!({ "status": "error", "message": "File not found" }),
+        );
+    };
+
+    let data = file.data();
+    let digest = Sha256::digest(data);
+    let etag = format!(
+        "\"{}\"",
+        digest[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    );
+    let header = |name: header::HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+
+    // A range only counts while the file is the one the client has part of
+    let offset = header(header::RANGE)
+        .filter(|_| header(header::IF_RANGE).is_none_or(|validator| validator == etag))
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok());
+
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(
+            "Repr-Digest",
+            format!(
+                "sha-256=:{}:",
+                base64::engine::general_purpose::STANDARD.encode(digest)
+            ),
+        );
+    let response = match offset {
+        None => builder
+            .status(200)
+            .body(Served::full(Bytes::copy_from_slice(data))),
+        Some(start) if start < data.len() => builder
+            .status(206)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, data.len() - 1, data.len()),
+            )
+            .body(Served::full(Bytes::copy_from_slice(&data[start..]))),
+        Some(_) => builder
+            .status(416)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", data.len()))
+            .body(Served::full(Bytes::new())),
+    };
+    response.unwrap()
+}
+
+// Request and response plumbing
+
+/// Query string, JSON body and form fields of a request in one map, since
+/// Ticketbase accepts its parameters in any of them.
+struct Params(Map<String, Value>);
+
+impl Params {
+    fn str(&self, key: &str) -> Option<&str> {
+        self.0.get(key)?.as_str()
+    }
+
+    fn i64(&self, key: &str) -> Option<i64> {
+        let value = self.0.get(key)?;
+        value
+            .as_i64()
+            .or_else(|| value.as_str()?.trim().parse().ok())
+    }
+
+    fn u32(&self, key: &str) -> Option<u32> {
+        self.i64(key).and_then(|value| u32::try_from(value).ok())
+    }
+}
+
+fn read_params(parts: &Parts, body: &[u8]) -> (Params, Vec<Field>) {
+    let mut params: Map<String, Value> =
+        form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .map(|(key, value)| (key.into_owned(), json!(value)))
+            .collect();
+    let mut files = Vec::new();
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if let Some(boundary) = multipart::boundary(content_type) {
+        for field in multipart::parse(body, boundary) {
+            if field.filename.is_some() {
+                files.push(field);
+            } else {
+                params.insert(field.name, json!(String::from_utf8_lossy(&field.data)));
+            }
+        }
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        params.extend(
+            form_urlencoded::parse(body).map(|(key, value)| (key.into_owned(), json!(value))),
+        );
+    } else if let Ok(Value::Object(fields)) = serde_json::from_slice(body) {
+        params.extend(fields);
+    }
+    (Params(params), files)
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn ok(mut payload: Value) -> Answer {
+    if let Some(fields) = payload.as_object_mut() {
+        fields.entry("status").or_insert_with(|| json!("success"));
+    }
+    (200, payload)
+}
+
+fn error(status: u16, message: &str) -> Answer {
+    (status, json!({ "status": "error", "message": message }))
+}
+
+fn not_found(path: &str) -> Reply {
+    json(
+        404,
+        &json!({ "message": format!("The route {} could not be found.", path.trim_start_matches('/')) }),
+    )
+}
+
+fn json(status: u16, value: &Value) -> Reply {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Served::full(Bytes::from(value.to_string())))
+        .unwrap()
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Everything the server starts with. Records are kept as JSON in the shape
+/// Ticketbase sends them; tickets carry `company_id` and `technician_id`
+/// instead of the nested company and are expanded when served.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fixtures {
+    /// Users with an extra `password` field that is never sent back.
+    pub users: Vec<Value>,
+    pub companies: Vec<Value>,
+    pub templates: Vec<Value>,
+    pub tickets: Vec<Value>,
+    pub histories: Vec<Value>,
+    pub todos: Vec<Value>,
+    pub messages: Vec<Value>,
+    pub attachments: Vec<StoredFile>,
+    pub wiki: Vec<Value>,
+    pub ratings: Vec<Value>,
+    pub top_users: Vec<Value>,
+    pub mail_settings: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoredFile {
+    pub ticket_id: u32,
+    pub filename: String,
+    /// Text content; uploads keep their raw bytes.
+    pub content: String,
+    #[serde(skip)]
+    pub bytes: Option<Vec<u8>>,
+}
+
+impl StoredFile {
+    pub fn data(&self) -> &[u8] {
+        self.bytes.as_deref().unwrap_or(self.content.as_bytes())
+    }
+}
+
+impl Fixtures {
+    /// The bundled data set: two technicians and a customer (all with the
+    /// password `secret`), a few tickets in different states and some wiki
+    /// articles.
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("../fixtures/default.json"))
+            .expect("bundled fixtures are valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_slice(&data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Timer of one user on one ticket, with the PLAY/PAUSE/RESUME/STOP codes
+/// the API uses.
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub play_status: u8,
+    /// Seconds booked before the current run.
+    pub seconds: i64,
+    pub running_since: Option<Instant>,
+}
+
+impl Player {
+    pub fn total_seconds(&self) -> i64 {
+        self.seconds
+            + self
+                .running_since
+                .map_or(0, |since| since.elapsed().as_secs() as i64)
+    }
+}
+
+/// The server's mutable state, behind one lock.
+pub struct Store {
+    pub data: Fixtures,
+    /// Bearer token to user id.
+    pub sessions: HashMap<String, u32>,
+    pub players: HashMap<(u32, u32), Player>,
+    pub active: HashMap<u32, bool>,
+    pub corrections: Vec<Value>,
+    /// Answers to writes by `Idempotency-Key`, so replays are not applied twice.
+    pub replies: HashMap<String, (u16, Value)>,
+}
+
+impl Store {
+    pub fn new(data: Fixtures) -> Self {
+        Self {
+            data,
+            sessions: HashMap::new(),
+            players: HashMap::new(),
+            active: HashMap::new(),
+            corrections: Vec::new(),
+            replies: HashMap::new(),
+        }
+    }
+
+    pub fn user(&self, id: u32) -> Option<&Value> {
+        self.data.users.iter().find(|user| id_of(user) == id)
+    }
+
+    pub fn user_mut(&mut self, id: u32) -> Option<&mut Value> {
+        self.data.users.iter_mut().find(|user| id_of(user) == id)
+    }
+
+    /// A user as the API shows it, without the password.
+    pub fn public_user(&self, id: u32) -> Value {
+        let mut user = self.user(id).cloned().unwrap_or(Value::Null);
+        if let Some(fields) = user.as_object_mut() {
+            fields.remove("password");
+        }
+        user
+    }
+
+    pub fn user_name(&self, id: u32) -> Option<String> {
+        Some(self.user(id)?["name"].as_str()?.to_string())
+    }
+
+    pub fn company(&self, id: u32) -> Option<&Value> {
+        self.data
+            .companies
+            .iter()
+            .find(|company| id_of(company) == id)
+    }
+
+    pub fn ticket(&self, id: u32) -> Option<&Value> {
+        self.data.tickets.iter().find(|ticket| id_of(ticket) == id)
+    }
+
+    pub fn ticket_mut(&mut self, id: u32) -> Option<&mut Value> {
+        self.data
+            .tickets
+            .iter_mut()
+            .find(|ticket| id_of(ticket) == id)
+    }
+
+    /// A stored ticket in the list shape of `/getTickets`.
+    pub fn ticket_view(&self, ticket: &Value, viewer: u32) -> Value {
+        let mut view = ticket.as_object().cloned().unwrap_or_default();
+        let id = id_of(ticket);
+        let company_id = view
+            .remove("company_id")
+            .and_then(|id| id.as_u64())
+            .unwrap_or(0) as u32;
+        view.remove("technician_id");
+
+        let mut company = self.company(company_id).cloned().unwrap_or(json!({}));
+        if let Some(fields) = company.as_object_mut() {
+            fields.remove("locations");
+        }
+        view.insert("company".to_string(), company);
+        view.insert(
+            "ticketMessagesCount".to_string(),
+            json!(self
+                .data
+                .messages
+                .iter()
+                .filter(|message| message["ticket_id"] == json!(id))
+                .count()),
+        );
+        if let Some(player) = self.players.get(&(id, viewer)) {
+            view.insert(
+                "playStatus".to_string(),
+                json!(player.play_status.to_string()),
+            );
+        }
+        Value::Object(view)
+    }
+
+    /// A stored ticket as the database row `/getTicketById` returns.
+    pub fn raw_ticket(&self, ticket: &Value) -> Value {
+        let company_id = ticket["company_id"].as_u64().unwrap_or(0) as u32;
+        let company = self.company(company_id).cloned().unwrap_or(json!({}));
+        json!({
+            "id": ticket["id"],
+            "description": ticket["description"],
+            "status": { "id": ticket["status_id"], "name": ticket["status"] },
+            "status_id": ticket["status_id"],
+            "summary": ticket["summary"],
+            "userone": { "id": 0, "name": ticket["ticketCreator"] },
+            "ticketuser": {
+                "id": 0,
+                "name": ticket["ticketUser"],
+                "phone": ticket["ticketUserPhone"],
+            },
+            "servicedetail": { "id": ticket["dyn_template_id"], "name": ticket["subject"] },
+            "priority": ticket["priority"],
+            "priority_index": ticket["index"],
+            "my_ticket_id": ticket["my_ticket_id"],
+            "location_id": ticket["location_id"],
+            "companyone": {
+                "id": company["id"],
+                "name": company["name"],
+                "number": company["number"],
+                "email": company["companyMail"],
+                "phone": company["companyPhone"],
+                "zip": company["companyZip"],
+                "address": company["companyAdress"],
+            },
+            "dyn_template_id": ticket["dyn_template_id"],
+            "created_at": ticket["created_at"],
+            "template_data": ticket["template_data"],
+        })
+    }
+
+    /// Sets a ticket's status by the ids the frontend knows.
+    pub fn set_status(&mut self, ticket_id: u32, status_id: u32) {
+        if let Some(ticket) = self.ticket_mut(ticket_id) {
+            ticket["status_id"] = json!(status_id);
+            ticket["status"] = json!(status_name(status_id));
+        }
+    }
+}
+
+pub fn status_name(status_id: u32) -> &'static str {
+    match status_id {
+        1 => "Neu",
+        2 => "Terminiert",
+        3 => "Prüfen",
+        4 => "Abgeschlossen",
+        5 => "Offen",
+        6 => "Vor Ort",
+        8 => "Wieder geöffnet",
+        9 => "Warten auf Rückmeldung",
+        11 => "Warten (Extern)",
+        13 => "In Bearbeitung",
+        _ => "Unbekannt",
+    }
+}
+
+/// The id a new record in `records` gets, like an auto-increment column.
+pub fn next_id(records: &[Value]) -> u32 {
+    records.iter().map(id_of).max().unwrap_or(0) + 1
+}
+
+pub fn id_of(record: &Value) -> u32 {
+    record["id"].as_u64().unwrap_or(0) as u32
+}
+
+/// Drops `keys` from a JSON object, for fields only the mock keeps.
+pub fn without(mut record: Value, keys: &[&str]) -> Value {
+    if let Some(fields) = record.as_object_mut() {
+        for key in keys {
+            fields.remove(*key);
+        }
+    }
+    record
+}
+
+/// Seconds since the epoch.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// The current UTC time as `YYYY-MM-DD HH:MM:SS`, like Laravel prints it.
+pub fn now() -> String {
+    let seconds = unix_now();
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
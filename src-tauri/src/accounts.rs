@@ -0,0 +1,459 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::api::models::User;
+use crate::api::ApiClient;
+use crate::cache::{self, OfflineCache};
+use crate::credentials::{self, CredentialStore, Session};
+use crate::outbox::Outbox;
+use crate::poller::{self, TicketPoller};
+use crate::profiles::{ProfileStore, ServerProfile, DEFAULT_PROFILE_ID};
+use crate::search::{self, SearchIndex};
+use crate::tempfiles::TempFiles;
+
+const ACCOUNTS_DIR: &str = "accounts";
+const INDEX_FILE: &str = "accounts.json";
+
+/// Which accounts are signed in, without their secrets.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccountIndex {
+    accounts: Vec<String>,
+    active: Option<String>,
+}
+
+/// One signed in user on one server, with everything that must not mix
+/// with other accounts: the API client and its token, the offline cache,
+/// the search index, the poller and the outbox.
+pub struct Account {
+    /// `<profile id>-<user id>`, also the name of its data directory.
+    pub id: String,
+    pub profile_id: String,
+    pub user: User,
+    pub api: ApiClient,
+    pub cache: OfflineCache,
+    pub index: SearchIndex,
+    pub poller: TicketPoller,
+    pub outbox: Outbox,
+}
+
+/// What the frontend needs to show an account. Tokens never leave Rust.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub id: String,
+    pub profile_id: String,
+    /// Name of the server profile.
+    pub server: String,
+    pub base_url: String,
+    pub user: User,
+    pub active: bool,
+    /// Changes in the outbox that signing out would throw away.
+    pub unsent: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsChanged {
+    pub active: Option<String>,
+}
+
+fn account_id(session: &Session) -> String {
+    format!(
+        "{}-{}",
+        session.profile_id.as_deref().unwrap_or(DEFAULT_PROFILE_ID),
+        session.user.id
+    )
+}
+
+/// All signed in accounts and the one the windows show. Each account polls
+/// in the background whether it is active or not.
+pub struct Accounts {
+    dir: PathBuf,
+    index_path: PathBuf,
+    accounts: RwLock<Vec<Arc<Account>>>,
+    active: RwLock<Option<String>>,
+    /// Listed in the index but not signed back in, e.g. while the keyring
+    /// was locked. Kept in the index, with their data, for the next start.
+    unrestored: RwLock<Vec<String>>,
+}
+
+impl Accounts {
+    pub fn new<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(data_dir.join(ACCOUNTS_DIR)).map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            dir: data_dir.join(ACCOUNTS_DIR),
+            index_path: config_dir.join(INDEX_FILE),
+            accounts: RwLock::new(Vec::new()),
+            active: RwLock::new(None),
+            unrestored: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Signs the accounts of the last run back in. Called once from `setup`.
+    pub fn restore<R: Runtime>(&self, app: &AppHandle<R>) {
+        let credentials = app.state::<CredentialStore>();
+        let index = match fs::read(&self.index_path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                log::warn!("Account list is unreadable: {}", e);
+                self.index_from_dirs()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.migrate_legacy(app),
+            Err(e) => {
+                log::warn!("Account list is unreadable: {}", e);
+                self.index_from_dirs()
+            }
+        };
+
+        for id in &index.accounts {
+            let restored = match credentials.load(id) {
+                Ok(Some(session)) => self.add(app, session).map(|_| ()),
+                Ok(None) => Err("no session stored".to_string()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = restored {
+                log::error!("Failed to restore account {}: {}", id, e);
+                self.unrestored.write().unwrap().push(id.clone());
+            }
+        }
+
+        let active = index
+            .active
+            .filter(|id| self.get(id).is_some())
+            .or_else(|| self.all().first().map(|account| account.id.clone()));
+        *self.active.write().unwrap() = active;
+        self.save_index();
+        self.sweep(&index.accounts);
+    }
+
+    /// An index of every data directory, when the one on disk is lost.
+    fn index_from_dirs(&self) -> AccountIndex {
+        let accounts = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|dir| dir.file_name().into_string().ok())
+            .collect();
+        AccountIndex {
+            accounts,
+            active: None,
+        }
+    }
+
+    /// Turns the single session of earlier versions into the first account
+    /// and moves its cache along, so nobody has to sign in again.
+    fn migrate_legacy<R: Runtime>(&self, app: &AppHandle<R>) -> AccountIndex {
+        let credentials = app.state::<CredentialStore>();
+        let Ok(Some(session)) = credentials.load_legacy() else {
+            return AccountIndex::default();
+        };
+
+        let id = account_id(&session);
+        let data_dir = self.dir.parent().unwrap_or(&self.dir);
+        let account_dir = self.dir.join(&id);
+        let _ = fs::create_dir_all(&account_dir);
+        for name in [
+            cache::DATABASE_FILE.to_string(),
+            format!("{}-wal", cache::DATABASE_FILE),
+            format!("{}-shm", cache::DATABASE_FILE),
+            search::INDEX_DIR.to_string(),
+        ] {
+            let _ = fs::rename(data_dir.join(&name), account_dir.join(&name));
+        }
+
+        if let Err(e) = credentials.store(&id, &session) {
+            log::error!("Failed to migrate session: {}", e);
+            return AccountIndex::default();
+        }
+        let _ = credentials.clear_legacy();
+        AccountIndex {
+            accounts: vec![id.clone()],
+            active: Some(id),
+        }
+    }
+
+    fn save_index(&self) {
+        let mut accounts: Vec<String> = self
+            .all()
+            .iter()
+            .map(|account| account.id.clone())
+            .collect();
+        for id in self.unrestored.read().unwrap().iter() {
+            if !accounts.contains(id) {
+                accounts.push(id.clone());
+            }
+        }
+        let index = AccountIndex {
+            accounts,
+            active: self.active.read().unwrap().clone(),
+        };
+        let result = serde_json::to_vec_pretty(&index)
+            .map_err(|e| e.to_string())
+            .and_then(|json| credentials::write_private(&self.index_path, &json));
+
+        if let Err(e) = result {
+            log::error!("Failed to write account list: {}", e);
+        }
+    }
+
+    /// Data directories of accounts that are gone, e.g. because a database
+    /// was still open when they were removed. Only those the index on disk
+    /// does not list: an account that failed to load keeps its cache and
+    /// unsent outbox.
+    fn sweep(&self, listed: &[String]) {
+        let Ok(dirs) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for dir in dirs.flatten() {
+            let name = dir.file_name();
+            if name
+                .to_str()
+                .is_some_and(|id| !listed.iter().any(|listed| listed == id))
+            {
+                let _ = fs::remove_dir_all(dir.path());
+            }
+        }
+    }
+
+    pub fn all(&self) -> Vec<Arc<Account>> {
+        self.accounts.read().unwrap().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<Account>> {
+        self.accounts
+            .read()
+            .unwrap()
+            .iter()
+            .find(|account| account.id == id)
+            .cloned()
+    }
+
+    pub fn active(&self) -> Result<Arc<Account>, String> {
+        self.active
+            .read()
+            .unwrap()
+            .as_deref()
+            .and_then(|id| self.get(id))
+            .ok_or_else(|| "Not logged in".to_string())
+    }
+
+    pub fn is_active(&self, id: &str) -> bool {
+        self.active.read().unwrap().as_deref() == Some(id)
+    }
+
+    /// "Jane Doe (Staging)", to tell notifications of background accounts apart.
+    pub fn label<R: Runtime>(&self, app: &AppHandle<R>, account: &Account) -> String {
+        match app.state::<ProfileStore>().profile(&account.profile_id) {
+            Some(profile) => format!("{} ({})", account.user.name, profile.name),
+            None => account.user.name.clone(),
+        }
+    }
+
+    pub fn info<R: Runtime>(&self, app: &AppHandle<R>, account: &Account) -> AccountInfo {
+        AccountInfo {
+            id: account.id.clone(),
+            profile_id: account.profile_id.clone(),
+            server: app
+                .state::<ProfileStore>()
+                .profile(&account.profile_id)
+                .map(|profile| profile.name)
+                .unwrap_or_default(),
+            base_url: account.api.base_url(),
+            user: account.user.clone(),
+            active: self.is_active(&account.id),
+            unsent: account
+                .cache
+                .pending_mutations(account.user.id)
+                .map(|entries| entries.len())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Stores the session and starts the account, or hands a new token to
+    /// the account if it is signed in already.
+    pub fn add<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        session: Session,
+    ) -> Result<Arc<Account>, String> {
+        let id = account_id(&session);
+        app.state::<CredentialStore>().store(&id, &session)?;
+
+        if let Some(account) = self.get(&id) {
+            account.api.set_token(Some(session.token));
+            return Ok(account);
+        }
+
+        let profile_id = session
+            .profile_id
+            .unwrap_or_else(|| DEFAULT_PROFILE_ID.to_string());
+        let api = app.state::<ProfileStore>().client(app, &profile_id)?;
+        api.set_token(Some(session.token));
+
+        let dir = self.dir.join(&id);
+        let poller = TicketPoller::default();
+        poller.set_user(Some(&session.user));
+        if let Some(other) = self.all().first() {
+            poller.inherit(&other.poller);
+        }
+        let account = Arc::new(Account {
+            cache: OfflineCache::open(&dir)?,
+            index: SearchIndex::open(&dir)?,
+            id,
+            profile_id,
+            user: session.user,
+            api,
+            poller,
+            outbox: Outbox::default(),
+        });
+
+        self.accounts.write().unwrap().push(account.clone());
+        self.save_index();
+        poller::spawn(app.clone(), account.clone());
+        crate::api::spawn_throttle_events(app.clone(), &account);
+        Ok(account)
+    }
+
+    /// Shows `id` in all windows from now on. Ticket windows belong to the
+    /// account they were opened for, so they are closed.
+    pub fn activate<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+    ) -> Result<Arc<Account>, String> {
+        let account = self
+            .get(id)
+            .ok_or_else(|| format!("Unknown account: {}", id))?;
+        if self.is_active(id) {
+            return Ok(account);
+        }
+
+        *self.active.write().unwrap() = Some(id.to_string());
+        self.save_index();
+        for (label, window) in app.webview_windows() {
+            if label.starts_with("ticket-") {
+                let _ = window.close();
+            }
+        }
+        let _ = app.emit(
+            "accounts://changed",
+            AccountsChanged {
+                active: Some(id.to_string()),
+            },
+        );
+        Ok(account)
+    }
+
+    /// Signs the account out and deletes what was cached for it. If it was
+    /// the active one, the next account takes over. Changes still in the
+    /// outbox would go too, so that takes `discard_unsent`.
+    pub fn remove<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        discard_unsent: bool,
+    ) -> Result<(), String> {
+        let Some(account) = self.get(id) else {
+            return Ok(());
+        };
+        let unsent = !account.cache.pending_mutations(account.user.id)?.is_empty();
+        if unsent && !discard_unsent {
+            return Err(format!(
+                "Changes of {} have not been sent yet",
+                account.user.name
+            ));
+        }
+
+        account.poller.stop();
+        account.api.logout();
+        account.cache.clear()?;
+        account.index.clear()?;
+        app.state::<CredentialStore>().clear(id)?;
+        self.accounts.write().unwrap().retain(|a| a.id != id);
+        drop(account);
+        // Fails while a request still holds the database, `sweep` gets it later
+        let _ = fs::remove_dir_all(self.dir.join(id));
+        if let Some(temp_files) = app.try_state::<TempFiles>() {
+            let _ = fs::remove_dir_all(temp_files.partial_dir().join(id));
+        }
+
+        if self.is_active(id) {
+            let next = self.all().first().map(|account| account.id.clone());
+            *self.active.write().unwrap() = next.clone();
+            let _ = app.emit("accounts://changed", AccountsChanged { active: next });
+        }
+        self.save_index();
+        Ok(())
+    }
+
+    /// Applies a changed server profile to the accounts that use it.
+    pub fn reconfigure<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        profile: &ServerProfile,
+    ) -> Result<(), String> {
+        for account in self.all() {
+            if account.profile_id == profile.id {
+                ProfileStore::configure(app, &account.api, profile)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds every account's HTTP client, after the network settings changed.
+    pub fn reconnect<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        let profiles = app.state::<ProfileStore>();
+        for account in self.all() {
+            if let Some(profile) = profiles.profile(&account.profile_id) {
+                ProfileStore::configure(app, &account.api, &profile)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn uses_profile(&self, profile_id: &str) -> bool {
+        self.all()
+            .iter()
+            .any(|account| account.profile_id == profile_id)
+    }
+}
+
+#[tauri::command]
+pub fn get_accounts<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+) -> Vec<AccountInfo> {
+    accounts
+        .all()
+        .iter()
+        .map(|account| accounts.info(&app, account))
+        .collect()
+}
+
+/// Switches all windows to another signed in account without signing out.
+#[tauri::command]
+pub fn switch_account<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    id: String,
+) -> Result<AccountInfo, String> {
+    let account = accounts.activate(&app, &id)?;
+    Ok(accounts.info(&app, &account))
+}
+
+#[tauri::command]
+pub fn remove_account<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    id: String,
+    discard_unsent: bool,
+) -> Result<(), String> {
+    accounts.remove(&app, &id, discard_unsent)
+}
@@ -0,0 +1,663 @@
+use serde_json::Value;
+use tauri::{AppHandle, Runtime, State};
+
+use super::models::*;
+use super::ThrottleState;
+use crate::accounts::{AccountInfo, Accounts};
+use crate::credentials::Session;
+use crate::profiles::ProfileStore;
+use crate::timer::{self, TimerAction};
+
+// One command per Ticketbase endpoint. Errors are stringified so the
+// frontend sees the same "HTTP error! status: ..." messages as before.
+// Successful reads are also written to the offline cache and search index.
+
+/// Signs in to `profile_id`, or the login screen's server, and makes the
+/// account the active one. The token goes straight to the credential store.
+#[tauri::command]
+pub async fn login<R: Runtime>(
+    app: AppHandle<R>,
+    profiles: State<'_, ProfileStore>,
+    accounts: State<'_, Accounts>,
+    email: String,
+    password: String,
+    profile_id: Option<String>,
+) -> Result<AccountInfo, String> {
+    let profile_id = profile_id.unwrap_or_else(|| profiles.profiles().active);
+    let api = profiles.client(&app, &profile_id)?;
+    let response = api
+        .login(&email, &password)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status != "success" || response.authorisation.token.is_empty() {
+        return Err("Login failed".to_string());
+    }
+
+    let account = accounts.add(
+        &app,
+        Session {
+            token: response.authorisation.token,
+            user: response.user,
+            profile_id: Some(profile_id),
+        },
+    )?;
+    accounts.activate(&app, &account.id)?;
+    Ok(accounts.info(&app, &account))
+}
+
+#[tauri::command]
+pub fn get_throttle_state(accounts: State<'_, Accounts>) -> ThrottleState {
+    accounts
+        .active()
+        .map(|account| account.api.throttle_state())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_tickets(
+    accounts: State<'_, Accounts>,
+    filter: TicketFilter,
+) -> Result<TicketsResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_tickets(&filter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tickets_unfiltered(
+    accounts: State<'_, Accounts>,
+    filter: TicketFilter,
+) -> Result<TicketsResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_tickets_unfiltered(&filter)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    account.index.index_tickets(
+        response
+            .new_tickets
+            .iter()
+            .chain(&response.my_tickets)
+            .chain(&response.all_tickets),
+    );
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_tickets_today(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    datum: String,
+) -> Result<ApiResponse<TodayTicketsPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_tickets_today(user_id, &datum)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_ticket_data(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<ApiResponse<TicketDataPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_ticket_data(ticket_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status == "success" {
+        account
+            .cache
+            .store_histories(ticket_id, &response.payload.ticket_data);
+        account
+            .index
+            .index_histories(ticket_id, &response.payload.ticket_data);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_ticket_by_id(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<Ticket>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_ticket_by_id(ticket_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ticket = response.payload.tickets.map(Ticket::from);
+    if let Some(ticket) = &ticket {
+        account.cache.store_ticket(ticket);
+        account.index.index_tickets([ticket]);
+    }
+    Ok(ticket)
+}
+
+#[tauri::command]
+pub async fn create_ticket_json(
+    accounts: State<'_, Accounts>,
+    ticket: NewTicket,
+) -> Result<ApiResponse<Value>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .create_ticket(&ticket)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ticket_terminieren(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    date: String,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .ticket_terminieren(ticket_id, user_id, &date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ticket_terminieren_api(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    ticket_start: String,
+    mode: Option<u8>,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .ticket_terminieren_api(ticket_id, user_id, &ticket_start, mode.unwrap_or(1))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_ticket_history<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    entry: HistoryEntry,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .save_ticket_history(&entry)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" {
+        timer::history_saved(&app, &account, &entry);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn correct_watch(
+    accounts: State<'_, Accounts>,
+    correction: WatchCorrection,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .correct_watch(&correction)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_user_status(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+) -> Result<ApiResponse<Data<ActivityData>>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_user_status(user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn change_user_status(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    kind: u8,
+) -> Result<ApiResponse<Data<ActivityData>>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .change_user_status(user_id, kind)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_templates(
+    accounts: State<'_, Accounts>,
+    company_id: Option<u32>,
+) -> Result<ApiResponse<Data<TemplatesData>>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_templates(company_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_customers(
+    accounts: State<'_, Accounts>,
+) -> Result<ApiResponse<CustomersPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_customers()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status == "success" {
+        account.cache.store_companies(&response.payload.customers);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_customer_locations(
+    accounts: State<'_, Accounts>,
+    customer_id: u32,
+) -> Result<ApiResponse<Data<LocationsData>>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_customer_locations(customer_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(data) = &response.payload.data {
+        account.cache.store_locations(customer_id, &data.locations);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_location_users(
+    accounts: State<'_, Accounts>,
+    location_id: u32,
+) -> Result<ApiResponse<Data<UsersData>>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_location_users(location_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(data) = &response.payload.data {
+        account.cache.store_users(&data.users);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_check_list(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<ApiResponse<CheckListPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_check_list(ticket_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status == "success" {
+        account
+            .cache
+            .store_todos(ticket_id, &response.payload.check_list);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn new_todo(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    todo: String,
+) -> Result<ApiResponse<CheckListPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .new_todo(ticket_id, user_id, &todo)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_todo(
+    accounts: State<'_, Accounts>,
+    todo_id: u32,
+    kind: u8,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .check_todo(todo_id, kind)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Timer changes of the signed in user are also kept by the time tracker.
+#[tauri::command]
+pub async fn play<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .play(ticket_id, user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" && user_id == account.user.id {
+        timer::record(&app, &account, ticket_id, TimerAction::Play);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn pause<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    current_state: Option<u8>,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .pause(ticket_id, user_id, current_state.unwrap_or(1))
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" && user_id == account.user.id {
+        timer::record(&app, &account, ticket_id, TimerAction::Pause);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn resume<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    current_state: Option<u8>,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .resume(ticket_id, user_id, current_state.unwrap_or(2))
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" && user_id == account.user.id {
+        timer::record(&app, &account, ticket_id, TimerAction::Resume);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn stop<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .stop(ticket_id, user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" && user_id == account.user.id {
+        timer::record(&app, &account, ticket_id, TimerAction::Stop);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_player_status<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+) -> Result<ApiResponse<PlayerStatusPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_player_status(ticket_id, user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status == "success" && user_id == account.user.id {
+        timer::reconcile(
+            &app,
+            &account,
+            ticket_id,
+            response.payload.player_status.as_ref(),
+        );
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn edit_profile(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    name: String,
+    phone: String,
+) -> Result<ApiResponse<Data<ProfileData>>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .edit_profile(user_id, &name, &phone)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn change_password(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    new_password: String,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .change_password(user_id, &new_password)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_users_mail_settings(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+) -> Result<ApiResponse<Data<MailSettingsData>>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_users_mail_settings(user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn user_mail_settings(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    value: u8,
+    kind: u8,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .user_mail_settings(user_id, value, kind)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_ticket_messages(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<ApiResponse<MessagesPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_ticket_messages(ticket_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status == "success" {
+        account
+            .cache
+            .store_messages(ticket_id, &response.payload.messages);
+        account
+            .index
+            .index_messages(ticket_id, &response.payload.messages);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn send_message(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    message: String,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .send_message(ticket_id, user_id, &message)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_wiki_data(
+    accounts: State<'_, Accounts>,
+) -> Result<ApiResponse<WikiPayload>, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_wiki_data()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status == "success" {
+        account.index.index_wiki(&response.payload.wiki_data);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_closed_confirmed_tickets(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+) -> Result<ApiResponse<ClosedTicketsPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_closed_confirmed_tickets(user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rate_ticket(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+    user_id: u32,
+    rating: u8,
+    feedback: Option<String>,
+) -> Result<ApiResponse, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .rate_ticket(ticket_id, user_id, rating, feedback.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_report4(
+    accounts: State<'_, Accounts>,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<ReportPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_report4(&start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_report5(
+    accounts: State<'_, Accounts>,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<ReportPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_report5(&start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_top_users(
+    accounts: State<'_, Accounts>,
+    month: u32,
+) -> Result<ApiResponse<TopUsersPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_top_users(month)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_correct_watch_histories_for_period(
+    accounts: State<'_, Accounts>,
+    user_id: u32,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<HistoriesPayload>, String> {
+    let account = accounts.active()?;
+    account
+        .api
+        .get_correct_watch_histories_for_period(user_id, &start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
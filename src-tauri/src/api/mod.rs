@@ -0,0 +1,713 @@
+pub mod commands;
+pub mod models;
+mod scheduler;
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri_plugin_http::reqwest::{self, multipart, Method};
+
+use models::*;
+
+use crate::accounts::{Account, Accounts};
+use scheduler::Scheduler;
+pub use scheduler::{RequestKind, ThrottleState};
+
+pub const DEFAULT_BASE_URL: &str = "https://itm.ticketbase.net/api";
+
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// The server answered with a non 2xx status.
+    Http { status: u16, body: String },
+    /// The request never got an answer (DNS, TLS, connection reset, ...).
+    Network(String),
+    /// The body was not the JSON we expected.
+    Decode(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Same wording as the TypeScript client so existing checks keep working
+            ApiError::Http { status, body } if body.is_empty() => {
+                write!(f, "HTTP error! status: {}", status)
+            }
+            ApiError::Http { status, body } => {
+                write!(f, "HTTP error! status: {} - {}", status, body)
+            }
+            ApiError::Network(e) => write!(f, "Network error: {}", e),
+            ApiError::Decode(e) => write!(f, "Invalid response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            ApiError::Decode(e.to_string())
+        } else {
+            ApiError::Network(e.to_string())
+        }
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Typed client for the Ticketbase REST API. Each signed in account has
+/// its own, shared by its commands and background tasks.
+pub struct ApiClient {
+    http: RwLock<reqwest::Client>,
+    base_url: RwLock<String>,
+    token: RwLock<Option<String>>,
+    scheduler: Scheduler,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            http: RwLock::new(reqwest::Client::new()),
+            base_url: RwLock::new(base_url.trim_end_matches('/').to_string()),
+            token: RwLock::new(None),
+            scheduler: Scheduler::default(),
+        }
+    }
+
+    pub fn base_url(&self) -> String {
+        self.base_url.read().unwrap().clone()
+    }
+
+    pub fn set_base_url(&self, url: &str) {
+        *self.base_url.write().unwrap() = url.trim_end_matches('/').to_string();
+    }
+
+    fn http(&self) -> reqwest::Client {
+        self.http.read().unwrap().clone()
+    }
+
+    /// Replaces the HTTP client, e.g. with one that trusts a private CA or
+    /// goes through a proxy. Requests in flight finish on the old one.
+    pub fn set_http_client(&self, client: reqwest::Client) {
+        *self.http.write().unwrap() = client;
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.token.read().unwrap().clone()
+    }
+
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.write().unwrap() = token;
+    }
+
+    pub fn throttle_state(&self) -> ThrottleState {
+        self.scheduler.throttle_state()
+    }
+
+    /// Changes whenever the scheduler starts or stops holding requests back.
+    pub fn subscribe_throttle(&self) -> tokio::sync::watch::Receiver<ThrottleState> {
+        self.scheduler.subscribe()
+    }
+
+    fn builder(&self, method: Method, endpoint: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}{}", self.base_url(), endpoint);
+        let mut builder = self
+            .http()
+            .request(method, url)
+            .header(reqwest::header::ACCEPT, "application/json");
+
+        if let Some(token) = self.token() {
+            builder = builder.bearer_auth(token);
+        }
+
+        builder
+    }
+
+    async fn send(
+        &self,
+        builder: reqwest::RequestBuilder,
+        kind: RequestKind,
+    ) -> ApiResult<Vec<u8>> {
+        let request = builder.build()?;
+        self.scheduler.execute(&self.http(), request, kind).await
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> ApiResult<T> {
+        serde_json::from_slice(bytes).map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+    ) -> ApiResult<T> {
+        let bytes = self
+            .send(
+                self.builder(Method::GET, endpoint).query(query),
+                RequestKind::Read,
+            )
+            .await?;
+        Self::decode(&bytes)
+    }
+
+    /// POST for endpoints that only read; Ticketbase uses POST for most queries.
+    pub async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> ApiResult<T> {
+        let bytes = self
+            .send(
+                self.builder(Method::POST, endpoint).json(body),
+                RequestKind::Read,
+            )
+            .await?;
+        Self::decode(&bytes)
+    }
+
+    /// POST that changes server state: never merged with other calls and only
+    /// retried when the server rejected it with 429.
+    pub async fn submit<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> ApiResult<T> {
+        let bytes = self
+            .send(
+                self.builder(Method::POST, endpoint).json(body),
+                RequestKind::Write,
+            )
+            .await?;
+        Self::decode(&bytes)
+    }
+
+    /// `submit` tagged with an `Idempotency-Key`, so a write replayed from the
+    /// outbox can be recognised as the same one.
+    pub async fn submit_with_key<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        endpoint: &str,
+        body: &B,
+        idempotency_key: &str,
+    ) -> ApiResult<T> {
+        let bytes = self
+            .send(
+                self.builder(Method::POST, endpoint)
+                    .header("Idempotency-Key", idempotency_key)
+                    .json(body),
+                RequestKind::Write,
+            )
+            .await?;
+        Self::decode(&bytes)
+    }
+
+    // Authentication
+
+    pub async fn login(&self, email: &str, password: &str) -> ApiResult<LoginResponse> {
+        let response: LoginResponse = self
+            .submit("/login", &json!({ "email": email, "password": password }))
+            .await?;
+
+        if response.status == "success" && !response.authorisation.token.is_empty() {
+            self.set_token(Some(response.authorisation.token.clone()));
+        }
+
+        Ok(response)
+    }
+
+    pub fn logout(&self) {
+        self.set_token(None);
+    }
+
+    // Tickets
+
+    pub async fn get_tickets(&self, filter: &TicketFilter) -> ApiResult<TicketsResponse> {
+        self.post("/getTickets", filter).await
+    }
+
+    /// All tickets without pool filtering (used for search).
+    pub async fn get_tickets_unfiltered(
+        &self,
+        filter: &TicketFilter,
+    ) -> ApiResult<TicketsResponse> {
+        self.post("/testGetTickets", filter).await
+    }
+
+    pub async fn get_tickets_today(
+        &self,
+        user_id: u32,
+        datum: &str,
+    ) -> ApiResult<ApiResponse<TodayTicketsPayload>> {
+        self.post(
+            "/getTicketsToday",
+            &json!({ "user_id": user_id, "datum": datum }),
+        )
+        .await
+    }
+
+    pub async fn get_ticket_data(
+        &self,
+        ticket_id: u32,
+    ) -> ApiResult<ApiResponse<TicketDataPayload>> {
+        self.post("/getTicketData", &json!({ "ticket_id": ticket_id }))
+            .await
+    }
+
+    pub async fn get_ticket_by_id(
+        &self,
+        ticket_id: u32,
+    ) -> ApiResult<ApiResponse<TicketByIdPayload>> {
+        self.get("/getTicketById", &[("ticket_id", ticket_id.to_string())])
+            .await
+    }
+
+    pub async fn create_ticket(&self, ticket: &NewTicket) -> ApiResult<ApiResponse<Value>> {
+        self.submit("/createTicket", ticket).await
+    }
+
+    /// `/createTicket` as `multipart/form-data`, every file under the
+    /// `ticket_image` key like the web form sends them. The parts are
+    /// streamed, so the request cannot be resent.
+    pub async fn create_ticket_with_attachments(
+        &self,
+        ticket: &NewTicket,
+        attachments: Vec<multipart::Part>,
+    ) -> ApiResult<ApiResponse<Value>> {
+        let mut form = multipart::Form::new()
+            .text("user_id", ticket.user_id.to_string())
+            .text("description", ticket.description.clone())
+            .text("priority", ticket.priority.clone())
+            .text("company_id", ticket.company_id.to_string())
+            .text("location_id", ticket.location_id.to_string())
+            .text("for_user_id", ticket.for_user_id.to_string());
+        if let Some(template_id) = ticket.dyn_template_id {
+            form = form.text("dyn_template_id", template_id.to_string());
+        }
+        for part in attachments {
+            form = form.part("ticket_image", part);
+        }
+
+        let request = self
+            .builder(Method::POST, "/createTicket")
+            .multipart(form)
+            .build()?;
+        let response = self
+            .scheduler
+            .execute_streaming(&self.http(), request)
+            .await?;
+        Self::decode(&response.bytes().await?)
+    }
+
+    pub async fn ticket_terminieren(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        date: &str,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/TicketTerminieren",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id, "date": date }),
+        )
+        .await
+    }
+
+    pub async fn ticket_terminieren_api(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        ticket_start: &str,
+        mode: u8,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/ticketTerminierenApi",
+            &json!({
+                "ticket_id": ticket_id,
+                "user_id": user_id,
+                "ticket_start": ticket_start,
+                "mode": mode,
+            }),
+        )
+        .await
+    }
+
+    pub async fn save_ticket_history(&self, entry: &HistoryEntry) -> ApiResult<ApiResponse> {
+        self.submit("/saveVerlaufApi", entry).await
+    }
+
+    pub async fn correct_watch(&self, correction: &WatchCorrection) -> ApiResult<ApiResponse> {
+        self.submit("/correctWatch", correction).await
+    }
+
+    // User Status
+
+    pub async fn get_user_status(
+        &self,
+        user_id: u32,
+    ) -> ApiResult<ApiResponse<Data<ActivityData>>> {
+        self.post("/getUserStatus", &json!({ "user_id": user_id }))
+            .await
+    }
+
+    pub async fn change_user_status(
+        &self,
+        user_id: u32,
+        kind: u8,
+    ) -> ApiResult<ApiResponse<Data<ActivityData>>> {
+        self.submit(
+            "/changeUserStatus",
+            &json!({ "user_id": user_id, "type": kind }),
+        )
+        .await
+    }
+
+    // Templates and Data
+
+    pub async fn get_templates(
+        &self,
+        company_id: Option<u32>,
+    ) -> ApiResult<ApiResponse<Data<TemplatesData>>> {
+        let endpoint = match company_id {
+            Some(id) => format!("/getTemplates/{}", id),
+            None => "/getTemplates".to_string(),
+        };
+        self.post(&endpoint, &json!({ "company_id": company_id }))
+            .await
+    }
+
+    pub async fn get_customers(&self) -> ApiResult<ApiResponse<CustomersPayload>> {
+        self.post("/getCustomers", &json!({})).await
+    }
+
+    pub async fn get_customer_locations(
+        &self,
+        customer_id: u32,
+    ) -> ApiResult<ApiResponse<Data<LocationsData>>> {
+        self.post(
+            "/getCustomerLocations",
+            &json!({ "customer_id": customer_id }),
+        )
+        .await
+    }
+
+    pub async fn get_location_users(
+        &self,
+        location_id: u32,
+    ) -> ApiResult<ApiResponse<Data<UsersData>>> {
+        self.post("/getLocationUsers", &json!({ "location_id": location_id }))
+            .await
+    }
+
+    // Todo List
+
+    pub async fn get_check_list(&self, ticket_id: u32) -> ApiResult<ApiResponse<CheckListPayload>> {
+        self.post("/getCheckList", &json!({ "ticket_id": ticket_id }))
+            .await
+    }
+
+    pub async fn new_todo(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        todo: &str,
+    ) -> ApiResult<ApiResponse<CheckListPayload>> {
+        self.submit(
+            "/newTodo",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id, "todo": todo }),
+        )
+        .await
+    }
+
+    pub async fn check_todo(&self, todo_id: u32, kind: u8) -> ApiResult<ApiResponse> {
+        self.submit("/checkTodo", &json!({ "todo_id": todo_id, "type": kind }))
+            .await
+    }
+
+    // Ticket Player Controls
+
+    pub async fn play(&self, ticket_id: u32, user_id: u32) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/play",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id }),
+        )
+        .await
+    }
+
+    /// `current_state` is 1 when pausing from PLAY and 3 from RESUME.
+    pub async fn pause(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        current_state: u8,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/pause",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id, "current_state": current_state }),
+        )
+        .await
+    }
+
+    /// `current_state` is 2 (PAUSE) when resuming.
+    pub async fn resume(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        current_state: u8,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/resume",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id, "current_state": current_state }),
+        )
+        .await
+    }
+
+    pub async fn stop(&self, ticket_id: u32, user_id: u32) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/stop",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id }),
+        )
+        .await
+    }
+
+    pub async fn get_player_status(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+    ) -> ApiResult<ApiResponse<PlayerStatusPayload>> {
+        self.post(
+            "/getPlayerStatus",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id }),
+        )
+        .await
+    }
+
+    // Profile Management
+
+    pub async fn edit_profile(
+        &self,
+        user_id: u32,
+        name: &str,
+        phone: &str,
+    ) -> ApiResult<ApiResponse<Data<ProfileData>>> {
+        self.submit(
+            "/editProfile",
+            &json!({ "user_id": user_id, "name": name, "phone": phone }),
+        )
+        .await
+    }
+
+    pub async fn change_password(
+        &self,
+        user_id: u32,
+        new_password: &str,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/changePassword",
+            &json!({ "user_id": user_id, "new_password": new_password }),
+        )
+        .await
+    }
+
+    // Mail Settings
+
+    pub async fn get_users_mail_settings(
+        &self,
+        user_id: u32,
+    ) -> ApiResult<ApiResponse<Data<MailSettingsData>>> {
+        self.post("/getUsersMailSettings", &json!({ "user_id": user_id }))
+            .await
+    }
+
+    pub async fn user_mail_settings(
+        &self,
+        user_id: u32,
+        value: u8,
+        kind: u8,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/userMailSettings",
+            &json!({ "user_id": user_id, "value": value, "type": kind }),
+        )
+        .await
+    }
+
+    // Ticket Messaging
+
+    pub async fn get_ticket_messages(
+        &self,
+        ticket_id: u32,
+    ) -> ApiResult<ApiResponse<MessagesPayload>> {
+        self.get(
+            "/getTicketMessages",
+            &[("ticket_id", ticket_id.to_string())],
+        )
+        .await
+    }
+
+    pub async fn send_message(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        message: &str,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/sendMessage",
+            &json!({ "ticket_id": ticket_id, "user_id": user_id, "message": message }),
+        )
+        .await
+    }
+
+    // Attachments
+
+    /// Opens `/getDataUrl` as a stream. With an `offset` only the rest is
+    /// asked for, and `if_range` makes the server send the whole file again
+    /// if it changed since the part we have.
+    pub async fn download_attachment(
+        &self,
+        ticket_id: u32,
+        filename: &str,
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> ApiResult<reqwest::Response> {
+        let mut builder = self
+            .builder(Method::POST, "/getDataUrl")
+            .json(&json!({ "ticket_id": ticket_id, "filename": filename }));
+
+        if offset > 0 {
+            builder = builder.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+            if let Some(validator) = if_range {
+                builder = builder.header(reqwest::header::IF_RANGE, validator);
+            }
+        }
+
+        let mut request = builder.build()?;
+        request.headers_mut().insert(
+            reqwest::header::ACCEPT,
+            reqwest::header::HeaderValue::from_static("application/octet-stream"),
+        );
+        self.scheduler
+            .execute_streaming(&self.http(), request)
+            .await
+    }
+
+    // Wiki / Knowledge Base
+
+    pub async fn get_wiki_data(&self) -> ApiResult<ApiResponse<WikiPayload>> {
+        self.get("/getWikiData", &[]).await
+    }
+
+    // Ticket Rating
+
+    pub async fn get_closed_confirmed_tickets(
+        &self,
+        user_id: u32,
+    ) -> ApiResult<ApiResponse<ClosedTicketsPayload>> {
+        self.get(
+            "/getClosedConfirmedTickets",
+            &[("user_id", user_id.to_string())],
+        )
+        .await
+    }
+
+    pub async fn rate_ticket(
+        &self,
+        ticket_id: u32,
+        user_id: u32,
+        rating: u8,
+        feedback: Option<&str>,
+    ) -> ApiResult<ApiResponse> {
+        self.submit(
+            "/rateTicket",
+            &json!({
+                "ticket_id": ticket_id,
+                "user_id": user_id,
+                "rating": rating,
+                "feedback": feedback,
+            }),
+        )
+        .await
+    }
+
+    // Reports
+
+    pub async fn get_report4(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> ApiResult<ApiResponse<ReportPayload>> {
+        self.get(
+            "/Report4",
+            &[
+                ("start_date", start_date.to_string()),
+                ("end_date", end_date.to_string()),
+            ],
+        )
+        .await
+    }
+
+    pub async fn get_report5(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> ApiResult<ApiResponse<ReportPayload>> {
+        self.get(
+            "/Report5",
+            &[
+                ("start_date", start_date.to_string()),
+                ("end_date", end_date.to_string()),
+            ],
+        )
+        .await
+    }
+
+    pub async fn get_top_users(&self, month: u32) -> ApiResult<ApiResponse<TopUsersPayload>> {
+        self.get("/getTopUsers", &[("month", month.to_string())])
+            .await
+    }
+
+    // Time Tracking History
+
+    pub async fn get_correct_watch_histories_for_period(
+        &self,
+        user_id: u32,
+        start_date: &str,
+        end_date: &str,
+    ) -> ApiResult<ApiResponse<HistoriesPayload>> {
+        self.get(
+            "/getCorrectWatchHistoriesForPeriod",
+            &[
+                ("user_id", user_id.to_string()),
+                ("start_date", start_date.to_string()),
+                ("end_date", end_date.to_string()),
+            ],
+        )
+        .await
+    }
+}
+
+/// Forwards the account's throttle state to every window as `api://throttle`
+/// while it is the active account. Ends when the account is removed.
+pub fn spawn_throttle_events<R: tauri::Runtime>(app: tauri::AppHandle<R>, account: &Arc<Account>) {
+    use tauri::{Emitter, Manager};
+
+    let mut throttle = account.api.subscribe_throttle();
+    let account = Arc::downgrade(account);
+    tauri::async_runtime::spawn(async move {
+        while throttle.changed().await.is_ok() {
+            let state = throttle.borrow_and_update().clone();
+            let Some(account) = account.upgrade() else {
+                break;
+            };
+            if app.state::<Accounts>().is_active(&account.id) {
+                let _ = app.emit("api://throttle", state);
+            }
+        }
+    });
+}
@@ -0,0 +1,504 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Field names follow the Ticketbase JSON exactly (see src/types/api.ts), so
+// the structs serialize back into the shape the frontend already expects.
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Role {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
+    pub email: String,
+    pub firstname: Option<String>,
+    pub surname: Option<String>,
+    pub phone: Option<String>,
+    pub company_id: u32,
+    pub user_group_id: u32,
+    pub sub_user_group_id: Option<u32>,
+    pub location_id: Option<u32>,
+    pub profile_photo_url: Option<String>,
+    pub role: Option<Role>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Authorisation {
+    pub token: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoginResponse {
+    pub status: String,
+    pub user: User,
+    pub authorisation: Authorisation,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompanyLocation {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Company {
+    pub id: u32,
+    pub name: String,
+    pub number: Option<String>,
+    #[serde(rename = "companyMail")]
+    pub company_mail: Option<String>,
+    #[serde(rename = "companyPhone")]
+    pub company_phone: Option<String>,
+    #[serde(rename = "companyZip")]
+    pub company_zip: Option<String>,
+    #[serde(rename = "companyAdress")]
+    pub company_adress: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locations: Option<Vec<CompanyLocation>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Location {
+    pub id: u32,
+    pub name: String,
+    pub company_id: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Template {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Attachments arrive either as a bare file name or as an object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Attachment {
+    Name(String),
+    Detailed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        attachment: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ticket {
+    pub id: u32,
+    pub description: String,
+    pub status: String,
+    pub status_id: u32,
+    pub summary: Option<String>,
+    #[serde(rename = "ticketCreator")]
+    pub ticket_creator: Option<String>,
+    #[serde(rename = "ticketUser")]
+    pub ticket_user: Option<String>,
+    #[serde(rename = "ticketUserPhone")]
+    pub ticket_user_phone: Option<String>,
+    #[serde(rename = "playStatus", skip_serializing_if = "Option::is_none")]
+    pub play_status: Option<String>,
+    #[serde(rename = "ticketTerminatedUser")]
+    pub ticket_terminated_user: Option<String>,
+    pub attachments: Vec<Attachment>,
+    pub subject: Option<String>,
+    pub priority: String,
+    pub index: Option<u32>,
+    pub my_ticket_id: Option<u32>,
+    pub location_id: Option<u32>,
+    pub company: Company,
+    pub dyn_template_id: Option<u32>,
+    pub created_at: String,
+    pub ticket_start: Option<String>,
+    #[serde(rename = "ticketMessagesCount")]
+    pub ticket_messages_count: u32,
+    pub template_data: Option<String>,
+    pub pool_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketsResponse {
+    pub status: String,
+    pub new_tickets: Vec<Ticket>,
+    pub my_tickets: Vec<Ticket>,
+    pub all_tickets: Vec<Ticket>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketHistory {
+    pub id: u32,
+    pub ticket_id: u32,
+    pub technician_id: u32,
+    pub status_id: u32,
+    pub technician_reply: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub service_start: Option<i64>,
+    pub service_end: Option<i64>,
+    pub total_time: Option<i64>,
+    pub user: Option<User>,
+    pub status_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoItem {
+    pub id: u32,
+    pub ticket_id: u32,
+    pub user_id: u32,
+    pub to_do: String,
+    pub checked: u8,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStatus {
+    pub id: u32,
+    pub play_status: u8,
+    pub status_id: u32,
+    pub total_time: i64,
+    pub total_time_raw: Option<String>,
+    pub tmp_description: Option<String>,
+    pub ticket_status_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageAuthor {
+    pub id: u32,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketMessage {
+    pub id: u32,
+    pub ticket_id: u32,
+    pub user_id: u32,
+    pub message: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<MessageAuthor>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserStatus {
+    #[serde(rename = "activeStatus")]
+    pub active_status: bool,
+    pub message: String,
+}
+
+/// Common envelope of every Ticketbase response. The endpoint specific part
+/// is flattened in, because some endpoints put their payload at the top
+/// level (`ticket_data`, `check_list`, ...) and others nest it under `data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiResponse<T = Empty> {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(flatten)]
+    pub payload: T,
+}
+
+/// Payload for endpoints that only report a status.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Empty {}
+
+/// Payload nested under the `data` key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Data<T> {
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+}
+
+impl<T> Default for Data<T> {
+    fn default() -> Self {
+        Self { data: None }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketDataPayload {
+    pub ticket_data: Vec<TicketHistory>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckListPayload {
+    pub check_list: Vec<TodoItem>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStatusPayload {
+    #[serde(rename = "playerStatus")]
+    pub player_status: Option<PlayerStatus>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Named {
+    pub id: u32,
+    pub name: String,
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RawCompany {
+    pub id: u32,
+    pub name: String,
+    pub number: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub zip: Option<String>,
+    pub address: Option<String>,
+}
+
+/// `/getTicketById` returns the database row with its relations instead of
+/// the flattened list shape.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RawTicket {
+    pub id: u32,
+    pub description: Option<String>,
+    pub status: Option<Named>,
+    pub status_id: u32,
+    pub summary: Option<String>,
+    pub userone: Option<Named>,
+    pub ticketuser: Option<Named>,
+    pub servicedetail: Option<Named>,
+    pub priority: Option<String>,
+    pub priority_index: Option<u32>,
+    pub my_ticket_id: Option<u32>,
+    pub location_id: Option<u32>,
+    pub companyone: Option<RawCompany>,
+    pub dyn_template_id: Option<u32>,
+    pub created_at: Option<String>,
+    pub template_data: Option<String>,
+}
+
+impl From<RawTicket> for Ticket {
+    fn from(raw: RawTicket) -> Self {
+        let company = raw.companyone.unwrap_or_default();
+        Ticket {
+            id: raw.id,
+            description: raw.description.unwrap_or_default(),
+            status: raw.status.map(|s| s.name).unwrap_or_default(),
+            status_id: raw.status_id,
+            summary: raw.summary,
+            ticket_creator: raw.userone.map(|u| u.name),
+            ticket_user_phone: raw.ticketuser.as_ref().and_then(|u| u.phone.clone()),
+            ticket_user: raw.ticketuser.map(|u| u.name),
+            subject: raw.servicedetail.map(|s| s.name),
+            priority: raw.priority.unwrap_or_default(),
+            index: raw.priority_index,
+            my_ticket_id: raw.my_ticket_id,
+            location_id: raw.location_id,
+            company: Company {
+                id: company.id,
+                name: company.name,
+                number: company.number,
+                company_mail: company.email,
+                company_phone: company.phone,
+                company_zip: company.zip,
+                company_adress: company.address,
+                locations: None,
+            },
+            dyn_template_id: raw.dyn_template_id,
+            created_at: raw.created_at.unwrap_or_default(),
+            template_data: raw.template_data,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketByIdPayload {
+    pub tickets: Option<RawTicket>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodayTicketsPayload {
+    #[serde(rename = "todayTickets")]
+    pub today_tickets: Vec<Ticket>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomersPayload {
+    pub customers: Vec<Company>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocationsData {
+    pub locations: Vec<Location>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsersData {
+    pub users: Vec<User>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplatesData {
+    pub templates: Vec<Template>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityData {
+    pub activity: UserStatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileData {
+    pub user: User,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MailSettingsData {
+    pub user_mail_settings_arr: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessagesPayload {
+    pub messages: Vec<TicketMessage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WikiPayload {
+    #[serde(rename = "wikiData")]
+    pub wiki_data: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportPayload {
+    pub report: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TopUsersPayload {
+    pub top_users: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClosedTicketsPayload {
+    pub tickets: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoriesPayload {
+    pub histories: Vec<Value>,
+}
+
+// Request bodies that are passed through from the frontend as a whole.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketFilter {
+    pub user_id: u32,
+    pub user_group_id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_user_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_user_group_id: Option<u32>,
+}
+
+impl TicketFilter {
+    /// The filter the dashboard uses: everything visible to `user`.
+    pub fn for_user(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            user_group_id: user.user_group_id,
+            company_id: Some(user.company_id),
+            location_id: user.location_id,
+            for_user_id: Some(user.id),
+            sub_user_group_id: user.sub_user_group_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewTicket {
+    pub user_id: u32,
+    pub description: String,
+    pub priority: String,
+    pub company_id: u32,
+    pub location_id: u32,
+    pub for_user_id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyn_template_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub ticket_id: u32,
+    pub user_id: u32,
+    pub verlauf_text: String,
+    pub status_id: u32,
+    #[serde(rename = "sendMail", default, skip_serializing_if = "Option::is_none")]
+    pub send_mail: Option<u8>,
+    #[serde(
+        rename = "retermUserId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reterm_user_id: Option<u32>,
+    #[serde(
+        rename = "retermDate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reterm_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchCorrection {
+    pub ticket_id: u32,
+    pub user_id: u32,
+    pub old_time: i64,
+    pub new_time: i64,
+}
@@ -0,0 +1,343 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri_plugin_http::reqwest::{self, header, StatusCode};
+use tokio::sync::{watch, Semaphore};
+
+use super::{ApiError, ApiResult};
+
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+const MAX_ATTEMPTS: u32 = 5;
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Whether a request may be merged with an identical one and retried after
+/// errors where we cannot tell if the server processed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThrottleState {
+    pub throttled: bool,
+    /// How long requests are held back, counted from when the state was sent.
+    pub retry_in_ms: u64,
+    pub endpoint: Option<String>,
+}
+
+type Shared = watch::Receiver<Option<ApiResult<Vec<u8>>>>;
+
+/// Sits in front of every Ticketbase request: merges identical in-flight
+/// reads, caps concurrency and backs off when the server answers 429.
+pub struct Scheduler {
+    permits: Semaphore,
+    in_flight: Mutex<HashMap<String, Shared>>,
+    blocked_until: Mutex<Option<Instant>>,
+    throttle: watch::Sender<ThrottleState>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            permits: Semaphore::new(MAX_CONCURRENT_REQUESTS),
+            in_flight: Mutex::new(HashMap::new()),
+            blocked_until: Mutex::new(None),
+            throttle: watch::channel(ThrottleState::default()).0,
+        }
+    }
+}
+
+/// Drops the in-flight entry even if the leading request gets cancelled, so
+/// waiting callers fall back to sending their own.
+struct InFlightGuard<'a> {
+    map: &'a Mutex<HashMap<String, Shared>>,
+    key: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.map.lock().unwrap().remove(&self.key);
+    }
+}
+
+impl Scheduler {
+    pub fn subscribe(&self) -> watch::Receiver<ThrottleState> {
+        self.throttle.subscribe()
+    }
+
+    pub fn throttle_state(&self) -> ThrottleState {
+        self.throttle.borrow().clone()
+    }
+
+    pub async fn execute(
+        &self,
+        http: &reqwest::Client,
+        request: reqwest::Request,
+        kind: RequestKind,
+    ) -> ApiResult<Vec<u8>> {
+        if kind == RequestKind::Write {
+            return self.send_with_retry(http, request, kind).await;
+        }
+
+        let key = request_key(&request);
+        loop {
+            // Looked up and claimed under one lock, or two identical reads
+            // could both go out
+            let leader = match self.in_flight.lock().unwrap().entry(key.clone()) {
+                Entry::Occupied(entry) => Err(entry.get().clone()),
+                Entry::Vacant(entry) => {
+                    let (tx, rx) = watch::channel(None);
+                    entry.insert(rx);
+                    Ok(tx)
+                }
+            };
+
+            match leader {
+                Ok(tx) => {
+                    let _guard = InFlightGuard {
+                        map: &self.in_flight,
+                        key,
+                    };
+                    let result = self.send_with_retry(http, request, kind).await;
+                    let _ = tx.send(Some(result.clone()));
+                    return result;
+                }
+                Err(mut shared) => {
+                    if let Ok(result) = shared.wait_for(Option::is_some).await {
+                        return result.clone().unwrap();
+                    }
+                    // The leader was cancelled; one of the waiting takes over
+                }
+            }
+        }
+    }
+
+    async fn send_with_retry(
+        &self,
+        http: &reqwest::Client,
+        request: reqwest::Request,
+        kind: RequestKind,
+    ) -> ApiResult<Vec<u8>> {
+        let endpoint = request.url().path().to_string();
+
+        for attempt in 0..MAX_ATTEMPTS {
+            let last_attempt = attempt + 1 == MAX_ATTEMPTS;
+            self.wait_until_unblocked().await;
+
+            let attempt_request = request
+                .try_clone()
+                .ok_or_else(|| ApiError::Network("Request body cannot be retried".to_string()))?;
+
+            let outcome = {
+                let _permit = self.permits.acquire().await.unwrap();
+                http.execute(attempt_request).await
+            };
+
+            let response = match outcome {
+                Ok(response) => response,
+                // Only reads are resent: a write may have reached the server
+                Err(e) if kind == RequestKind::Read && !last_attempt && is_transient(&e) => {
+                    tokio::time::sleep(backoff(attempt)).await;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let status = response.status();
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                // The server rejected it without processing, so writes are safe to resend
+                let wait = retry_after(response.headers()).unwrap_or_else(|| backoff(attempt));
+                self.block_for(wait, &endpoint);
+                if last_attempt {
+                    return Err(http_error(response).await);
+                }
+                continue;
+            }
+
+            if status.is_server_error() && kind == RequestKind::Read && !last_attempt {
+                tokio::time::sleep(backoff(attempt)).await;
+                continue;
+            }
+
+            if !status.is_success() {
+                return Err(http_error(response).await);
+            }
+
+            self.clear_throttle();
+            return Ok(response.bytes().await?.to_vec());
+        }
+
+        unreachable!("the last attempt always returns")
+    }
+
+    /// Hands back the response once its headers are in, for bodies too large
+    /// to buffer in either direction. Waits out a 429 block like every
+    /// request but is not retried here; the caller resumes from what it
+    /// already has or sends again.
+    pub async fn execute_streaming(
+        &self,
+        http: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> ApiResult<reqwest::Response> {
+        let endpoint = request.url().path().to_string();
+        self.wait_until_unblocked().await;
+
+        // The permit only covers connecting, a long download must not starve the rest
+        let response = {
+            let _permit = self.permits.acquire().await.unwrap();
+            http.execute(request).await?
+        };
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            self.block_for(
+                retry_after(response.headers()).unwrap_or_else(|| backoff(0)),
+                &endpoint,
+            );
+            return Err(http_error(response).await);
+        }
+        if !status.is_success() {
+            return Err(http_error(response).await);
+        }
+
+        self.clear_throttle();
+        Ok(response)
+    }
+
+    async fn wait_until_unblocked(&self) {
+        loop {
+            let until = *self.blocked_until.lock().unwrap();
+            match until {
+                Some(until) if until > Instant::now() => {
+                    tokio::time::sleep_until(until.into()).await
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn block_for(&self, wait: Duration, endpoint: &str) {
+        let until = Instant::now() + wait;
+        let mut blocked_until = self.blocked_until.lock().unwrap();
+        if blocked_until.is_none_or(|current| current < until) {
+            *blocked_until = Some(until);
+        }
+
+        self.throttle.send_replace(ThrottleState {
+            throttled: true,
+            retry_in_ms: wait.as_millis() as u64,
+            endpoint: Some(endpoint.to_string()),
+        });
+    }
+
+    fn clear_throttle(&self) {
+        let mut blocked_until = self.blocked_until.lock().unwrap();
+        if blocked_until.is_some_and(|until| until <= Instant::now()) {
+            *blocked_until = None;
+        }
+        if blocked_until.is_none() {
+            self.throttle.send_if_modified(|state| {
+                let was_throttled = state.throttled;
+                *state = ThrottleState::default();
+                was_throttled
+            });
+        }
+    }
+}
+
+fn request_key(request: &reqwest::Request) -> String {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    format!("{} {} {}", request.method(), request.url(), body)
+}
+
+fn is_transient(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout()
+}
+
+/// Full jitter: a random delay between zero and the exponential cap.
+fn backoff(attempt: u32) -> Duration {
+    let cap = BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX);
+    cap.mul_f64(rand::random::<f64>())
+}
+
+/// `Retry-After` in seconds. The HTTP-date form is not used by Ticketbase.
+fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(BACKOFF_MAX))
+}
+
+async fn http_error(response: reqwest::Response) -> ApiError {
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    ApiError::Http {
+        status,
+        body: body.chars().take(500).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_stays_under_the_exponential_cap() {
+        for attempt in 0..MAX_ATTEMPTS {
+            let cap = BACKOFF_BASE * 2u32.pow(attempt);
+            for _ in 0..100 {
+                assert!(backoff(attempt) <= cap);
+            }
+        }
+        // Far past the point where the doubling would overflow
+        for attempt in [7, 32, u32::MAX] {
+            assert!(backoff(attempt) <= BACKOFF_MAX);
+        }
+    }
+
+    #[test]
+    fn backoff_is_jittered() {
+        let delays: Vec<Duration> = (0..20).map(|_| backoff(3)).collect();
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+    }
+
+    fn headers(retry_after: &str) -> header::HeaderMap {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, retry_after.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        assert_eq!(retry_after(&headers("2")), Some(Duration::from_secs(2)));
+        assert_eq!(retry_after(&headers(" 7 ")), Some(Duration::from_secs(7)));
+        assert_eq!(retry_after(&headers("0")), Some(Duration::ZERO));
+        // Never longer than the backoff would wait
+        assert_eq!(retry_after(&headers("3600")), Some(BACKOFF_MAX));
+    }
+
+    #[test]
+    fn retry_after_ignores_what_it_cannot_read() {
+        assert_eq!(retry_after(&header::HeaderMap::new()), None);
+        assert_eq!(retry_after(&headers("-1")), None);
+        assert_eq!(retry_after(&headers("1.5")), None);
+        assert_eq!(retry_after(&headers("Wed, 21 Oct 2026 07:28:00 GMT")), None);
+    }
+}
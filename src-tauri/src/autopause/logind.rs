@@ -0,0 +1,191 @@
+//! Watches logind on the system bus, and the screen saver on the session bus
+//! for desktops that don't tell logind about their lock screen. Both buses
+//! honour `DBUS_SYSTEM_BUS_ADDRESS` and `DBUS_SESSION_BUS_ADDRESS`.
+
+use futures_util::StreamExt;
+use tokio::sync::mpsc::UnboundedSender;
+use zbus::zvariant::{OwnedFd, OwnedObjectPath};
+use zbus::{proxy, Connection};
+
+use super::{Away, Hold, SessionEvent};
+use crate::cache::now_millis;
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(name = "GetSessionByPID")]
+    fn get_session_by_pid(&self, pid: u32) -> zbus::Result<OwnedObjectPath>;
+
+    /// Holds `what` off, in "delay" mode until the fd is closed.
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    #[zbus(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+
+    /// Microseconds since the epoch.
+    #[zbus(property)]
+    fn idle_since_hint(&self) -> zbus::Result<u64>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.ScreenSaver",
+    default_service = "org.freedesktop.ScreenSaver",
+    default_path = "/org/freedesktop/ScreenSaver"
+)]
+trait ScreenSaver {
+    #[zbus(signal)]
+    fn active_changed(&self, active: bool) -> zbus::Result<()>;
+}
+
+type Events = UnboundedSender<SessionEvent>;
+
+pub fn spawn(events: Events) {
+    let screen_saver = events.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = watch_logind(&events).await {
+            log::warn!("Not watching logind: {}", e);
+        }
+    });
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = watch_screen_saver(&screen_saver).await {
+            log::warn!("Not watching the screen saver: {}", e);
+        }
+    });
+}
+
+fn left(reason: Away) -> SessionEvent {
+    SessionEvent::Left {
+        reason,
+        since: now_millis(),
+        hold: None,
+    }
+}
+
+/// Makes logind wait with suspending until the timers are paused; taken
+/// again after every wake up.
+async fn delay_sleep(manager: &ManagerProxy<'_>) -> Option<Hold> {
+    match manager
+        .inhibit("sleep", "Ticketbase", "Pausing running timers", "delay")
+        .await
+    {
+        Ok(fd) => Some(Box::new(fd)),
+        Err(e) => {
+            log::warn!("Suspend will not wait for timers to pause: {}", e);
+            None
+        }
+    }
+}
+
+async fn watch_logind(events: &Events) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager = ManagerProxy::new(&connection).await?;
+    // Started outside the session, e.g. by a user service, the pid has none
+    let path = match std::env::var("XDG_SESSION_ID") {
+        Ok(id) => manager.get_session(&id).await?,
+        Err(_) => manager.get_session_by_pid(std::process::id()).await?,
+    };
+    let session = SessionProxy::builder(&connection)
+        .path(path)?
+        .build()
+        .await?;
+
+    let mut sleep = manager.receive_prepare_for_sleep().await?;
+    let mut lock = session.receive_lock().await?;
+    let mut unlock = session.receive_unlock().await?;
+    let mut locked = session.receive_locked_hint_changed().await;
+    let mut idle = session.receive_idle_hint_changed().await;
+    let mut delay = delay_sleep(&manager).await;
+
+    // One message we cannot read must not end the watching
+    loop {
+        let event = tokio::select! {
+            Some(signal) = sleep.next() => match signal.args().map(|args| *args.start()) {
+                Ok(true) => SessionEvent::Left {
+                    reason: Away::Sleep,
+                    since: now_millis(),
+                    hold: delay.take(),
+                },
+                Ok(false) => {
+                    delay = delay_sleep(&manager).await;
+                    SessionEvent::Returned(Away::Sleep)
+                }
+                Err(e) => {
+                    log::warn!("Unreadable PrepareForSleep signal: {}", e);
+                    continue;
+                }
+            },
+            Some(_) = lock.next() => left(Away::Locked),
+            Some(_) = unlock.next() => SessionEvent::Returned(Away::Locked),
+            Some(change) = locked.next() => match change.get().await {
+                Ok(true) => left(Away::Locked),
+                Ok(false) => SessionEvent::Returned(Away::Locked),
+                Err(e) => {
+                    log::warn!("Unreadable LockedHint: {}", e);
+                    continue;
+                }
+            },
+            Some(change) = idle.next() => match change.get().await {
+                Ok(true) => {
+                    let since = session.idle_since_hint().await.unwrap_or(0) as i64 / 1000;
+                    SessionEvent::Left {
+                        reason: Away::Idle,
+                        since: if since > 0 { since } else { now_millis() },
+                        hold: None,
+                    }
+                }
+                Ok(false) => SessionEvent::Returned(Away::Idle),
+                Err(e) => {
+                    log::warn!("Unreadable IdleHint: {}", e);
+                    continue;
+                }
+            },
+            else => return Ok(()),
+        };
+        if events.send(event).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+async fn watch_screen_saver(events: &Events) -> zbus::Result<()> {
+    let connection = Connection::session().await?;
+    let screen_saver = ScreenSaverProxy::new(&connection).await?;
+    let mut active = screen_saver.receive_active_changed().await?;
+    while let Some(signal) = active.next().await {
+        let event = match signal.args().map(|args| *args.active()) {
+            Ok(true) => left(Away::Locked),
+            Ok(false) => SessionEvent::Returned(Away::Locked),
+            Err(e) => {
+                log::warn!("Unreadable ActiveChanged signal: {}", e);
+                continue;
+            }
+        };
+        if events.send(event).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,328 @@
+//! Pauses running ticket timers while nobody is at the desk: the screen is
+//! locked, the machine sleeps or the session went idle. Once the user is
+//! back they decide whether the time away counts.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::mpsc;
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::WatchCorrection;
+use crate::cache::now_millis;
+use crate::timer::{self, TimerAction, TimerState, PAUSE};
+
+#[cfg(target_os = "linux")]
+mod logind;
+
+/// Time the server booked late is corrected once it adds up to a minute,
+/// its precision.
+const CORRECTION_MIN_MS: i64 = 60_000;
+
+/// Why nobody is at the desk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Away {
+    Locked,
+    Sleep,
+    Idle,
+}
+
+/// Dropped once the timers are paused, e.g. logind's lock that holds off
+/// suspending until then.
+pub type Hold = Box<dyn Send>;
+
+/// What the session watchers report.
+pub enum SessionEvent {
+    /// `since` is when it started, in milliseconds since the epoch.
+    Left {
+        reason: Away,
+        since: i64,
+        hold: Option<Hold>,
+    },
+    Returned(Away),
+}
+
+/// A timer that was paused because the user left.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PausedTimer {
+    pub account_id: String,
+    pub ticket_id: u32,
+    /// When it stopped counting, in milliseconds since the epoch.
+    pub paused_at: i64,
+}
+
+/// Sent as "timer://away" when the user is back, and as `null` once they
+/// kept or discarded it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwayGap {
+    pub reason: Away,
+    pub since: i64,
+    pub returned_at: i64,
+    pub timers: Vec<PausedTimer>,
+}
+
+#[derive(Default)]
+struct Presence {
+    /// What keeps the user away, empty while they are at the desk.
+    reasons: Vec<Away>,
+    /// Why and since when the current absence lasts.
+    left: Option<(Away, i64)>,
+    paused: Vec<PausedTimer>,
+    /// Waiting for the user's answer.
+    gap: Option<AwayGap>,
+}
+
+#[derive(Default)]
+pub struct AutoPause {
+    presence: Mutex<Presence>,
+}
+
+impl AutoPause {
+    /// Notes `reason`, returning when the absence began if it just did.
+    fn leave(&self, reason: Away, since: i64) -> Option<i64> {
+        let mut presence = self.presence.lock().unwrap();
+        if !presence.reasons.contains(&reason) {
+            presence.reasons.push(reason);
+        }
+        if presence.left.is_some() {
+            return None;
+        }
+        presence.left = Some((reason, since));
+        Some(since)
+    }
+
+    /// Clears `reason`, returning the gap to ask about once nothing else
+    /// keeps the user away.
+    fn come_back(&self, reason: Away) -> Option<AwayGap> {
+        let mut presence = self.presence.lock().unwrap();
+        presence.reasons.retain(|away| *away != reason);
+        if !presence.reasons.is_empty() {
+            return None;
+        }
+        let (reason, since) = presence.left.take()?;
+        let timers = std::mem::take(&mut presence.paused);
+        if timers.is_empty() {
+            return None;
+        }
+
+        let returned_at = now_millis();
+        // An earlier gap nobody answered yet takes this one in
+        let gap = match presence.gap.take() {
+            Some(mut gap) => {
+                gap.returned_at = returned_at;
+                gap.timers.extend(timers);
+                gap
+            }
+            None => AwayGap {
+                reason,
+                since,
+                returned_at,
+                timers,
+            },
+        };
+        presence.gap = Some(gap.clone());
+        Some(gap)
+    }
+}
+
+/// Feeds the session watchers' events to the time tracker. Only Linux
+/// reports them so far; elsewhere timers run on as before.
+pub fn spawn<R: Runtime>(app: AppHandle<R>) {
+    let (events, mut receiver) = mpsc::unbounded_channel();
+    #[cfg(target_os = "linux")]
+    logind::spawn(events);
+    #[cfg(not(target_os = "linux"))]
+    drop(events);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            handle(&app, event).await;
+        }
+    });
+}
+
+async fn handle<R: Runtime>(app: &AppHandle<R>, event: SessionEvent) {
+    let auto_pause = app.state::<AutoPause>();
+    match event {
+        SessionEvent::Left {
+            reason,
+            since,
+            hold,
+        } => {
+            if let Some(since) = auto_pause.leave(reason, since) {
+                let paused = pause_running(app, since).await;
+                auto_pause.presence.lock().unwrap().paused.extend(paused);
+            }
+            drop(hold);
+        }
+        SessionEvent::Returned(reason) => {
+            if let Some(gap) = auto_pause.come_back(reason) {
+                let _ = app.emit("timer://away", Some(gap));
+            }
+        }
+    }
+}
+
+/// Pauses the running timers of every account as of `since`.
+async fn pause_running<R: Runtime>(app: &AppHandle<R>, since: i64) -> Vec<PausedTimer> {
+    let mut paused = Vec::new();
+    for account in app.state::<Accounts>().all() {
+        let timers = account
+            .cache
+            .open_timers(account.user.id)
+            .unwrap_or_default();
+        for timer in timers.iter().filter(|timer| timer.running) {
+            match pause(app, &account, timer, since).await {
+                Ok(()) => paused.push(PausedTimer {
+                    account_id: account.id.clone(),
+                    ticket_id: timer.ticket_id,
+                    paused_at: since,
+                }),
+                Err(e) => log::error!(
+                    "Failed to pause the timer of ticket {}: {}",
+                    timer.ticket_id,
+                    e
+                ),
+            }
+        }
+    }
+    paused
+}
+
+async fn pause<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    timer: &TimerState,
+    since: i64,
+) -> Result<(), String> {
+    let user_id = account.user.id;
+    let response = account
+        .api
+        .pause(timer.ticket_id, user_id, timer.play_status)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status != "success" {
+        return Err(response.message.unwrap_or(response.status));
+    }
+    timer::record_at(app, account, timer.ticket_id, TimerAction::Pause, since);
+
+    // The server paused just now, but an idle session left a while before
+    let late = now_millis() - since;
+    if late >= CORRECTION_MIN_MS {
+        if let Some(local) = account.cache.timer(user_id, timer.ticket_id)? {
+            correct(
+                account,
+                timer.ticket_id,
+                local.elapsed_ms + late,
+                local.elapsed_ms,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Resumes a timer paused while the user was away, counting the time away
+/// with `keep`.
+async fn resume<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    paused: &PausedTimer,
+    keep: bool,
+    now: i64,
+) -> Result<(), String> {
+    let user_id = account.user.id;
+    // Resumed or stopped by hand in the meantime
+    let Some(timer) = account.cache.timer(user_id, paused.ticket_id)? else {
+        return Ok(());
+    };
+    if timer.play_status != PAUSE {
+        return Ok(());
+    }
+
+    let response = account
+        .api
+        .resume(paused.ticket_id, user_id, PAUSE)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status != "success" {
+        return Err(response.message.unwrap_or(response.status));
+    }
+    if keep {
+        let from = paused.paused_at.max(timer.started_at);
+        account
+            .cache
+            .add_timer_interval(user_id, paused.ticket_id, from, now)?;
+    }
+    timer::record_at(app, account, paused.ticket_id, TimerAction::Resume, now);
+
+    if keep {
+        if let Some(kept) = account.cache.timer(user_id, paused.ticket_id)? {
+            correct(account, paused.ticket_id, timer.elapsed_ms, kept.elapsed_ms).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Books `new_ms` on the server instead of `old_ms`, in whole minutes.
+async fn correct(
+    account: &Account,
+    ticket_id: u32,
+    old_ms: i64,
+    new_ms: i64,
+) -> Result<(), String> {
+    let minutes = |ms: i64| (ms + 30_000) / 60_000;
+    account
+        .api
+        .correct_watch(&WatchCorrection {
+            ticket_id,
+            user_id: account.user.id,
+            old_time: minutes(old_ms),
+            new_time: minutes(new_ms),
+        })
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// The time away the user has not answered for yet.
+#[tauri::command]
+pub fn get_away_gap(auto_pause: State<'_, AutoPause>) -> Option<AwayGap> {
+    auto_pause.presence.lock().unwrap().gap.clone()
+}
+
+/// Resumes the timers paused while the user was away. With `keep` the time
+/// away counts as work on their tickets, otherwise it is dropped.
+#[tauri::command]
+pub async fn resolve_away_gap<R: Runtime>(
+    app: AppHandle<R>,
+    auto_pause: State<'_, AutoPause>,
+    accounts: State<'_, Accounts>,
+    keep: bool,
+) -> Result<(), String> {
+    let Some(gap) = auto_pause.presence.lock().unwrap().gap.take() else {
+        return Ok(());
+    };
+    let _ = app.emit("timer://away", None::<AwayGap>);
+
+    let now = now_millis();
+    let mut errors = Vec::new();
+    for paused in &gap.timers {
+        // Signed out in the meantime
+        let Some(account) = accounts.get(&paused.account_id) else {
+            continue;
+        };
+        if let Err(e) = resume(&app, &account, paused, keep, now).await {
+            errors.push(format!("Ticket #{}: {}", paused.ticket_id, e));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
@@ -0,0 +1,120 @@
+use serde_json::Value;
+use tauri::State;
+
+use crate::accounts::Accounts;
+use crate::api::models::*;
+
+// Offline counterparts of the API commands. They answer in the same envelope
+// as the live endpoint, or with `null` when nothing has been cached yet.
+// They read the active account's cache.
+
+fn cached<T>(payload: T) -> ApiResponse<T> {
+    ApiResponse {
+        status: "success".to_string(),
+        result: None,
+        message: None,
+        payload,
+    }
+}
+
+#[tauri::command]
+pub fn get_cached_ticket(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<Ticket>, String> {
+    accounts.active()?.cache.ticket(ticket_id)
+}
+
+#[tauri::command]
+pub fn get_cached_ticket_data(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<ApiResponse<TicketDataPayload>>, String> {
+    let ticket_data = accounts.active()?.cache.histories(ticket_id)?;
+    Ok((!ticket_data.is_empty()).then(|| cached(TicketDataPayload { ticket_data })))
+}
+
+#[tauri::command]
+pub fn get_cached_check_list(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<ApiResponse<CheckListPayload>>, String> {
+    let check_list = accounts.active()?.cache.todos(ticket_id)?;
+    Ok((!check_list.is_empty()).then(|| cached(CheckListPayload { check_list })))
+}
+
+#[tauri::command]
+pub fn get_cached_ticket_messages(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<ApiResponse<MessagesPayload>>, String> {
+    let messages = accounts.active()?.cache.messages(ticket_id)?;
+    Ok((!messages.is_empty()).then(|| cached(MessagesPayload { messages })))
+}
+
+#[tauri::command]
+pub fn get_cached_customers(
+    accounts: State<'_, Accounts>,
+) -> Result<Option<ApiResponse<CustomersPayload>>, String> {
+    let customers = accounts.active()?.cache.companies()?;
+    Ok((!customers.is_empty()).then(|| cached(CustomersPayload { customers })))
+}
+
+#[tauri::command]
+pub fn get_cached_customer_locations(
+    accounts: State<'_, Accounts>,
+    customer_id: u32,
+) -> Result<Option<ApiResponse<Data<LocationsData>>>, String> {
+    let locations = accounts.active()?.cache.locations(customer_id)?;
+    Ok((!locations.is_empty()).then(|| {
+        cached(Data {
+            data: Some(LocationsData { locations }),
+        })
+    }))
+}
+
+#[tauri::command]
+pub fn get_cached_location_users(
+    accounts: State<'_, Accounts>,
+    location_id: u32,
+) -> Result<Option<ApiResponse<Data<UsersData>>>, String> {
+    let users = accounts.active()?.cache.location_users(location_id)?;
+    Ok((!users.is_empty()).then(|| {
+        cached(Data {
+            data: Some(UsersData { users }),
+        })
+    }))
+}
+
+// Key/value entries with a TTL, backing `src/lib/cache.ts`
+
+#[tauri::command]
+pub fn cache_get(accounts: State<'_, Accounts>, key: String) -> Result<Option<Value>, String> {
+    accounts.active()?.cache.entry(&key)
+}
+
+#[tauri::command]
+pub fn cache_set(
+    accounts: State<'_, Accounts>,
+    key: String,
+    data: Value,
+    ttl_ms: i64,
+) -> Result<(), String> {
+    accounts.active()?.cache.set_entry(&key, &data, ttl_ms)
+}
+
+#[tauri::command]
+pub fn cache_delete(accounts: State<'_, Accounts>, key: String) -> Result<(), String> {
+    accounts.active()?.cache.remove_entry(&key)
+}
+
+#[tauri::command]
+pub fn cache_invalidate(
+    accounts: State<'_, Accounts>,
+    pattern: Option<String>,
+) -> Result<(), String> {
+    accounts
+        .active()?
+        .cache
+        .remove_entries(pattern.as_deref().unwrap_or_default())
+}
@@ -0,0 +1,644 @@
+pub mod commands;
+mod outbox;
+mod schema;
+mod timer;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde_json::Value;
+
+use crate::api::models::*;
+use crate::poller::TicketList;
+
+pub const DATABASE_FILE: &str = "cache.sqlite3";
+
+const TICKET_COLUMNS: &str = "t.id, t.location_id, t.status, t.status_id, t.priority, \
+    t.priority_index, t.subject, t.summary, t.description, t.ticket_creator, t.ticket_user, \
+    t.ticket_user_phone, t.play_status, t.ticket_terminated_user, t.my_ticket_id, \
+    t.dyn_template_id, t.template_data, t.pool_name, t.created_at, t.ticket_start, \
+    t.messages_count, t.attachments, \
+    t.company_id, c.name, c.number, c.mail, c.phone, c.zip, c.address";
+
+const USER_COLUMNS: &str = "u.id, u.name, u.email, u.firstname, u.surname, u.phone, \
+    u.company_id, u.user_group_id, u.sub_user_group_id, u.location_id, \
+    u.profile_photo_url, u.role_id, u.role_name";
+
+/// Offline copy of everything an account has loaded, in SQLite in its data
+/// dir. API commands write through to it; the `get_cached_*` commands read
+/// it back so the app starts instantly and stays browsable without network.
+pub struct OfflineCache {
+    conn: Mutex<Connection>,
+}
+
+impl OfflineCache {
+    /// Opens the database kept in `dir`, an account's data directory.
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+        let mut conn = Connection::open(dir.join(DATABASE_FILE)).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| e.to_string())?;
+        schema::migrate(&mut conn).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<T>(
+        &self,
+        query: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        query(&self.conn.lock().unwrap()).map_err(|e| e.to_string())
+    }
+
+    /// Like `write`, for changes whose failure the caller has to know about.
+    fn transaction<T>(
+        &self,
+        update: impl FnOnce(&Transaction) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let value = update(&tx).map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(value)
+    }
+
+    /// Writes in one transaction. Failures are only logged, a broken cache
+    /// must never fail a request that reached the server fine.
+    fn write(&self, what: &str, update: impl FnOnce(&Transaction) -> rusqlite::Result<()>) {
+        let mut conn = self.conn.lock().unwrap();
+        let result = conn.transaction().and_then(|tx| {
+            update(&tx)?;
+            tx.commit()
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to cache {}: {}", what, e);
+        }
+    }
+
+    /// Drops everything, e.g. on logout.
+    pub fn clear(&self) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute_batch(
+                "DELETE FROM companies; DELETE FROM locations; DELETE FROM users;
+                 DELETE FROM tickets; DELETE FROM ticket_lists; DELETE FROM histories;
+                 DELETE FROM todos; DELETE FROM messages; DELETE FROM entries;",
+            )
+        })
+    }
+
+    // Tickets
+
+    pub fn store_tickets(&self, user_id: u32, response: &TicketsResponse) {
+        self.write("tickets", |tx| {
+            tx.execute("DELETE FROM ticket_lists WHERE user_id = ?1", [user_id])?;
+            for list in TicketList::ALL {
+                for (position, ticket) in list.of(response).iter().enumerate() {
+                    upsert_ticket(tx, ticket)?;
+                    tx.execute(
+                        "INSERT INTO ticket_lists (user_id, list, position, ticket_id)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![user_id, list.as_str(), position, ticket.id],
+                    )?;
+                }
+            }
+            Ok(())
+        });
+    }
+
+    pub fn store_ticket(&self, ticket: &Ticket) {
+        self.write("ticket", |tx| upsert_ticket(tx, ticket));
+    }
+
+    pub fn tickets(&self, user_id: u32) -> Result<Option<TicketsResponse>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {TICKET_COLUMNS} FROM ticket_lists l
+                 JOIN tickets t ON t.id = l.ticket_id
+                 LEFT JOIN companies c ON c.id = t.company_id
+                 WHERE l.user_id = ?1 AND l.list = ?2
+                 ORDER BY l.position"
+            ))?;
+
+            let mut response = TicketsResponse {
+                status: "success".to_string(),
+                ..Default::default()
+            };
+            let mut found = false;
+            for list in TicketList::ALL {
+                let tickets = statement
+                    .query_map(params![user_id, list.as_str()], ticket_from_row)?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                found |= !tickets.is_empty();
+                match list {
+                    TicketList::New => response.new_tickets = tickets,
+                    TicketList::My => response.my_tickets = tickets,
+                    TicketList::All => response.all_tickets = tickets,
+                }
+            }
+
+            Ok(found.then_some(response))
+        })
+    }
+
+    pub fn ticket(&self, ticket_id: u32) -> Result<Option<Ticket>, String> {
+        self.with_conn(|conn| {
+            conn.query_row(
+                &format!(
+                    "SELECT {TICKET_COLUMNS} FROM tickets t
+                     LEFT JOIN companies c ON c.id = t.company_id
+                     WHERE t.id = ?1"
+                ),
+                [ticket_id],
+                ticket_from_row,
+            )
+            .optional()
+        })
+    }
+
+    // Ticket details
+
+    pub fn store_histories(&self, ticket_id: u32, histories: &[TicketHistory]) {
+        self.write("ticket history", |tx| {
+            tx.execute("DELETE FROM histories WHERE ticket_id = ?1", [ticket_id])?;
+            for history in histories {
+                if let Some(user) = &history.user {
+                    upsert_user(tx, user)?;
+                }
+                tx.execute(
+                    "INSERT OR REPLACE INTO histories (id, ticket_id, technician_id, status_id,
+                        status_name, technician_reply, created_at, updated_at, service_start,
+                        service_end, total_time, user_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        history.id,
+                        ticket_id,
+                        history.technician_id,
+                        history.status_id,
+                        history.status_name,
+                        history.technician_reply,
+                        history.created_at,
+                        history.updated_at,
+                        history.service_start,
+                        history.service_end,
+                        history.total_time,
+                        history.user.as_ref().map(|u| u.id),
+                    ],
+                )?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn histories(&self, ticket_id: u32) -> Result<Vec<TicketHistory>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT h.id, h.ticket_id, h.technician_id, h.status_id, h.status_name,
+                        h.technician_reply, h.created_at, h.updated_at, h.service_start,
+                        h.service_end, h.total_time, {USER_COLUMNS}
+                 FROM histories h LEFT JOIN users u ON u.id = h.user_id
+                 WHERE h.ticket_id = ?1
+                 ORDER BY h.created_at, h.id"
+            ))?;
+            let histories = statement
+                .query_map([ticket_id], |row| {
+                    Ok(TicketHistory {
+                        id: row.get(0)?,
+                        ticket_id: row.get(1)?,
+                        technician_id: row.get(2)?,
+                        status_id: row.get(3)?,
+                        status_name: row.get(4)?,
+                        technician_reply: row.get(5)?,
+                        created_at: row.get(6)?,
+                        updated_at: row.get(7)?,
+                        service_start: row.get(8)?,
+                        service_end: row.get(9)?,
+                        total_time: row.get(10)?,
+                        user: match row.get::<_, Option<u32>>(11)? {
+                            Some(_) => Some(user_from_row(row, 11)?),
+                            None => None,
+                        },
+                    })
+                })?
+                .collect();
+            histories
+        })
+    }
+
+    pub fn store_todos(&self, ticket_id: u32, todos: &[TodoItem]) {
+        self.write("check list", |tx| {
+            tx.execute("DELETE FROM todos WHERE ticket_id = ?1", [ticket_id])?;
+            for todo in todos {
+                tx.execute(
+                    "INSERT OR REPLACE INTO todos (id, ticket_id, user_id, to_do, checked, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        todo.id,
+                        ticket_id,
+                        todo.user_id,
+                        todo.to_do,
+                        todo.checked,
+                        todo.created_at
+                    ],
+                )?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn todos(&self, ticket_id: u32) -> Result<Vec<TodoItem>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, ticket_id, user_id, to_do, checked, created_at
+                 FROM todos WHERE ticket_id = ?1 ORDER BY id",
+            )?;
+            let todos = statement
+                .query_map([ticket_id], |row| {
+                    Ok(TodoItem {
+                        id: row.get(0)?,
+                        ticket_id: row.get(1)?,
+                        user_id: row.get(2)?,
+                        to_do: row.get(3)?,
+                        checked: row.get(4)?,
+                        created_at: row.get(5)?,
+                    })
+                })?
+                .collect();
+            todos
+        })
+    }
+
+    pub fn store_messages(&self, ticket_id: u32, messages: &[TicketMessage]) {
+        self.write("messages", |tx| {
+            tx.execute("DELETE FROM messages WHERE ticket_id = ?1", [ticket_id])?;
+            for message in messages {
+                tx.execute(
+                    "INSERT OR REPLACE INTO messages (id, ticket_id, user_id, author_name,
+                        author_email, message, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        message.id,
+                        ticket_id,
+                        message.user_id,
+                        message.user.as_ref().map(|u| &u.name),
+                        message.user.as_ref().and_then(|u| u.email.as_ref()),
+                        message.message,
+                        message.created_at,
+                    ],
+                )?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn messages(&self, ticket_id: u32) -> Result<Vec<TicketMessage>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, ticket_id, user_id, author_name, author_email, message, created_at
+                 FROM messages WHERE ticket_id = ?1 ORDER BY created_at, id",
+            )?;
+            let messages = statement
+                .query_map([ticket_id], |row| {
+                    let user_id = row.get(2)?;
+                    let author_name: Option<String> = row.get(3)?;
+                    Ok(TicketMessage {
+                        id: row.get(0)?,
+                        ticket_id: row.get(1)?,
+                        user_id,
+                        message: row.get(5)?,
+                        created_at: row.get(6)?,
+                        user: author_name
+                            .map(|name| -> rusqlite::Result<_> {
+                                Ok(MessageAuthor {
+                                    id: user_id,
+                                    name,
+                                    email: row.get(4)?,
+                                })
+                            })
+                            .transpose()?,
+                    })
+                })?
+                .collect();
+            messages
+        })
+    }
+
+    // Customers
+
+    pub fn store_companies(&self, companies: &[Company]) {
+        self.write("customers", |tx| {
+            for company in companies {
+                upsert_company(tx, company)?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn companies(&self) -> Result<Vec<Company>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, name, number, mail, phone, zip, address FROM companies ORDER BY name",
+            )?;
+            let mut companies = statement
+                .query_map([], |row| company_from_row(row, 0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            for company in &mut companies {
+                let locations = company_locations(conn, company.id)?;
+                company.locations = (!locations.is_empty()).then(|| {
+                    locations
+                        .into_iter()
+                        .map(|l| CompanyLocation {
+                            id: l.id,
+                            name: l.name,
+                        })
+                        .collect()
+                });
+            }
+
+            Ok(companies)
+        })
+    }
+
+    pub fn store_locations(&self, company_id: u32, locations: &[Location]) {
+        self.write("locations", |tx| {
+            tx.execute("DELETE FROM locations WHERE company_id = ?1", [company_id])?;
+            for location in locations {
+                tx.execute(
+                    "INSERT OR REPLACE INTO locations (id, company_id, name) VALUES (?1, ?2, ?3)",
+                    params![location.id, company_id, location.name],
+                )?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn locations(&self, company_id: u32) -> Result<Vec<Location>, String> {
+        self.with_conn(|conn| company_locations(conn, company_id))
+    }
+
+    pub fn store_users(&self, users: &[User]) {
+        self.write("users", |tx| {
+            for user in users {
+                upsert_user(tx, user)?;
+            }
+            Ok(())
+        });
+    }
+
+    pub fn location_users(&self, location_id: u32) -> Result<Vec<User>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {USER_COLUMNS} FROM users u WHERE u.location_id = ?1 ORDER BY u.name"
+            ))?;
+            let users = statement
+                .query_map([location_id], |row| user_from_row(row, 0))?
+                .collect();
+            users
+        })
+    }
+
+    // Free-form entries
+
+    pub fn entry(&self, key: &str) -> Result<Option<Value>, String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM entries WHERE key = ?1 AND expires_at <= ?2",
+                params![key, now_millis()],
+            )?;
+            let data: Option<String> = conn
+                .query_row("SELECT data FROM entries WHERE key = ?1", [key], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+        })
+    }
+
+    pub fn set_entry(&self, key: &str, data: &Value, ttl_ms: i64) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO entries (key, data, expires_at) VALUES (?1, ?2, ?3)",
+                params![key, data.to_string(), now_millis().saturating_add(ttl_ms)],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Removes every entry whose key contains `pattern`; an empty pattern
+    /// removes all of them.
+    pub fn remove_entries(&self, pattern: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM entries WHERE instr(key, ?1) > 0", [pattern])
+                .map(|_| ())
+        })
+    }
+
+    pub fn remove_entry(&self, key: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM entries WHERE key = ?1", [key])
+                .map(|_| ())
+        })
+    }
+}
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+// Companies embedded in tickets only carry a few fields, so never let them
+// blank out what a full `/getCustomers` stored before.
+fn upsert_company(tx: &Transaction, company: &Company) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO companies (id, name, number, mail, phone, zip, address)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (id) DO UPDATE SET
+            name = excluded.name,
+            number = COALESCE(excluded.number, number),
+            mail = COALESCE(excluded.mail, mail),
+            phone = COALESCE(excluded.phone, phone),
+            zip = COALESCE(excluded.zip, zip),
+            address = COALESCE(excluded.address, address)",
+        params![
+            company.id,
+            company.name,
+            company.number,
+            company.company_mail,
+            company.company_phone,
+            company.company_zip,
+            company.company_adress,
+        ],
+    )?;
+
+    if let Some(locations) = &company.locations {
+        tx.execute("DELETE FROM locations WHERE company_id = ?1", [company.id])?;
+        for location in locations {
+            tx.execute(
+                "INSERT OR REPLACE INTO locations (id, company_id, name) VALUES (?1, ?2, ?3)",
+                params![location.id, company.id, location.name],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn company_from_row(row: &Row, offset: usize) -> rusqlite::Result<Company> {
+    Ok(Company {
+        id: row.get(offset)?,
+        name: row
+            .get::<_, Option<String>>(offset + 1)?
+            .unwrap_or_default(),
+        number: row.get(offset + 2)?,
+        company_mail: row.get(offset + 3)?,
+        company_phone: row.get(offset + 4)?,
+        company_zip: row.get(offset + 5)?,
+        company_adress: row.get(offset + 6)?,
+        locations: None,
+    })
+}
+
+fn company_locations(conn: &Connection, company_id: u32) -> rusqlite::Result<Vec<Location>> {
+    let mut statement = conn.prepare_cached(
+        "SELECT id, name, company_id FROM locations WHERE company_id = ?1 ORDER BY name",
+    )?;
+    let locations = statement
+        .query_map([company_id], |row| {
+            Ok(Location {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                company_id: row.get(2)?,
+            })
+        })?
+        .collect();
+    locations
+}
+
+fn upsert_user(tx: &Transaction, user: &User) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO users (id, name, email, firstname, surname, phone, company_id,
+            user_group_id, sub_user_group_id, location_id, profile_photo_url, role_id, role_name)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            user.id,
+            user.name,
+            user.email,
+            user.firstname,
+            user.surname,
+            user.phone,
+            user.company_id,
+            user.user_group_id,
+            user.sub_user_group_id,
+            user.location_id,
+            user.profile_photo_url,
+            user.role.as_ref().map(|r| r.id),
+            user.role.as_ref().map(|r| &r.name),
+        ],
+    )
+    .map(|_| ())
+}
+
+fn user_from_row(row: &Row, offset: usize) -> rusqlite::Result<User> {
+    let role_id: Option<u32> = row.get(offset + 11)?;
+    let role_name: Option<String> = row.get(offset + 12)?;
+
+    Ok(User {
+        id: row.get(offset)?,
+        name: row.get(offset + 1)?,
+        email: row.get(offset + 2)?,
+        firstname: row.get(offset + 3)?,
+        surname: row.get(offset + 4)?,
+        phone: row.get(offset + 5)?,
+        company_id: row.get(offset + 6)?,
+        user_group_id: row.get(offset + 7)?,
+        sub_user_group_id: row.get(offset + 8)?,
+        location_id: row.get(offset + 9)?,
+        profile_photo_url: row.get(offset + 10)?,
+        role: role_id.map(|id| Role {
+            id,
+            name: role_name.unwrap_or_default(),
+        }),
+    })
+}
+
+fn upsert_ticket(tx: &Transaction, ticket: &Ticket) -> rusqlite::Result<()> {
+    upsert_company(tx, &ticket.company)?;
+
+    let attachments = serde_json::to_string(&ticket.attachments)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    tx.execute(
+        "INSERT OR REPLACE INTO tickets (id, company_id, location_id, status, status_id,
+            priority, priority_index, subject, summary, description, ticket_creator,
+            ticket_user, ticket_user_phone, play_status, ticket_terminated_user,
+            my_ticket_id, dyn_template_id, template_data, pool_name, created_at,
+            ticket_start, messages_count, attachments)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+            ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+        params![
+            ticket.id,
+            ticket.company.id,
+            ticket.location_id,
+            ticket.status,
+            ticket.status_id,
+            ticket.priority,
+            ticket.index,
+            ticket.subject,
+            ticket.summary,
+            ticket.description,
+            ticket.ticket_creator,
+            ticket.ticket_user,
+            ticket.ticket_user_phone,
+            ticket.play_status,
+            ticket.ticket_terminated_user,
+            ticket.my_ticket_id,
+            ticket.dyn_template_id,
+            ticket.template_data,
+            ticket.pool_name,
+            ticket.created_at,
+            ticket.ticket_start,
+            ticket.ticket_messages_count,
+            attachments,
+        ],
+    )
+    .map(|_| ())
+}
+
+fn ticket_from_row(row: &Row) -> rusqlite::Result<Ticket> {
+    let attachments: String = row.get(21)?;
+
+    Ok(Ticket {
+        id: row.get(0)?,
+        location_id: row.get(1)?,
+        status: row.get(2)?,
+        status_id: row.get(3)?,
+        priority: row.get(4)?,
+        index: row.get(5)?,
+        subject: row.get(6)?,
+        summary: row.get(7)?,
+        description: row.get(8)?,
+        ticket_creator: row.get(9)?,
+        ticket_user: row.get(10)?,
+        ticket_user_phone: row.get(11)?,
+        play_status: row.get(12)?,
+        ticket_terminated_user: row.get(13)?,
+        my_ticket_id: row.get(14)?,
+        dyn_template_id: row.get(15)?,
+        template_data: row.get(16)?,
+        pool_name: row.get(17)?,
+        created_at: row.get(18)?,
+        ticket_start: row.get(19)?,
+        ticket_messages_count: row.get(20)?,
+        attachments: serde_json::from_str(&attachments).unwrap_or_default(),
+        company: company_from_row(row, 22)?,
+    })
+}
@@ -0,0 +1,125 @@
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{now_millis, OfflineCache};
+use crate::outbox::{Mutation, OutboxEntry};
+
+const ENTRY_COLUMNS: &str = "id, idempotency_key, ticket_id, mutation, created_at, error";
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<OutboxEntry> {
+    let mutation: String = row.get(3)?;
+    Ok(OutboxEntry {
+        id: row.get(0)?,
+        idempotency_key: row.get(1)?,
+        ticket_id: row.get(2)?,
+        mutation: serde_json::from_str(&mutation).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        created_at: row.get(4)?,
+        error: row.get(5)?,
+    })
+}
+
+fn to_json(mutation: &Mutation) -> rusqlite::Result<String> {
+    serde_json::to_string(mutation)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+impl OfflineCache {
+    /// Stores a write for `user_id`. Enqueuing the same key twice returns the
+    /// entry from the first call.
+    pub fn enqueue_mutation(
+        &self,
+        user_id: u32,
+        idempotency_key: &str,
+        mutation: &Mutation,
+    ) -> Result<i64, String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO outbox (idempotency_key, user_id, ticket_id, mutation, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    idempotency_key,
+                    user_id,
+                    mutation.ticket_id(),
+                    to_json(mutation)?,
+                    now_millis()
+                ],
+            )?;
+            conn.query_row(
+                "SELECT id FROM outbox WHERE idempotency_key = ?1",
+                [idempotency_key],
+                |row| row.get(0),
+            )
+        })
+    }
+
+    /// Entries still waiting to be sent for `user_id`, oldest first.
+    pub fn pending_mutations(&self, user_id: u32) -> Result<Vec<OutboxEntry>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {ENTRY_COLUMNS} FROM outbox
+                 WHERE user_id = ?1 AND error IS NULL ORDER BY id"
+            ))?;
+            let entries = statement.query_map([user_id], entry_from_row)?.collect();
+            entries
+        })
+    }
+
+    pub fn has_pending_before(&self, user_id: u32, id: i64) -> Result<bool, String> {
+        self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT 1 FROM outbox WHERE user_id = ?1 AND error IS NULL AND id < ?2 LIMIT 1",
+                params![user_id, id],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|found| found.is_some())
+        })
+    }
+
+    /// Pending and rejected entries, optionally only those of one ticket.
+    pub fn outbox(&self, user_id: u32, ticket_id: Option<u32>) -> Result<Vec<OutboxEntry>, String> {
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {ENTRY_COLUMNS} FROM outbox
+                 WHERE user_id = ?1 AND (?2 IS NULL OR ticket_id = ?2) ORDER BY id"
+            ))?;
+            let entries = statement
+                .query_map(params![user_id, ticket_id], entry_from_row)?
+                .collect();
+            entries
+        })
+    }
+
+    pub fn remove_mutation(&self, id: i64) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM outbox WHERE id = ?1", [id])
+                .map(|_| ())
+        })
+    }
+
+    pub fn reject_mutation(&self, id: i64, error: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE outbox SET error = ?2 WHERE id = ?1",
+                params![id, error],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Puts a rejected entry back in line, optionally with corrected content.
+    /// It keeps its place in the order it was originally recorded in.
+    pub fn requeue_mutation(&self, id: i64, mutation: Option<&Mutation>) -> Result<(), String> {
+        self.with_conn(|conn| {
+            match mutation {
+                Some(mutation) => conn.execute(
+                    "UPDATE outbox SET error = NULL, mutation = ?2, ticket_id = ?3 WHERE id = ?1",
+                    params![id, to_json(mutation)?, mutation.ticket_id()],
+                ),
+                None => conn.execute("UPDATE outbox SET error = NULL WHERE id = ?1", [id]),
+            }
+            .map(|_| ())
+        })
+    }
+}
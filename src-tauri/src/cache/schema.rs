@@ -0,0 +1,183 @@
+use rusqlite::Connection;
+
+/// Applied in order on startup. `PRAGMA user_version` holds how many already
+/// ran, so only append to this list and never edit a shipped entry.
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE companies (
+    id      INTEGER PRIMARY KEY,
+    name    TEXT NOT NULL,
+    number  TEXT,
+    mail    TEXT,
+    phone   TEXT,
+    zip     TEXT,
+    address TEXT
+);
+
+CREATE TABLE locations (
+    id         INTEGER PRIMARY KEY,
+    company_id INTEGER NOT NULL,
+    name       TEXT NOT NULL
+);
+CREATE INDEX locations_company ON locations (company_id);
+
+CREATE TABLE users (
+    id                INTEGER PRIMARY KEY,
+    name              TEXT NOT NULL,
+    email             TEXT NOT NULL,
+    firstname         TEXT,
+    surname           TEXT,
+    phone             TEXT,
+    company_id        INTEGER NOT NULL,
+    user_group_id     INTEGER NOT NULL,
+    sub_user_group_id INTEGER,
+    location_id       INTEGER,
+    profile_photo_url TEXT,
+    role_id           INTEGER,
+    role_name         TEXT
+);
+CREATE INDEX users_location ON users (location_id);
+
+CREATE TABLE tickets (
+    id                     INTEGER PRIMARY KEY,
+    company_id             INTEGER NOT NULL,
+    location_id            INTEGER,
+    status                 TEXT NOT NULL,
+    status_id              INTEGER NOT NULL,
+    priority               TEXT NOT NULL,
+    priority_index         INTEGER,
+    subject                TEXT,
+    summary                TEXT,
+    description            TEXT NOT NULL,
+    ticket_creator         TEXT,
+    ticket_user            TEXT,
+    ticket_user_phone      TEXT,
+    play_status            TEXT,
+    ticket_terminated_user TEXT,
+    my_ticket_id           INTEGER,
+    dyn_template_id        INTEGER,
+    template_data          TEXT,
+    pool_name              TEXT,
+    created_at             TEXT NOT NULL,
+    ticket_start           TEXT,
+    messages_count         INTEGER NOT NULL DEFAULT 0,
+    attachments            TEXT NOT NULL DEFAULT '[]'
+);
+CREATE INDEX tickets_company ON tickets (company_id);
+
+-- Which tickets the last poll put into new/my/all for a user, in order
+CREATE TABLE ticket_lists (
+    user_id   INTEGER NOT NULL,
+    list      TEXT NOT NULL,
+    position  INTEGER NOT NULL,
+    ticket_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, list, position)
+);
+
+CREATE TABLE histories (
+    id               INTEGER PRIMARY KEY,
+    ticket_id        INTEGER NOT NULL,
+    technician_id    INTEGER NOT NULL,
+    status_id        INTEGER NOT NULL,
+    status_name      TEXT,
+    technician_reply TEXT,
+    created_at       TEXT NOT NULL,
+    updated_at       TEXT NOT NULL,
+    service_start    INTEGER,
+    service_end      INTEGER,
+    total_time       INTEGER,
+    user_id          INTEGER
+);
+CREATE INDEX histories_ticket ON histories (ticket_id);
+
+CREATE TABLE todos (
+    id         INTEGER PRIMARY KEY,
+    ticket_id  INTEGER NOT NULL,
+    user_id    INTEGER NOT NULL,
+    to_do      TEXT NOT NULL,
+    checked    INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX todos_ticket ON todos (ticket_id);
+
+CREATE TABLE messages (
+    id           INTEGER PRIMARY KEY,
+    ticket_id    INTEGER NOT NULL,
+    user_id      INTEGER NOT NULL,
+    author_name  TEXT,
+    author_email TEXT,
+    message      TEXT NOT NULL,
+    created_at   TEXT NOT NULL
+);
+CREATE INDEX messages_ticket ON messages (ticket_id);
+
+-- Free-form JSON with a TTL, what the old localStorage CacheManager held
+CREATE TABLE entries (
+    key        TEXT PRIMARY KEY,
+    data       TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+"#,
+    r#"
+-- Writes waiting for the network. `error` is set once the server rejected
+-- one, those stay until the user retries or discards them.
+CREATE TABLE outbox (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    idempotency_key TEXT NOT NULL UNIQUE,
+    user_id         INTEGER NOT NULL,
+    ticket_id       INTEGER,
+    mutation        TEXT NOT NULL,
+    created_at      INTEGER NOT NULL,
+    error           TEXT
+);
+"#,
+    r#"
+-- The ticket timers as the time tracker knows them. A run starts with play
+-- and ends with stop; `offset_ms` is time the server booked that the
+-- intervals don't cover, like corrections or work from another device.
+CREATE TABLE timers (
+    user_id     INTEGER NOT NULL,
+    ticket_id   INTEGER NOT NULL,
+    play_status INTEGER NOT NULL,
+    started_at  INTEGER NOT NULL,
+    offset_ms   INTEGER NOT NULL DEFAULT 0,
+    synced_at   INTEGER,
+    PRIMARY KEY (user_id, ticket_id)
+);
+
+-- Every stretch a timer ran. `ended_at` stays NULL while it runs, so after
+-- a crash the timer simply goes on from its start.
+CREATE TABLE timer_intervals (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id    INTEGER NOT NULL,
+    ticket_id  INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at   INTEGER
+);
+CREATE INDEX timer_intervals_ticket ON timer_intervals (user_id, ticket_id);
+"#,
+    r#"
+-- Runs that ended used to keep their intervals around for good
+DELETE FROM timer_intervals
+WHERE NOT EXISTS (
+    SELECT 1 FROM timers t
+    WHERE t.user_id = timer_intervals.user_id
+      AND t.ticket_id = timer_intervals.ticket_id
+      AND t.play_status != 4
+      AND timer_intervals.started_at >= t.started_at
+);
+"#,
+];
+
+pub fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (version, sql) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
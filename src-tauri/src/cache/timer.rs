@@ -0,0 +1,243 @@
+use rusqlite::{params, OptionalExtension, Row, Transaction};
+
+use super::{now_millis, OfflineCache};
+use crate::timer::{is_running, ServerTimer, TimerAction, TimerState, STOP};
+
+// How much a run has taken: its offset plus every interval since it
+// started, the open one counted up to now (`?1`).
+const TIMER_COLUMNS: &str = "t.ticket_id, t.play_status, t.started_at, t.offset_ms + COALESCE((
+        SELECT SUM(MAX(COALESCE(i.ended_at, ?1), i.started_at) - i.started_at)
+        FROM timer_intervals i
+        WHERE i.user_id = t.user_id AND i.ticket_id = t.ticket_id
+          AND i.started_at >= t.started_at
+    ), 0)";
+
+fn timer_from_row(row: &Row) -> rusqlite::Result<TimerState> {
+    let play_status: u8 = row.get(1)?;
+    Ok(TimerState {
+        ticket_id: row.get(0)?,
+        play_status,
+        running: is_running(play_status),
+        started_at: row.get(2)?,
+        elapsed_ms: row.get(3)?,
+    })
+}
+
+fn load(
+    tx: &Transaction,
+    user_id: u32,
+    ticket_id: u32,
+    now: i64,
+) -> rusqlite::Result<Option<TimerState>> {
+    tx.query_row(
+        &format!("SELECT {TIMER_COLUMNS} FROM timers t WHERE t.user_id = ?2 AND t.ticket_id = ?3"),
+        params![now, user_id, ticket_id],
+        timer_from_row,
+    )
+    .optional()
+}
+
+/// Starts a new run, forgetting what the previous one added up to.
+fn start(
+    tx: &Transaction,
+    user_id: u32,
+    ticket_id: u32,
+    play_status: u8,
+    at: i64,
+    offset_ms: i64,
+) -> rusqlite::Result<()> {
+    forget_intervals(tx, user_id, ticket_id)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO timers (user_id, ticket_id, play_status, started_at, offset_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![user_id, ticket_id, play_status, at, offset_ms],
+    )?;
+    if is_running(play_status) {
+        open_interval(tx, user_id, ticket_id, at)?;
+    }
+    Ok(())
+}
+
+fn set_status(
+    tx: &Transaction,
+    user_id: u32,
+    ticket_id: u32,
+    play_status: u8,
+) -> rusqlite::Result<()> {
+    tx.execute(
+        "UPDATE timers SET play_status = ?3 WHERE user_id = ?1 AND ticket_id = ?2",
+        params![user_id, ticket_id, play_status],
+    )
+    .map(|_| ())
+}
+
+fn open_interval(tx: &Transaction, user_id: u32, ticket_id: u32, at: i64) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO timer_intervals (user_id, ticket_id, started_at)
+         SELECT ?1, ?2, ?3 WHERE NOT EXISTS (
+             SELECT 1 FROM timer_intervals
+             WHERE user_id = ?1 AND ticket_id = ?2 AND ended_at IS NULL
+         )",
+        params![user_id, ticket_id, at],
+    )
+    .map(|_| ())
+}
+
+fn close_interval(tx: &Transaction, user_id: u32, ticket_id: u32, at: i64) -> rusqlite::Result<()> {
+    tx.execute(
+        "UPDATE timer_intervals SET ended_at = MAX(?3, started_at)
+         WHERE user_id = ?1 AND ticket_id = ?2 AND ended_at IS NULL",
+        params![user_id, ticket_id, at],
+    )
+    .map(|_| ())
+}
+
+/// Drops the intervals of a run that ended, nothing counts them anymore.
+fn forget_intervals(tx: &Transaction, user_id: u32, ticket_id: u32) -> rusqlite::Result<()> {
+    tx.execute(
+        "DELETE FROM timer_intervals WHERE user_id = ?1 AND ticket_id = ?2",
+        params![user_id, ticket_id],
+    )
+    .map(|_| ())
+}
+
+/// Moves a timer to `play_status`, opening or closing its interval at `at`.
+fn transition(
+    tx: &Transaction,
+    user_id: u32,
+    ticket_id: u32,
+    play_status: u8,
+    at: i64,
+) -> rusqlite::Result<()> {
+    if is_running(play_status) {
+        open_interval(tx, user_id, ticket_id, at)?;
+    } else if play_status == STOP {
+        forget_intervals(tx, user_id, ticket_id)?;
+    } else {
+        close_interval(tx, user_id, ticket_id, at)?;
+    }
+    set_status(tx, user_id, ticket_id, play_status)
+}
+
+impl OfflineCache {
+    /// Records a play, pause, resume or stop the server accepted, which took
+    /// effect at `at`.
+    pub fn record_timer(
+        &self,
+        user_id: u32,
+        ticket_id: u32,
+        action: TimerAction,
+        at: i64,
+    ) -> Result<(), String> {
+        self.transaction(|tx| {
+            let current = load(tx, user_id, ticket_id, at)?;
+            match (action, current) {
+                (TimerAction::Play, _) | (TimerAction::Resume, None) => {
+                    start(tx, user_id, ticket_id, action.play_status(), at, 0)
+                }
+                (_, None) => Ok(()),
+                (_, Some(_)) => transition(tx, user_id, ticket_id, action.play_status(), at),
+            }
+        })
+    }
+
+    /// Takes over what `/getPlayerStatus` says. The server's state wins, and
+    /// so does its total once ours is off by more than its precision.
+    pub fn reconcile_timer(
+        &self,
+        user_id: u32,
+        ticket_id: u32,
+        server: Option<&ServerTimer>,
+    ) -> Result<(), String> {
+        let at = now_millis();
+        self.transaction(|tx| {
+            let current = load(tx, user_id, ticket_id, at)?;
+            match (server, current) {
+                (None, None) => return Ok(()),
+                (None, Some(local)) if local.play_status == STOP => return Ok(()),
+                (None, Some(_)) => return transition(tx, user_id, ticket_id, STOP, at),
+                (Some(server), None) => start(
+                    tx,
+                    user_id,
+                    ticket_id,
+                    server.play_status,
+                    at,
+                    server.elapsed_ms,
+                )?,
+                (Some(server), Some(local)) if local.play_status == STOP => start(
+                    tx,
+                    user_id,
+                    ticket_id,
+                    server.play_status,
+                    at,
+                    server.elapsed_ms,
+                )?,
+                (Some(server), Some(local)) => {
+                    transition(tx, user_id, ticket_id, server.play_status, at)?;
+                    let drift = server.elapsed_ms - local.elapsed_ms;
+                    if drift.abs() > server.tolerance_ms {
+                        tx.execute(
+                            "UPDATE timers SET offset_ms = offset_ms + ?3
+                             WHERE user_id = ?1 AND ticket_id = ?2",
+                            params![user_id, ticket_id, drift],
+                        )?;
+                    }
+                }
+            }
+            tx.execute(
+                "UPDATE timers SET synced_at = ?3 WHERE user_id = ?1 AND ticket_id = ?2",
+                params![user_id, ticket_id, at],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Counts `from..to` towards the current run, as if the timer had run.
+    pub fn add_timer_interval(
+        &self,
+        user_id: u32,
+        ticket_id: u32,
+        from: i64,
+        to: i64,
+    ) -> Result<(), String> {
+        self.transaction(|tx| {
+            tx.execute(
+                "INSERT INTO timer_intervals (user_id, ticket_id, started_at, ended_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![user_id, ticket_id, from, to],
+            )
+            .map(|_| ())
+        })
+    }
+
+    pub fn timer(&self, user_id: u32, ticket_id: u32) -> Result<Option<TimerState>, String> {
+        let now = now_millis();
+        self.with_conn(|conn| {
+            conn.query_row(
+                &format!(
+                    "SELECT {TIMER_COLUMNS} FROM timers t
+                     WHERE t.user_id = ?2 AND t.ticket_id = ?3 AND t.play_status != {STOP}"
+                ),
+                params![now, user_id, ticket_id],
+                timer_from_row,
+            )
+            .optional()
+        })
+    }
+
+    /// Timers that are running or paused, most recently started first.
+    pub fn open_timers(&self, user_id: u32) -> Result<Vec<TimerState>, String> {
+        let now = now_millis();
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {TIMER_COLUMNS} FROM timers t
+                 WHERE t.user_id = ?2 AND t.play_status != {STOP}
+                 ORDER BY t.started_at DESC"
+            ))?;
+            let timers = statement
+                .query_map(params![now, user_id], timer_from_row)?
+                .collect();
+            timers
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Runtime, State};
+
+use super::{CalendarFeed, FeedStatus};
+use crate::export;
+
+#[tauri::command]
+pub fn get_calendar_feed(feed: State<'_, CalendarFeed>) -> FeedStatus {
+    feed.status()
+}
+
+/// `port` 0 lets the system pick one.
+#[tauri::command]
+pub async fn set_calendar_feed<R: Runtime>(
+    app: AppHandle<R>,
+    feed: State<'_, CalendarFeed>,
+    enabled: bool,
+    port: u16,
+) -> Result<FeedStatus, String> {
+    feed.configure(&app, enabled, port).await
+}
+
+#[tauri::command]
+pub fn reset_calendar_feed_url(feed: State<'_, CalendarFeed>) -> Result<FeedStatus, String> {
+    feed.reset_token()
+}
+
+/// Saves the appointments as an `.ics` file; returns how many there are.
+#[tauri::command]
+pub async fn export_appointments<R: Runtime>(
+    app: AppHandle<R>,
+    path: PathBuf,
+) -> Result<usize, String> {
+    let (calendar, count) = super::calendar(&app).await;
+    export::write(&path, calendar.as_bytes())?;
+    Ok(count)
+}
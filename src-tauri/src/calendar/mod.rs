@@ -0,0 +1,342 @@
+//! Scheduled tickets as an iCalendar feed, saved to a file or served on
+//! 127.0.0.1 so calendar apps can subscribe to it.
+
+pub mod commands;
+mod server;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::{Location, Ticket, TicketFilter};
+use crate::export::ics;
+
+const SETTINGS_FILE: &str = "calendar-feed.json";
+/// Appointments have a start only; calendars show them as an hour.
+const APPOINTMENT_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct FeedSettings {
+    enabled: bool,
+    /// 0 picks a free port when the feed is turned on. The port it got is
+    /// kept, so subscriptions survive restarts.
+    port: u16,
+    /// Secret part of the URL, so other local users and web pages can't
+    /// read the feed off 127.0.0.1.
+    token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedStatus {
+    pub enabled: bool,
+    pub port: u16,
+    /// What to subscribe to, while the feed is served.
+    pub url: Option<String>,
+}
+
+pub struct CalendarFeed {
+    path: PathBuf,
+    settings: Mutex<FeedSettings>,
+    server: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CalendarFeed {
+    pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+        let path = config_dir.join(SETTINGS_FILE);
+
+        let settings = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            settings: Mutex::new(settings),
+            server: Mutex::new(None),
+        })
+    }
+
+    /// Serves the feed if it was on when the app quit.
+    pub fn resume<R: Runtime>(&self, app: &AppHandle<R>) {
+        let mut settings = self.settings.lock().unwrap().clone();
+        if settings.enabled {
+            if let Err(e) = self.serve(app, &mut settings) {
+                log::error!("Failed to serve the calendar feed: {}", e);
+            }
+        }
+    }
+
+    pub fn status(&self) -> FeedStatus {
+        let settings = self.settings.lock().unwrap();
+        let serving = self.server.lock().unwrap().is_some();
+        FeedStatus {
+            enabled: settings.enabled,
+            port: settings.port,
+            url: serving.then(|| {
+                format!(
+                    "http://127.0.0.1:{}/{}/appointments.ics",
+                    settings.port, settings.token
+                )
+            }),
+        }
+    }
+
+    /// Turns the feed on or off, or moves it to another port. If the port
+    /// is taken the feed stays as it was.
+    pub async fn configure<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        enabled: bool,
+        port: u16,
+    ) -> Result<FeedStatus, String> {
+        let previous = self.settings.lock().unwrap().clone();
+        let mut settings = FeedSettings {
+            enabled,
+            port,
+            ..previous.clone()
+        };
+        if settings.token.is_empty() {
+            settings.token = new_token();
+        }
+        let serving = self.server.lock().unwrap().is_some();
+        if settings == previous && serving == enabled {
+            return Ok(self.status());
+        }
+
+        self.stop().await;
+        if enabled {
+            if let Err(e) = self.serve(app, &mut settings) {
+                self.resume(app);
+                return Err(e);
+            }
+        }
+        self.save(settings)?;
+        Ok(self.status())
+    }
+
+    /// A new URL; subscriptions to the old one stop working.
+    pub fn reset_token(&self) -> Result<FeedStatus, String> {
+        let settings = FeedSettings {
+            token: new_token(),
+            ..self.settings.lock().unwrap().clone()
+        };
+        self.save(settings)?;
+        Ok(self.status())
+    }
+
+    fn token(&self) -> String {
+        self.settings.lock().unwrap().token.clone()
+    }
+
+    fn serve<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        settings: &mut FeedSettings,
+    ) -> Result<(), String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))
+            .map_err(|e| format!("Port {} is not available: {}", settings.port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        settings.port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        *self.server.lock().unwrap() = Some(tauri::async_runtime::spawn(server::serve(
+            app.clone(),
+            listener,
+        )));
+        Ok(())
+    }
+
+    /// Waits until the listener is closed, so its port can be bound again.
+    async fn stop(&self) {
+        let server = self.server.lock().unwrap().take();
+        if let Some(server) = server {
+            server.abort();
+            let _ = server.await;
+        }
+    }
+
+    fn save(&self, settings: FeedSettings) -> Result<(), String> {
+        let data = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        fs::write(&self.path, data).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+}
+
+fn new_token() -> String {
+    rand::random::<[u8; 16]>()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The appointments of every signed in account. One that can't be read is
+/// left out rather than failing the whole calendar.
+pub async fn calendar<R: Runtime>(app: &AppHandle<R>) -> (String, usize) {
+    let mut events = Vec::new();
+    for account in app.state::<Accounts>().all() {
+        match appointments(&account).await {
+            Ok(appointments) => events.extend(appointments),
+            Err(e) => log::error!("Failed to load appointments of {}: {}", account.id, e),
+        }
+    }
+    events.sort_by_key(|event| match event.time {
+        ics::Time::Span(start, _) => start,
+        ics::Time::Day(day) => day.and_time(Default::default()).and_utc(),
+    });
+    (
+        ics::to_string("Ticketbase appointments", &events),
+        events.len(),
+    )
+}
+
+/// The account's own tickets that have a `ticket_start`, from the cache the
+/// poller keeps fresh, or from the server before the first poll.
+async fn appointments(account: &Account) -> Result<Vec<ics::Event>, String> {
+    let tickets = match account.cache.tickets(account.user.id)? {
+        Some(response) => response.my_tickets,
+        None => {
+            let response = account
+                .api
+                .get_tickets(&TicketFilter::for_user(&account.user))
+                .await
+                .map_err(|e| e.to_string())?;
+            account.cache.store_tickets(account.user.id, &response);
+            response.my_tickets
+        }
+    };
+
+    let mut locations: HashMap<u32, Vec<Location>> = HashMap::new();
+    let mut events = Vec::new();
+    for ticket in tickets {
+        let Some(time) = ticket.ticket_start.as_deref().and_then(parse_start) else {
+            continue;
+        };
+        if let Entry::Vacant(vacant) = locations.entry(ticket.company.id) {
+            vacant.insert(company_locations(account, ticket.company.id).await);
+        }
+        let location = ticket
+            .location_id
+            .and_then(|id| {
+                locations[&ticket.company.id]
+                    .iter()
+                    .find(|location| location.id == id)
+            })
+            .map(|location| location.name.as_str());
+        events.push(event(account, &ticket, time, location));
+    }
+    Ok(events)
+}
+
+async fn company_locations(account: &Account, company_id: u32) -> Vec<Location> {
+    match account.cache.locations(company_id) {
+        Ok(locations) if !locations.is_empty() => return locations,
+        _ => {}
+    }
+    match account.api.get_customer_locations(company_id).await {
+        Ok(response) => {
+            let locations = response.payload.data.unwrap_or_default().locations;
+            account.cache.store_locations(company_id, &locations);
+            locations
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Entered in the user's local time, like the frontend shows it; a date
+/// alone is an all-day appointment.
+fn parse_start(text: &str) -> Option<ics::Time> {
+    let text = text.trim();
+    let span = |start: DateTime<Utc>| {
+        ics::Time::Span(start, start + TimeDelta::minutes(APPOINTMENT_MINUTES))
+    };
+    if let Ok(start) = DateTime::parse_from_rfc3339(text) {
+        return Some(span(start.with_timezone(&Utc)));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(start) = NaiveDateTime::parse_from_str(text, format) {
+            let start = Local.from_local_datetime(&start).earliest()?;
+            return Some(span(start.with_timezone(&Utc)));
+        }
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .map(ics::Time::Day)
+}
+
+fn event(
+    account: &Account,
+    ticket: &Ticket,
+    time: ics::Time,
+    location: Option<&str>,
+) -> ics::Event {
+    let company = &ticket.company;
+    let topic = ticket
+        .summary
+        .as_deref()
+        .or(ticket.subject.as_deref())
+        .filter(|topic| !topic.trim().is_empty());
+
+    let mut summary = format!("#{}", ticket.id);
+    for part in [topic, Some(company.name.as_str())].into_iter().flatten() {
+        if !part.is_empty() {
+            summary.push_str(" · ");
+            summary.push_str(part);
+        }
+    }
+
+    let mut body = Vec::new();
+    let mut line = |label: &str, value: Option<&str>| {
+        if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+            body.push(format!("{label}: {value}"));
+        }
+    };
+    line("Company", Some(company.name.as_str()));
+    line("Customer number", company.number.as_deref());
+    line("Location", location);
+    line("Contact", ticket.ticket_user.as_deref());
+    line("Phone", ticket.ticket_user_phone.as_deref());
+    line("Company phone", company.company_phone.as_deref());
+    line("Status", Some(ticket.status.as_str()));
+    let description = ticket.description.trim();
+    if !description.is_empty() {
+        body.push(String::new());
+        body.push(description.to_string());
+    }
+
+    let address: Vec<&str> = [
+        Some(company.name.as_str()),
+        location,
+        company.company_adress.as_deref(),
+        company.company_zip.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|part| !part.is_empty())
+    .collect();
+
+    ics::Event {
+        uid: format!("ticket-{}-{}@ticketbase-desktop", ticket.id, account.id),
+        time,
+        summary,
+        location: (!address.is_empty()).then(|| address.join(", ")),
+        description: Some(body.join("\n")),
+    }
+}
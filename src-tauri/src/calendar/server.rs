@@ -0,0 +1,92 @@
+//! Just enough HTTP/1.1 for calendar apps to fetch the feed: `GET` or
+//! `HEAD` of its one URL, one request per connection.
+
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::CalendarFeed;
+
+/// Calendar apps send a few hundred bytes.
+const MAX_REQUEST: usize = 16 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(super) async fn serve<R: Runtime>(app: AppHandle<R>, listener: std::net::TcpListener) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to serve the calendar feed: {}", e);
+            return;
+        }
+    };
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                // Out of file descriptors, most likely; don't spin on it
+                log::warn!("Failed to accept a calendar feed connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = respond(&app, stream).await {
+                log::warn!("Failed to answer a calendar feed request: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond<R: Runtime>(app: &AppHandle<R>, mut stream: TcpStream) -> std::io::Result<()> {
+    let head = match tokio::time::timeout(READ_TIMEOUT, read_head(&mut stream)).await {
+        Ok(head) => head?,
+        Err(_) => return Ok(()),
+    };
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+    let feed = format!("/{}/appointments.ics", app.state::<CalendarFeed>().token());
+
+    let (status, content_type, body) = match method {
+        "GET" | "HEAD" if path == feed => {
+            let (calendar, _) = super::calendar(app).await;
+            ("200 OK", "text/calendar; charset=utf-8", calendar)
+        }
+        "GET" | "HEAD" => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Everything up to the blank line; the feed takes no request body.
+async fn read_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST {
+            return Err(std::io::Error::other("request too large"));
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
@@ -0,0 +1,296 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::accounts::{AccountInfo, Accounts};
+use crate::api::models::User;
+
+/// Where the single session of earlier versions was kept.
+const LEGACY_ACCOUNT: &str = "session";
+const NONCE_LEN: usize = 12;
+/// Random bytes mixed into the key of the encrypted files, so the machine id,
+/// which anyone on the machine can read, does not open them on its own.
+/// It lies next to them in the app data dir.
+const INSTALL_SECRET_FILE: &str = "install.key";
+const INSTALL_SECRET_LEN: usize = 32;
+
+/// What we need to restore a login without asking for the password again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub token: String,
+    pub user: User,
+    /// The server profile the token belongs to; older sessions are from the
+    /// hosted one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
+}
+
+/// Keeps one session per account, and the few other secrets, in the OS
+/// keyring (Secret Service, Keychain, Credential Manager). Systems without a
+/// keyring get encrypted files in the app data dir instead, readable only by
+/// the user. Their key is in the same dir, so they keep other users out but
+/// not someone who copies the whole dir; only a keyring protects against
+/// that.
+pub struct CredentialStore {
+    service: String,
+    data_dir: PathBuf,
+}
+
+impl CredentialStore {
+    pub fn new<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            service: app.config().identifier.clone(),
+            data_dir,
+        })
+    }
+
+    /// Keyring user and fallback file of an account's session. Separate
+    /// entries, Credential Manager only takes 2560 bytes per secret.
+    fn session_slot(&self, account: &str) -> (String, PathBuf) {
+        if account == LEGACY_ACCOUNT {
+            (
+                LEGACY_ACCOUNT.to_string(),
+                self.data_dir.join("session.bin"),
+            )
+        } else {
+            (
+                format!("session:{}", account),
+                self.data_dir.join(format!("session-{}.bin", account)),
+            )
+        }
+    }
+
+    fn secret_slot(&self, name: &str) -> (String, PathBuf) {
+        (
+            format!("secret:{}", name),
+            self.data_dir.join(format!("secret-{}.bin", name)),
+        )
+    }
+
+    fn read(&self, (user, file): &(String, PathBuf)) -> Result<Option<String>, String> {
+        match keyring::Entry::new(&self.service, user).and_then(|entry| entry.get_password()) {
+            Ok(secret) => return Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("Keyring unavailable, using encrypted file: {}", e),
+        }
+
+        self.load_fallback(file)
+    }
+
+    fn write(&self, (user, file): &(String, PathBuf), secret: &str) -> Result<(), String> {
+        match keyring::Entry::new(&self.service, user).and_then(|entry| entry.set_password(secret))
+        {
+            Ok(()) => {
+                // Don't leave an older copy lying around once the keyring works
+                let _ = fs::remove_file(file);
+                Ok(())
+            }
+            Err(e) => {
+                log::warn!("Keyring unavailable, using encrypted file: {}", e);
+                self.store_fallback(file, secret.as_bytes())
+            }
+        }
+    }
+
+    fn delete(&self, (user, file): &(String, PathBuf)) -> Result<(), String> {
+        match keyring::Entry::new(&self.service, user).and_then(|entry| entry.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("Failed to delete keyring entry: {}", e),
+        }
+
+        match fs::remove_file(file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn load(&self, account: &str) -> Result<Option<Session>, String> {
+        match self.read(&self.session_slot(account))? {
+            Some(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// The session of a version that only knew one account.
+    pub fn load_legacy(&self) -> Result<Option<Session>, String> {
+        self.load(LEGACY_ACCOUNT)
+    }
+
+    pub fn store(&self, account: &str, session: &Session) -> Result<(), String> {
+        let json = serde_json::to_string(session).map_err(|e| e.to_string())?;
+        self.write(&self.session_slot(account), &json)
+    }
+
+    pub fn clear_legacy(&self) -> Result<(), String> {
+        self.clear(LEGACY_ACCOUNT)
+    }
+
+    pub fn clear(&self, account: &str) -> Result<(), String> {
+        self.delete(&self.session_slot(account))
+    }
+
+    /// Other secrets the app needs to remember, like the proxy password.
+    pub fn load_secret(&self, name: &str) -> Result<Option<String>, String> {
+        self.read(&self.secret_slot(name))
+    }
+
+    pub fn store_secret(&self, name: &str, secret: &str) -> Result<(), String> {
+        self.write(&self.secret_slot(name), secret)
+    }
+
+    pub fn clear_secret(&self, name: &str) -> Result<(), String> {
+        self.delete(&self.secret_slot(name))
+    }
+
+    /// Created on first use, like the files it protects.
+    fn install_secret(&self) -> Result<Vec<u8>, String> {
+        let file = self.data_dir.join(INSTALL_SECRET_FILE);
+        match fs::read(&file) {
+            Ok(secret) if secret.len() == INSTALL_SECRET_LEN => return Ok(secret),
+            Ok(_) => return Err("Install secret is corrupt".to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+        write_private(&file, &secret)?;
+        Ok(secret)
+    }
+
+    fn cipher(&self) -> Result<ChaCha20Poly1305, String> {
+        let install_secret = self.install_secret()?;
+        Self::machine_cipher(&self.service, Some(&install_secret))
+    }
+
+    /// Keyed to this machine, and to this install once given its secret.
+    /// Files of earlier versions only used the machine.
+    fn machine_cipher(
+        service: &str,
+        install_secret: Option<&[u8]>,
+    ) -> Result<ChaCha20Poly1305, String> {
+        let machine_id = machine_uid::get().map_err(|e| e.to_string())?;
+        let mut hasher = Sha256::new()
+            .chain_update(service.as_bytes())
+            .chain_update(machine_id.as_bytes());
+        if let Some(secret) = install_secret {
+            hasher.update(secret);
+        }
+
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize())))
+    }
+
+    fn load_fallback(&self, file: &Path) -> Result<Option<String>, String> {
+        let data = match fs::read(file) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        if data.len() < NONCE_LEN {
+            return Err("Credential file is corrupt".to_string());
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::from_slice(nonce);
+        let plaintext = match self.cipher()?.decrypt(nonce, ciphertext) {
+            Ok(plaintext) => plaintext,
+            Err(_) => {
+                let plaintext = Self::machine_cipher(&self.service, None)?
+                    .decrypt(nonce, ciphertext)
+                    // Written on another machine or tampered with
+                    .map_err(|_| "Credential file could not be decrypted".to_string())?;
+                // From before the install secret; move it over to the new key
+                self.store_fallback(file, &plaintext)?;
+                plaintext
+            }
+        };
+
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    fn store_fallback(&self, file: &Path, plaintext: &[u8]) -> Result<(), String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, plaintext)
+            .map_err(|e| e.to_string())?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        write_private(file, &data)
+    }
+}
+
+/// Writes a file only the user can read, through a temporary file so a
+/// crash never leaves half of it behind.
+pub(crate) fn write_private(file: &Path, data: &[u8]) -> Result<(), String> {
+    let temp = file.with_extension("tmp");
+    // A leftover could have other permissions, which opening keeps
+    match fs::remove_file(&temp) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut out = options.open(&temp).map_err(|e| e.to_string())?;
+    out.write_all(data)
+        .and_then(|_| out.sync_all())
+        .map_err(|e| e.to_string())?;
+    drop(out);
+    fs::rename(&temp, file).map_err(|e| e.to_string())
+}
+
+/// Moves a session the frontend kept in localStorage before sessions lived
+/// in Rust into an account, and makes it the active one.
+#[tauri::command]
+pub fn store_session<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    session: Session,
+) -> Result<AccountInfo, String> {
+    let account = accounts.add(&app, session)?;
+    accounts.activate(&app, &account.id)?;
+    Ok(accounts.info(&app, &account))
+}
+
+/// The active account, if any account is signed in.
+#[tauri::command]
+pub fn load_session<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+) -> Option<AccountInfo> {
+    accounts
+        .active()
+        .ok()
+        .map(|account| accounts.info(&app, &account))
+}
+
+/// Signs the active account out; another signed in account takes over.
+#[tauri::command]
+pub fn clear_session<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    discard_unsent: bool,
+) -> Result<(), String> {
+    match accounts.active() {
+        Ok(account) => accounts.remove(&app, &account.id, discard_unsent),
+        Err(_) => Ok(()),
+    }
+}
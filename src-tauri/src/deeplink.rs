@@ -0,0 +1,164 @@
+//! `ticketbase://` links, as pasted into chat and email: `ticket/123` opens
+//! a ticket, `new?company=…&description=…&priority=…` a prefilled new ticket
+//! form. A link is checked in full before any window is touched.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::notifications;
+
+pub const SCHEME: &str = "ticketbase";
+
+/// Longest description the new ticket form accepts.
+const MAX_DESCRIPTION: usize = 1000;
+const MAX_COMPANY: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    Ticket(u32),
+    NewTicket(NewTicketDraft),
+}
+
+/// What a `new` link fills into the new ticket form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTicketDraft {
+    /// Id, customer number or name; the form picks the matching company.
+    pub company: Option<String>,
+    pub description: Option<String>,
+    /// As the form sends it: `NORMAL`, `HIGH` or `VERY_HIGH`.
+    pub priority: Option<String>,
+}
+
+/// A draft from a link, until the new ticket window asks for it.
+#[derive(Default)]
+pub struct DeepLinks {
+    draft: Mutex<Option<NewTicketDraft>>,
+}
+
+impl DeepLink {
+    pub fn parse(link: &str) -> Result<Self, String> {
+        let url = Url::parse(link.trim()).map_err(|e| format!("Not a link: {}", e))?;
+        if url.scheme() != SCHEME {
+            return Err(format!("Not a {}:// link", SCHEME));
+        }
+        let path = url.path().trim_matches('/');
+        match url.host_str().unwrap_or_default() {
+            "ticket" => {
+                if url.query().is_some_and(|query| !query.is_empty()) {
+                    return Err("Ticket links take no parameters".to_string());
+                }
+                match path.parse::<u32>() {
+                    Ok(ticket_id) if ticket_id > 0 => Ok(DeepLink::Ticket(ticket_id)),
+                    _ => Err(format!("Not a ticket number: {:?}", path)),
+                }
+            }
+            "new" => {
+                if !path.is_empty() {
+                    return Err(format!("Unknown link: {}", link.trim()));
+                }
+                NewTicketDraft::from_query(&url).map(DeepLink::NewTicket)
+            }
+            other => Err(format!("Unknown link target: {:?}", other)),
+        }
+    }
+}
+
+impl NewTicketDraft {
+    fn from_query(url: &Url) -> Result<Self, String> {
+        let mut draft = NewTicketDraft::default();
+        for (key, value) in url.query_pairs() {
+            let (field, limit) = match key.as_ref() {
+                "company" => (&mut draft.company, MAX_COMPANY),
+                "description" => (&mut draft.description, MAX_DESCRIPTION),
+                "priority" => (&mut draft.priority, usize::MAX),
+                _ => return Err(format!("Unknown parameter: {:?}", key)),
+            };
+            if field.is_some() {
+                return Err(format!("Parameter given twice: {:?}", key));
+            }
+            // Line breaks stay, other control characters have no place in a form
+            let value: String = value
+                .chars()
+                .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+                .collect::<String>()
+                .trim()
+                .to_string();
+            if value.chars().count() > limit {
+                return Err(format!("{} is longer than {} characters", key, limit));
+            }
+            if !value.is_empty() {
+                *field = Some(value);
+            }
+        }
+        if let Some(priority) = &mut draft.priority {
+            // The names the form shows, not the API's
+            *priority = match priority.to_lowercase().as_str() {
+                "low" => "NORMAL",
+                "medium" => "HIGH",
+                "high" => "VERY_HIGH",
+                _ => return Err(format!("Unknown priority: {:?}", priority)),
+            }
+            .to_string();
+        }
+        Ok(draft)
+    }
+}
+
+/// Opens what a link points to.
+pub fn open<R: Runtime>(app: &AppHandle<R>, link: &str) -> Result<(), String> {
+    match DeepLink::parse(link)? {
+        DeepLink::Ticket(ticket_id) => crate::show_ticket_window(app, ticket_id, None),
+        DeepLink::NewTicket(draft) => {
+            *app.state::<DeepLinks>().draft.lock().unwrap() = Some(draft);
+            crate::show_new_ticket_window(app)?;
+            // A form that was already open doesn't ask again by itself
+            let _ = app.emit_to("new-ticket", "deeplink://new-ticket", ());
+            Ok(())
+        }
+    }
+}
+
+/// Opens links the app was started or woken with. A broken one is reported
+/// with a notification, as no window may be in view.
+pub fn open_all<R: Runtime>(app: &AppHandle<R>, links: &[Url]) {
+    for link in links {
+        if let Err(e) = open(app, link.as_str()) {
+            log::error!("Failed to open {}: {}", link, e);
+            notifications::notify(app, "Link not opened", &e);
+        }
+    }
+}
+
+/// Registers the scheme where the installer may not have, then opens the
+/// links the app was started with and those it receives from now on.
+pub fn listen<R: Runtime>(app: &AppHandle<R>) {
+    let deep_link = app.deep_link();
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = deep_link.register_all() {
+        log::error!("Failed to register {}:// links: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    deep_link.on_open_url(move |event| open_all(&handle, &event.urls()));
+    match deep_link.get_current() {
+        Ok(Some(links)) => open_all(app, &links),
+        Ok(None) => {}
+        Err(e) => log::error!("Failed to read the link the app was started with: {}", e),
+    }
+}
+
+/// Opens a `ticketbase://` link, as pasted into the app.
+#[tauri::command]
+pub fn open_deep_link<R: Runtime>(app: AppHandle<R>, link: String) -> Result<(), String> {
+    open(&app, &link)
+}
+
+/// The draft of the last `new` link, once.
+#[tauri::command]
+pub fn take_new_ticket_draft(deep_links: State<'_, DeepLinks>) -> Option<NewTicketDraft> {
+    deep_links.draft.lock().unwrap().take()
+}
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tauri_plugin_http::reqwest::{header, Response, StatusCode};
+use tokio::sync::Notify;
+
+use crate::accounts::Accounts;
+use crate::api::{ApiClient, ApiError};
+use crate::tempfiles::TempFiles;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    id: String,
+    received: u64,
+    /// `None` when the server did not say how large the file is.
+    total: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Hex encoded.
+    pub sha256: String,
+}
+
+/// What we learned about a partial download, next to its `.part` file, so
+/// a later attempt can ask for just the rest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PartInfo {
+    /// `ETag` or `Last-Modified`, sent back as `If-Range`.
+    validator: Option<String>,
+    total: Option<u64>,
+    /// SHA-256 of the whole file if the server told us.
+    sha256: Option<Vec<u8>>,
+}
+
+struct Paths {
+    target: PathBuf,
+    part: PathBuf,
+    info: PathBuf,
+}
+
+impl Paths {
+    fn new(
+        temp_files: &TempFiles,
+        account_id: &str,
+        ticket_id: u32,
+        filename: &str,
+    ) -> Result<Self, String> {
+        // Never let a file name from the server pick the directory
+        let name = Path::new(filename)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("Invalid file name: {}", filename))?;
+
+        // Parts outlive the session so they can be resumed after a restart.
+        // Ticket ids are per server, so each account keeps its own.
+        let partial_dir = temp_files
+            .partial_dir()
+            .join(account_id)
+            .join(ticket_id.to_string());
+        let target_dir = temp_files.session_dir().join(ticket_id.to_string());
+        fs::create_dir_all(&partial_dir).map_err(|e| e.to_string())?;
+        fs::create_dir_all(&target_dir).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            target: target_dir.join(name),
+            part: partial_dir.join(format!("{}.part", name)),
+            info: partial_dir.join(format!("{}.part.json", name)),
+        })
+    }
+
+    /// `target`, or a numbered sibling if an earlier copy is still open in a
+    /// viewer that keeps Windows from replacing it.
+    fn free_target(&self) -> PathBuf {
+        if !self.target.exists() || fs::remove_file(&self.target).is_ok() {
+            return self.target.clone();
+        }
+
+        let stem = self
+            .target
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let extension = self
+            .target
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+        (2..)
+            .map(|n| {
+                self.target
+                    .with_file_name(format!("{} ({}){}", stem, n, extension))
+            })
+            .find(|candidate| !candidate.exists())
+            .unwrap()
+    }
+
+    /// The partial file and what we know about it, if it can be resumed.
+    fn resumable(&self) -> Option<(u64, PartInfo)> {
+        let info = fs::read(&self.info)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())?;
+        let size = fs::metadata(&self.part).ok()?.len();
+        (size > 0).then_some((size, info))
+    }
+
+    fn discard_part(&self) {
+        let _ = fs::remove_file(&self.part);
+        let _ = fs::remove_file(&self.info);
+    }
+}
+
+struct Active {
+    cancel: Arc<Notify>,
+    part: PathBuf,
+}
+
+/// Running downloads by the id the frontend gave them, to cancel them.
+#[derive(Default)]
+pub struct Downloads {
+    active: Mutex<HashMap<String, Active>>,
+}
+
+/// Forgets the download however the command ends.
+struct ActiveGuard<'a> {
+    downloads: &'a Downloads,
+    id: String,
+}
+
+impl Drop for ActiveGuard<'_> {
+    fn drop(&mut self) {
+        self.downloads.active.lock().unwrap().remove(&self.id);
+    }
+}
+
+fn header_str(response: &Response, name: impl header::AsHeaderName) -> Option<&str> {
+    response.headers().get(name)?.to_str().ok()
+}
+
+/// The total size from `Content-Range: bytes 100-999/1000`.
+fn range_total(response: &Response) -> Option<u64> {
+    header_str(response, header::CONTENT_RANGE)?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// A SHA-256 of the whole file from `Repr-Digest: sha-256=:<b64>:` or the
+/// older `Digest: SHA-256=<b64>`. `Content-Digest` is skipped on purpose, for
+/// a range it only covers the part that was sent.
+fn advertised_sha256(response: &Response) -> Option<Vec<u8>> {
+    ["repr-digest", "digest"]
+        .into_iter()
+        .filter_map(|name| header_str(response, name))
+        .flat_map(|value| value.split(','))
+        .find_map(|item| {
+            let (algorithm, value) = item.trim().split_once('=')?;
+            if !algorithm.eq_ignore_ascii_case("sha-256") {
+                return None;
+            }
+            base64::engine::general_purpose::STANDARD
+                .decode(value.trim_matches(':'))
+                .ok()
+        })
+}
+
+fn sha256_of(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finalize().to_vec());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+enum Attempt {
+    Done(PartInfo),
+    /// The server would not continue our partial file, start over.
+    Restart,
+}
+
+/// Streams one response into the `.part` file.
+async fn fetch<R: Runtime>(
+    app: &AppHandle<R>,
+    api: &ApiClient,
+    id: &str,
+    ticket_id: u32,
+    filename: &str,
+    paths: &Paths,
+    cancel: &Notify,
+) -> Result<Attempt, String> {
+    let (offset, known) = paths.resumable().unwrap_or_default();
+    if offset == 0 {
+        paths.discard_part();
+    }
+
+    let mut response = match api
+        .download_attachment(ticket_id, filename, offset, known.validator.as_deref())
+        .await
+    {
+        // The part we have is not valid for the file on the server anymore
+        Err(ApiError::Http { status: 416, .. }) if offset > 0 => return Ok(Attempt::Restart),
+        result => result.map_err(|e| e.to_string())?,
+    };
+
+    let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let info = if resumed {
+        PartInfo {
+            total: range_total(&response).or(known.total),
+            sha256: advertised_sha256(&response).or(known.sha256),
+            ..known
+        }
+    } else {
+        PartInfo {
+            validator: header_str(&response, header::ETAG)
+                .or_else(|| header_str(&response, header::LAST_MODIFIED))
+                .map(str::to_string),
+            total: response.content_length(),
+            sha256: advertised_sha256(&response),
+        }
+    };
+
+    // Only keep what we have on failure if the server can send the rest later
+    let can_resume = resumed || header_str(&response, header::ACCEPT_RANGES) == Some("bytes");
+    if can_resume {
+        let json = serde_json::to_vec(&info).map_err(|e| e.to_string())?;
+        fs::write(&paths.info, json).map_err(|e| e.to_string())?;
+    } else {
+        let _ = fs::remove_file(&paths.info);
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&paths.part)
+        .map_err(|e| e.to_string())?;
+    let mut received = if resumed { offset } else { 0 };
+    let mut last_progress: Option<Instant> = None;
+
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk,
+            _ = cancel.notified() => {
+                drop(file);
+                paths.discard_part();
+                return Err("Download cancelled".to_string());
+            }
+        };
+
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                if !can_resume {
+                    drop(file);
+                    paths.discard_part();
+                }
+                return Err(ApiError::from(e).to_string());
+            }
+        };
+
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        received += chunk.len() as u64;
+
+        if last_progress.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            last_progress = Some(Instant::now());
+            let _ = app.emit(
+                "download://progress",
+                Progress {
+                    id: id.to_string(),
+                    received,
+                    total: info.total,
+                },
+            );
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    let _ = app.emit(
+        "download://progress",
+        Progress {
+            id: id.to_string(),
+            received,
+            total: info.total,
+        },
+    );
+    Ok(Attempt::Done(info))
+}
+
+/// Downloads an attachment into this session's temp dir in chunks and returns where it
+/// is once its size and, if the server sent one, its hash check out.
+/// Progress goes out as `download://progress` events tagged with `download_id`.
+#[tauri::command]
+pub async fn download_attachment<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    downloads: State<'_, Downloads>,
+    temp_files: State<'_, TempFiles>,
+    download_id: String,
+    ticket_id: u32,
+    filename: String,
+) -> Result<DownloadedFile, String> {
+    let account = accounts.active()?;
+    let paths = Paths::new(&temp_files, &account.id, ticket_id, &filename)?;
+
+    let cancel = Arc::new(Notify::new());
+    {
+        let mut active = downloads.active.lock().unwrap();
+        // Two writers on one `.part` file would corrupt it
+        if active.values().any(|download| download.part == paths.part) {
+            return Err(format!("{} is already being downloaded", filename));
+        }
+        active.insert(
+            download_id.clone(),
+            Active {
+                cancel: cancel.clone(),
+                part: paths.part.clone(),
+            },
+        );
+    }
+    let _guard = ActiveGuard {
+        downloads: &downloads,
+        id: download_id.clone(),
+    };
+
+    let mut attempt = fetch(
+        &app,
+        &account.api,
+        &download_id,
+        ticket_id,
+        &filename,
+        &paths,
+        &cancel,
+    )
+    .await?;
+    if let Attempt::Restart = attempt {
+        paths.discard_part();
+        attempt = fetch(
+            &app,
+            &account.api,
+            &download_id,
+            ticket_id,
+            &filename,
+            &paths,
+            &cancel,
+        )
+        .await?;
+    }
+    let Attempt::Done(info) = attempt else {
+        return Err("The server refused to send the file".to_string());
+    };
+
+    let size = fs::metadata(&paths.part).map_err(|e| e.to_string())?.len();
+    if let Some(total) = info.total.filter(|total| *total != size) {
+        // Too short is resumable, too long means the part was corrupt
+        if size > total {
+            paths.discard_part();
+        }
+        return Err(format!(
+            "Download incomplete: got {} of {} bytes",
+            size, total
+        ));
+    }
+
+    let part = paths.part.clone();
+    let sha256 = tauri::async_runtime::spawn_blocking(move || sha256_of(&part))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if info
+        .sha256
+        .as_ref()
+        .is_some_and(|expected| *expected != sha256)
+    {
+        paths.discard_part();
+        return Err(format!("Checksum mismatch for {}", filename));
+    }
+
+    let target = paths.free_target();
+    fs::rename(&paths.part, &target).map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(&paths.info);
+
+    Ok(DownloadedFile {
+        path: temp_files.register(&target)?,
+        size,
+        sha256: hex(&sha256),
+    })
+}
+
+#[tauri::command]
+pub fn cancel_download(downloads: State<'_, Downloads>, download_id: String) {
+    if let Some(download) = downloads.active.lock().unwrap().get(&download_id) {
+        // Stores a permit, so it is not lost between two chunks
+        download.cancel.notify_one();
+    }
+}
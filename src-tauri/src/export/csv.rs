@@ -0,0 +1,175 @@
+use encoding_rs::WINDOWS_1252;
+use serde::Deserialize;
+
+use super::{Cell, Table};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Encoding {
+    /// UTF-8 with a BOM, so Excel doesn't take it for the local code page.
+    #[default]
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// What Excel on a German Windows reads without asking; characters
+    /// outside it become `?`.
+    #[serde(rename = "windows-1252")]
+    Windows1252,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Dialect {
+    pub delimiter: char,
+    pub encoding: Encoding,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            encoding: Encoding::Utf8Bom,
+        }
+    }
+}
+
+/// RFC 4180: comma separated, CRLF line ends, fields with separators, quotes
+/// or line breaks quoted. Starts with a BOM so Excel reads it as UTF-8.
+pub fn to_string(table: &Table) -> String {
+    let mut out = String::from('\u{feff}');
+    push_table(&mut out, table, ',');
+    out
+}
+
+/// RFC 4180 with another delimiter and encoding where Excel's locale wants
+/// them. Locales that separate with `;` write decimals with a comma.
+pub fn to_bytes(table: &Table, dialect: Dialect) -> Result<Vec<u8>, String> {
+    // Quotes and line breaks have their own meaning, `.` is in every number
+    if matches!(dialect.delimiter, '"' | '\r' | '\n' | '.') {
+        return Err(format!(
+            "{:?} cannot be used as CSV delimiter",
+            dialect.delimiter
+        ));
+    }
+
+    let mut out = String::new();
+    push_table(&mut out, table, dialect.delimiter);
+    Ok(match dialect.encoding {
+        Encoding::Utf8Bom => [&[0xef, 0xbb, 0xbf][..], out.as_bytes()].concat(),
+        Encoding::Utf8 => out.into_bytes(),
+        Encoding::Windows1252 => windows_1252(&out),
+    })
+}
+
+/// encoding_rs writes unmappable characters as HTML entities, which would
+/// only confuse a spreadsheet.
+pub(crate) fn windows_1252(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut buffer = [0u8; 4];
+    for c in text.chars() {
+        let (encoded, _, unmappable) = WINDOWS_1252.encode(c.encode_utf8(&mut buffer));
+        if unmappable {
+            bytes.push(b'?');
+        } else {
+            bytes.extend_from_slice(&encoded);
+        }
+    }
+    bytes
+}
+
+fn push_table(out: &mut String, table: &Table, delimiter: char) {
+    push_row(
+        out,
+        table.headers.iter().map(|header| quote(header, delimiter)),
+        delimiter,
+    );
+    for row in &table.rows {
+        push_row(
+            out,
+            row.iter().map(|cell| match cell {
+                Cell::Text(text) => quote(text, delimiter),
+                Cell::Number(_) | Cell::Percent(_) if delimiter == ';' => {
+                    cell.text().replace('.', ",")
+                }
+                cell => cell.text(),
+            }),
+            delimiter,
+        );
+    }
+}
+
+fn push_row(out: &mut String, fields: impl Iterator<Item = String>, delimiter: char) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        out.push_str(&field);
+    }
+    out.push_str("\r\n");
+}
+
+/// Text a spreadsheet would run as a formula gets a `'` in front, so a
+/// ticket subject like `=HYPERLINK(...)` stays text.
+fn quote(text: &str, delimiter: char) -> String {
+    let text = if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    };
+    if text.contains([delimiter, '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table {
+            headers: vec![
+                "Name".to_string(),
+                "Rating".to_string(),
+                "Share".to_string(),
+            ],
+            rows: vec![vec![
+                Cell::from("Anna"),
+                Cell::Number(4.5),
+                Cell::Percent(0.125),
+            ]],
+        }
+    }
+
+    fn write(delimiter: char) -> Result<String, String> {
+        let dialect = Dialect {
+            delimiter,
+            encoding: Encoding::Utf8,
+        };
+        to_bytes(&table(), dialect).map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    #[test]
+    fn semicolons_come_with_decimal_commas() {
+        assert_eq!(
+            write(';').unwrap(),
+            "Name;Rating;Share\r\nAnna;4,5;12,50%\r\n"
+        );
+        assert_eq!(
+            write(',').unwrap(),
+            "Name,Rating,Share\r\nAnna,4.5,12.50%\r\n"
+        );
+        assert_eq!(
+            write('\t').unwrap(),
+            "Name\tRating\tShare\r\nAnna\t4.5\t12.50%\r\n"
+        );
+    }
+
+    #[test]
+    fn delimiters_that_break_fields_are_refused() {
+        for delimiter in ['"', '\r', '\n', '.'] {
+            assert!(write(delimiter).is_err(), "{:?} was accepted", delimiter);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! iCalendar (RFC 5545) with just the properties calendars need to show an
+//! event.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// Stable across exports, so a re-import updates instead of duplicating.
+    pub uid: String,
+    pub time: Time,
+    pub summary: String,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Time {
+    Span(DateTime<Utc>, DateTime<Utc>),
+    /// All day, on the date wherever the calendar is looked at.
+    Day(NaiveDate),
+}
+
+pub fn to_string(name: &str, events: &[Event]) -> String {
+    let stamp = format_time(&Utc::now());
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//Ticketbase//Ticketbase Desktop//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", escape(&event.uid)));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        match event.time {
+            Time::Span(start, end) => {
+                push_line(&mut out, &format!("DTSTART:{}", format_time(&start)));
+                push_line(&mut out, &format!("DTEND:{}", format_time(&end)));
+            }
+            Time::Day(day) => {
+                let next = day.succ_opt().unwrap_or(day);
+                push_line(
+                    &mut out,
+                    &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")),
+                );
+                push_line(
+                    &mut out,
+                    &format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+                );
+            }
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&event.summary)));
+        if let Some(location) = &event.location {
+            push_line(&mut out, &format!("LOCATION:{}", escape(location)));
+        }
+        if let Some(description) = &event.description {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(description)));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Lines are folded at 75 octets, never inside a character, and end in CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
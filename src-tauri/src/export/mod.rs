@@ -0,0 +1,88 @@
+//! Writers for the files the app exports. They only know tables and events;
+//! what goes into them is up to the timesheet and report modules.
+
+pub mod csv;
+pub mod ics;
+pub mod pdf;
+pub mod xlsx;
+
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+/// One value of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Date(NaiveDate),
+    /// A fraction, `0.125` for 12.5%.
+    Percent(f64),
+}
+
+impl Cell {
+    /// How the cell reads in text formats.
+    pub fn text(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => number.to_string(),
+            Cell::Date(date) => date.format("%Y-%m-%d").to_string(),
+            Cell::Percent(fraction) => format!("{:.2}%", fraction * 100.0),
+        }
+    }
+}
+
+/// An empty cell.
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::Text(String::new())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(number: f64) -> Self {
+        Cell::Number(number)
+    }
+}
+
+impl From<NaiveDate> for Cell {
+    fn from(date: NaiveDate) -> Self {
+        Cell::Date(date)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(number: u32) -> Self {
+        Cell::Number(number.into())
+    }
+}
+
+/// A header row and the rows under it.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// Writes the whole file at once, next to `path` first so that a failed
+/// export never leaves half a file where the user expects one.
+pub fn write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, contents).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial);
+        e.to_string()
+    })
+}
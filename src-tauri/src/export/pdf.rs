@@ -0,0 +1,276 @@
+//! A printable summary: a title, a few lines about what it covers and a
+//! table, on as many A4 pages as it takes. Only the standard Helvetica fonts
+//! are used, so nothing is embedded and text is limited to Windows-1252.
+
+use super::csv::windows_1252;
+use super::{Cell, Table};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+const FONT_SIZE: f32 = 9.0;
+const ROW_HEIGHT: f32 = 14.0;
+const PADDING: f32 = 4.0;
+
+/// Helvetica's advance widths for ` ` to `~`, in thousandths of the size.
+const WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+pub struct Document<'a> {
+    pub title: &'a str,
+    /// Shown under the title, one per line.
+    pub lines: &'a [String],
+    pub table: &'a Table,
+}
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn name(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+
+    /// Bold is a little wider; close enough for cutting text to fit.
+    fn width(self, text: &[u8], size: f32) -> f32 {
+        let units: u32 = text
+            .iter()
+            .map(|byte| match byte {
+                32..=126 => u32::from(WIDTHS[usize::from(byte - 32)]),
+                _ => 556,
+            })
+            .sum();
+        let scale = match self {
+            Font::Regular => 1.0,
+            Font::Bold => 1.1,
+        };
+        units as f32 * size / 1000.0 * scale
+    }
+}
+
+pub fn to_bytes(document: &Document) -> Vec<u8> {
+    let table = document.table;
+    let headers: Vec<Vec<u8>> = table
+        .headers
+        .iter()
+        .map(|header| windows_1252(header))
+        .collect();
+    let rows: Vec<Vec<(Vec<u8>, bool)>> = table
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    let text = cell.text().split_whitespace().collect::<Vec<_>>().join(" ");
+                    let right = matches!(cell, Cell::Number(_) | Cell::Percent(_));
+                    (windows_1252(&text), right)
+                })
+                .collect()
+        })
+        .collect();
+    let widths = column_widths(&headers, &rows);
+
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN - 16.0;
+    push_text(
+        &mut page,
+        Font::Bold,
+        16.0,
+        MARGIN,
+        y,
+        &windows_1252(document.title),
+    );
+    y -= 8.0;
+    for line in document.lines {
+        y -= 14.0;
+        push_text(
+            &mut page,
+            Font::Regular,
+            10.0,
+            MARGIN,
+            y,
+            &windows_1252(line),
+        );
+    }
+    y -= 24.0;
+    push_header(&mut page, &headers, &widths, y);
+    for row in &rows {
+        y -= ROW_HEIGHT;
+        if y < MARGIN + 20.0 {
+            pages.push(std::mem::take(&mut page));
+            y = PAGE_HEIGHT - MARGIN - FONT_SIZE;
+            push_header(&mut page, &headers, &widths, y);
+            y -= ROW_HEIGHT;
+        }
+        push_row(&mut page, Font::Regular, row, &widths, y);
+        push_rule(&mut page, y - 4.0, 0.3, 0.8);
+    }
+    pages.push(page);
+
+    let count = pages.len();
+    for (i, page) in pages.iter_mut().enumerate() {
+        let footer = windows_1252(&format!("Page {} of {count}", i + 1));
+        let x = PAGE_WIDTH - MARGIN - Font::Regular.width(&footer, 8.0);
+        push_text(page, Font::Regular, 8.0, x, MARGIN - 15.0, &footer);
+    }
+    assemble(&pages)
+}
+
+/// Each column as wide as its widest value, all of them squeezed to the
+/// page in proportion if that is too wide.
+fn column_widths(headers: &[Vec<u8>], rows: &[Vec<(Vec<u8>, bool)>]) -> Vec<f32> {
+    let mut widths: Vec<f32> = headers
+        .iter()
+        .map(|header| Font::Bold.width(header, FONT_SIZE))
+        .collect();
+    for row in rows {
+        for (width, (text, _)) in widths.iter_mut().zip(row) {
+            *width = width.max(Font::Regular.width(text, FONT_SIZE));
+        }
+    }
+    let widths: Vec<f32> = widths.iter().map(|width| width + 2.0 * PADDING).collect();
+    let total: f32 = widths.iter().sum();
+    let available = PAGE_WIDTH - 2.0 * MARGIN;
+    if total <= available {
+        return widths;
+    }
+    widths
+        .iter()
+        .map(|width| width * available / total)
+        .collect()
+}
+
+fn push_header(page: &mut String, headers: &[Vec<u8>], widths: &[f32], y: f32) {
+    let cells: Vec<(Vec<u8>, bool)> = headers
+        .iter()
+        .map(|header| (header.clone(), false))
+        .collect();
+    push_row(page, Font::Bold, &cells, widths, y);
+    push_rule(page, y - 4.0, 0.8, 0.0);
+}
+
+fn push_row(page: &mut String, font: Font, cells: &[(Vec<u8>, bool)], widths: &[f32], y: f32) {
+    let mut x = MARGIN;
+    for ((text, right), width) in cells.iter().zip(widths) {
+        let text = fit(font, text, width - 2.0 * PADDING);
+        let offset = if *right {
+            width - PADDING - font.width(&text, FONT_SIZE)
+        } else {
+            PADDING
+        };
+        push_text(page, font, FONT_SIZE, x + offset, y, &text);
+        x += width;
+    }
+}
+
+/// Cuts `text` down to `width`, marking the cut with an ellipsis.
+fn fit(font: Font, text: &[u8], width: f32) -> Vec<u8> {
+    if font.width(text, FONT_SIZE) <= width {
+        return text.to_vec();
+    }
+    // The ellipsis in Windows-1252
+    let ellipsis = 0x85;
+    let mut cut = text.to_vec();
+    while !cut.is_empty() {
+        cut.pop();
+        let mut candidate = cut.clone();
+        candidate.push(ellipsis);
+        if font.width(&candidate, FONT_SIZE) <= width {
+            return candidate;
+        }
+    }
+    cut
+}
+
+fn push_text(page: &mut String, font: Font, size: f32, x: f32, y: f32, text: &[u8]) {
+    page.push_str(&format!(
+        "BT /{} {size} Tf {x:.2} {y:.2} Td <{}> Tj ET\n",
+        font.name(),
+        hex(text)
+    ));
+}
+
+/// A horizontal line across the table in `gray` (0 is black).
+fn push_rule(page: &mut String, y: f32, width: f32, gray: f32) {
+    page.push_str(&format!(
+        "{gray} G {width} w {MARGIN} {y:.2} m {:.2} {y:.2} l S 0 G\n",
+        PAGE_WIDTH - MARGIN
+    ));
+}
+
+/// Hex strings keep the content streams ASCII whatever the text.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+/// The catalog, the page tree and both fonts, then a page and its content
+/// per page, and the cross-reference table pointing at all of them.
+fn assemble(pages: &[String]) -> Vec<u8> {
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |out: &mut Vec<u8>, body: String| {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n{body}\nendobj\n", offsets.len()).as_bytes());
+    };
+
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 5 + 2 * i))
+        .collect();
+    object(&mut out, "<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    object(
+        &mut out,
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    );
+    for base in ["Helvetica", "Helvetica-Bold"] {
+        object(
+            &mut out,
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{base} /Encoding /WinAnsiEncoding >>"
+            ),
+        );
+    }
+    for (i, content) in pages.iter().enumerate() {
+        object(
+            &mut out,
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                6 + 2 * i
+            ),
+        );
+        object(
+            &mut out,
+            format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ),
+        );
+    }
+
+    let xref = out.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f\r\n", offsets.len() + 1);
+    for offset in &offsets {
+        trailer.push_str(&format!("{offset:010} 00000 n\r\n"));
+    }
+    trailer.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        offsets.len() + 1
+    ));
+    out.extend_from_slice(trailer.as_bytes());
+    out
+}
@@ -0,0 +1,212 @@
+//! The smallest workbook Excel and LibreOffice open without complaint: one
+//! worksheet per table, strings inline, the header row bold, dates and
+//! percentages as numbers with a format. The parts are stored uncompressed,
+//! which both accept.
+
+use std::io::{Cursor, Write};
+
+use chrono::NaiveDate;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::{Cell, Table};
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>
+{sheets}</Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+<numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy-mm-dd"/></numFmts>
+<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>
+<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>
+<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>
+<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>
+<cellXfs count="4"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/><xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/><xf numFmtId="10" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/></cellXfs>
+</styleSheet>"#;
+
+/// A workbook with a sheet per `(name, table)`.
+pub fn to_bytes(sheets: &[(&str, &Table)]) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let overrides: String = (1..=sheets.len())
+        .map(|n| {
+            format!(
+                "<Override PartName=\"/xl/worksheets/sheet{n}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\n"
+            )
+        })
+        .collect();
+    let mut workbook = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    );
+    let mut workbook_rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for (i, (name, _)) in sheets.iter().enumerate() {
+        let n = i + 1;
+        workbook.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{n}" r:id="rId{n}"/>"#,
+            escape(&sheet_name(name))
+        ));
+        workbook_rels.push_str(&format!(
+            r#"<Relationship Id="rId{n}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{n}.xml"/>"#
+        ));
+    }
+    workbook.push_str("</sheets></workbook>");
+    workbook_rels.push_str(&format!(
+        r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+        sheets.len() + 1
+    ));
+
+    let mut files = vec![
+        (
+            "[Content_Types].xml".to_string(),
+            CONTENT_TYPES.replace("{sheets}", &overrides),
+        ),
+        ("_rels/.rels".to_string(), ROOT_RELS.to_string()),
+        ("xl/workbook.xml".to_string(), workbook),
+        ("xl/_rels/workbook.xml.rels".to_string(), workbook_rels),
+        ("xl/styles.xml".to_string(), STYLES.to_string()),
+    ];
+    for (i, (_, table)) in sheets.iter().enumerate() {
+        files.push((
+            format!("xl/worksheets/sheet{}.xml", i + 1),
+            worksheet(table),
+        ));
+    }
+
+    for (name, contents) in files {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(contents.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    zip.finish()
+        .map(|cursor| cursor.into_inner())
+        .map_err(|e| e.to_string())
+}
+
+fn worksheet(table: &Table) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    );
+    push_columns(&mut xml, table);
+    xml.push_str("<sheetData>");
+    let header = table
+        .headers
+        .iter()
+        .map(|header| Cell::from(header.as_str()));
+    push_row(&mut xml, 1, header, true);
+    for (i, row) in table.rows.iter().enumerate() {
+        push_row(&mut xml, i + 2, row.iter().cloned(), false);
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn push_row(xml: &mut String, row: usize, cells: impl Iterator<Item = Cell>, bold: bool) {
+    let style = if bold { r#" s="1""# } else { "" };
+    xml.push_str(&format!(r#"<row r="{row}">"#));
+    for (column, cell) in cells.enumerate() {
+        let reference = format!("{}{row}", column_name(column));
+        match cell {
+            Cell::Text(text) => xml.push_str(&format!(
+                r#"<c r="{reference}"{style} t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                escape(&text)
+            )),
+            Cell::Number(number) if number.is_finite() => {
+                xml.push_str(&format!(r#"<c r="{reference}"{style}><v>{number}</v></c>"#))
+            }
+            Cell::Date(date) => xml.push_str(&format!(
+                r#"<c r="{reference}" s="2"><v>{}</v></c>"#,
+                serial(date)
+            )),
+            Cell::Percent(fraction) if fraction.is_finite() => {
+                xml.push_str(&format!(r#"<c r="{reference}" s="3"><v>{fraction}</v></c>"#))
+            }
+            Cell::Number(_) | Cell::Percent(_) => {}
+        }
+    }
+    xml.push_str("</row>");
+}
+
+/// Wide enough for the longest value, so dates don't show as `#####`.
+fn push_columns(xml: &mut String, table: &Table) {
+    if table.headers.is_empty() {
+        return;
+    }
+    xml.push_str("<cols>");
+    for (i, header) in table.headers.iter().enumerate() {
+        let longest = table
+            .rows
+            .iter()
+            .filter_map(|row| row.get(i))
+            .flat_map(|cell| cell.text().lines().map(|line| line.chars().count()).max())
+            .chain([header.chars().count()])
+            .max()
+            .unwrap_or(0);
+        let width = (longest + 2).clamp(8, 60);
+        xml.push_str(&format!(
+            r#"<col min="{n}" max="{n}" width="{width}" customWidth="1"/>"#,
+            n = i + 1
+        ));
+    }
+    xml.push_str("</cols>");
+}
+
+/// Days since Excel's epoch, 1899-12-30 once its 1900 leap day is allowed for.
+fn serial(date: NaiveDate) -> i64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).unwrap_or_default();
+    (date - epoch).num_days()
+}
+
+/// A, B, ... Z, AA, AB, ...
+fn column_name(mut column: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Sheet names are at most 31 characters and can't contain `[]:*?/\`.
+fn sheet_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect()
+}
+
+/// Escapes markup and drops the control characters XML can't hold.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,71 @@
+//! One running app per user. A second launch hands its arguments to the
+//! first and quits, rather than polling the API and timing tickets twice.
+
+use tauri::{AppHandle, Runtime, Url};
+
+use crate::{deeplink, notifications, tray};
+
+/// What an argument of a launch asks to open.
+#[derive(Debug, PartialEq)]
+enum Target<'a> {
+    Link(&'a str),
+    Ticket(u32),
+}
+
+impl<'a> Target<'a> {
+    /// `ticketbase://` links and ticket numbers like `123` or `#123`; other
+    /// arguments are none of ours.
+    fn parse(arg: &'a str) -> Option<Self> {
+        let arg = arg.trim();
+        if Url::parse(arg).is_ok_and(|url| url.scheme() == deeplink::SCHEME) {
+            return Some(Target::Link(arg));
+        }
+        match arg.strip_prefix('#').unwrap_or(arg).parse::<u32>() {
+            Ok(ticket_id) if ticket_id > 0 => Some(Target::Ticket(ticket_id)),
+            _ => None,
+        }
+    }
+
+    fn open<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        match self {
+            Target::Link(link) => deeplink::open(app, link),
+            Target::Ticket(ticket_id) => crate::show_ticket_window(app, *ticket_id, None),
+        }
+    }
+}
+
+/// Opens the tickets the app was started with. Links are left to the deep
+/// link plugin, which reads them at startup itself.
+pub fn open_args<R: Runtime>(app: &AppHandle<R>, args: impl IntoIterator<Item = String>) {
+    for arg in args {
+        if let Some(target @ Target::Ticket(_)) = Target::parse(&arg) {
+            open(app, &target);
+        }
+    }
+}
+
+/// The single instance plugin's callback, with the arguments of a second
+/// launch. Without anything to open, that launch brings the app to the front.
+pub fn forward<R: Runtime>(app: &AppHandle<R>, argv: Vec<String>, _cwd: String) {
+    // The first argument is the executable
+    let targets: Vec<Target> = argv
+        .iter()
+        .skip(1)
+        .filter_map(|arg| Target::parse(arg))
+        .collect();
+    if targets.is_empty() {
+        tray::show_main_window(app);
+    }
+    for target in &targets {
+        open(app, target);
+    }
+}
+
+/// A target that can't be opened is reported with a notification, as no
+/// window may be in view.
+fn open<R: Runtime>(app: &AppHandle<R>, target: &Target) {
+    if let Err(e) = target.open(app) {
+        log::error!("Failed to open {:?}: {}", target, e);
+        notifications::notify(app, "Not opened", &e);
+    }
+}
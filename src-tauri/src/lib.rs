@@ -1,4 +1,28 @@
-use tauri::Manager;
+use tauri::{Manager, Runtime};
+
+mod accounts;
+mod api;
+mod autopause;
+mod cache;
+mod calendar;
+mod credentials;
+mod deeplink;
+mod downloads;
+mod export;
+mod instance;
+mod network;
+mod notifications;
+mod outbox;
+mod poller;
+mod profiles;
+mod reports;
+mod search;
+mod shortcuts;
+mod tempfiles;
+mod timer;
+mod timesheet;
+mod tray;
+mod uploads;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -7,19 +31,39 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn open_ticket_window(app: tauri::AppHandle, ticket_id: u32) -> Result<(), String> {
+async fn open_ticket_window<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    ticket_id: u32,
+    account_id: Option<String>,
+) -> Result<(), String> {
+    show_ticket_window(&app, ticket_id, account_id.as_deref())
+}
+
+/// Focuses the `ticket-{id}` window, creating it first if needed. With an
+/// `account_id`, switches to that account first.
+pub(crate) fn show_ticket_window<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    ticket_id: u32,
+    account_id: Option<&str>,
+) -> Result<(), String> {
+    if let Some(account_id) = account_id {
+        app.state::<accounts::Accounts>()
+            .activate(app, account_id)?;
+    }
+
     let window_label = format!("ticket-{}", ticket_id);
 
     // Check if window already exists
     if let Some(window) = app.get_webview_window(&window_label) {
         // Window exists, focus it
+        let _ = window.unminimize();
         window.set_focus().map_err(|e| e.to_string())?;
         return Ok(());
     }
 
     // Create new window using WebviewWindowBuilder
     let _webview_window = tauri::WebviewWindowBuilder::new(
-        &app,
+        app,
         window_label,
         tauri::WebviewUrl::App(format!("/?ticketWindow=true#/ticket/{}", ticket_id).into()),
     )
@@ -33,16 +77,213 @@ async fn open_ticket_window(app: tauri::AppHandle, ticket_id: u32) -> Result<(),
     Ok(())
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
+/// Focuses the new ticket form's own window, creating it first if needed.
+pub(crate) fn show_new_ticket_window<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    show_tool_window(
+        app,
+        "new-ticket",
+        "/?newTicketWindow=true",
+        "New Ticket",
+        (720.0, 760.0),
+    )
+}
+
+/// Focuses a small window of its own for one task, creating it first if needed.
+pub(crate) fn show_tool_window<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    label: &str,
+    url: &str,
+    title: &str,
+    (width, height): (f64, f64),
+) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::App(url.into()))
+        .title(title)
+        .inner_size(width, height)
+        .min_inner_size(400.0, 300.0)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The app without its context, so tests can build it on the mock runtime.
+pub fn builder<R: Runtime>() -> tauri::Builder<R> {
+    configure(tauri::Builder::new())
+}
+
+/// Plugins, state and commands on top of what `run` registers first.
+fn configure<R: Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
+    builder
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_http::init())
-        .invoke_handler(tauri::generate_handler![greet, open_ticket_window])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .manage(downloads::Downloads::default())
+        .manage(autopause::AutoPause::default())
+        .manage(tray::Tray::default())
+        .manage(deeplink::DeepLinks::default())
+        .setup(|app| {
+            app.manage(profiles::ProfileStore::open(app.handle())?);
+            app.manage(credentials::CredentialStore::new(app.handle())?);
+            app.manage(network::Network::open(app.handle())?);
+            app.manage(notifications::Notifier::new(app.handle())?);
+            app.manage(accounts::Accounts::new(app.handle())?);
+            app.state::<accounts::Accounts>().restore(app.handle());
+            app.manage(tempfiles::TempFiles::open(app.handle())?);
+            app.manage(calendar::CalendarFeed::open(app.handle())?);
+            app.state::<calendar::CalendarFeed>().resume(app.handle());
+            timer::spawn(app.handle().clone());
+            autopause::spawn(app.handle().clone());
+            app.manage(shortcuts::Shortcuts::open(app.handle())?);
+            app.state::<shortcuts::Shortcuts>()
+                .register_all(app.handle());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            open_ticket_window,
+            api::commands::login,
+            api::commands::get_throttle_state,
+            api::commands::get_tickets,
+            api::commands::get_tickets_unfiltered,
+            api::commands::get_tickets_today,
+            api::commands::get_ticket_data,
+            api::commands::get_ticket_by_id,
+            api::commands::create_ticket_json,
+            api::commands::ticket_terminieren,
+            api::commands::ticket_terminieren_api,
+            api::commands::save_ticket_history,
+            api::commands::correct_watch,
+            api::commands::get_user_status,
+            api::commands::change_user_status,
+            api::commands::get_templates,
+            api::commands::get_customers,
+            api::commands::get_customer_locations,
+            api::commands::get_location_users,
+            api::commands::get_check_list,
+            api::commands::new_todo,
+            api::commands::check_todo,
+            api::commands::play,
+            api::commands::pause,
+            api::commands::resume,
+            api::commands::stop,
+            api::commands::get_player_status,
+            api::commands::edit_profile,
+            api::commands::change_password,
+            api::commands::get_users_mail_settings,
+            api::commands::user_mail_settings,
+            api::commands::get_ticket_messages,
+            api::commands::send_message,
+            api::commands::get_wiki_data,
+            api::commands::get_closed_confirmed_tickets,
+            api::commands::rate_ticket,
+            api::commands::get_report4,
+            api::commands::get_report5,
+            api::commands::get_top_users,
+            api::commands::get_correct_watch_histories_for_period,
+            profiles::get_server_profiles,
+            profiles::save_server_profile,
+            profiles::delete_server_profile,
+            profiles::select_server_profile,
+            credentials::store_session,
+            credentials::load_session,
+            credentials::clear_session,
+            accounts::get_accounts,
+            accounts::switch_account,
+            accounts::remove_account,
+            poller::set_poll_interval,
+            poller::pause_polling,
+            poller::resume_polling,
+            poller::refresh_tickets_now,
+            poller::get_tickets_snapshot,
+            network::commands::get_network_settings,
+            network::commands::set_network_settings,
+            network::commands::check_for_update,
+            notifications::get_notification_settings,
+            notifications::set_notification_settings,
+            notifications::show_notification,
+            cache::commands::get_cached_ticket,
+            cache::commands::get_cached_ticket_data,
+            cache::commands::get_cached_check_list,
+            cache::commands::get_cached_ticket_messages,
+            cache::commands::get_cached_customers,
+            cache::commands::get_cached_customer_locations,
+            cache::commands::get_cached_location_users,
+            cache::commands::cache_get,
+            cache::commands::cache_set,
+            cache::commands::cache_delete,
+            cache::commands::cache_invalidate,
+            search::commands::search,
+            downloads::download_attachment,
+            downloads::cancel_download,
+            tempfiles::open_attachment,
+            uploads::inspect_attachments,
+            uploads::create_ticket,
+            outbox::submit_mutation,
+            outbox::get_outbox,
+            outbox::retry_outbox_entry,
+            outbox::discard_outbox_entry,
+            timer::get_timer,
+            timer::get_timers,
+            autopause::get_away_gap,
+            autopause::resolve_away_gap,
+            reports::export_report,
+            calendar::commands::get_calendar_feed,
+            calendar::commands::set_calendar_feed,
+            calendar::commands::reset_calendar_feed_url,
+            calendar::commands::export_appointments,
+            timesheet::export_timesheet,
+            shortcuts::get_shortcuts,
+            shortcuts::set_shortcuts,
+            deeplink::open_deep_link,
+            deeplink::take_new_ticket_draft,
+            tray::take_stop_request,
+        ])
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // Single instance first, so a second launch quits before any other
+    // plugin starts. Logging is only set up here, as there is one logger
+    // per process and tests build many apps.
+    let builder = tauri::Builder::<tauri::Wry>::new()
+        .plugin(tauri_plugin_single_instance::init(instance::forward))
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
+        .plugin(tauri_plugin_deep_link::init());
+    configure(builder)
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcuts::handle)
+                .build(),
+        )
+        .on_window_event(tray::hide_on_close)
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::Ready => {
+                tray::create(app);
+                deeplink::listen(app);
+                instance::open_args(app, std::env::args().skip(1));
+            }
+            tauri::RunEvent::Exit => {
+                // Files a viewer still holds stay until the next start
+                if let Some(temp_files) = app.try_state::<tempfiles::TempFiles>() {
+                    temp_files.cleanup(true);
+                }
+            }
+            _ => {}
+        });
 }
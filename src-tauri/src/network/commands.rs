@@ -0,0 +1,69 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Runtime, State, Webview};
+use tauri_plugin_updater::UpdaterExt;
+
+use super::{Network, NetworkSettings};
+use crate::accounts::Accounts;
+
+#[tauri::command]
+pub fn get_network_settings(network: State<'_, Network>) -> NetworkSettings {
+    network.settings()
+}
+
+/// Fails without changing anything if the proxy, PAC file or CA bundle is
+/// unusable. Signed in accounts switch over right away.
+#[tauri::command]
+pub async fn set_network_settings<R: Runtime>(
+    app: AppHandle<R>,
+    network: State<'_, Network>,
+    accounts: State<'_, Accounts>,
+    settings: NetworkSettings,
+) -> Result<(), String> {
+    network.save(&app, settings).await?;
+    accounts.reconnect(&app)
+}
+
+/// What the updater plugin's own `check` answers, so the frontend can wrap
+/// it in its `Update` class.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMetadata {
+    rid: tauri::ResourceId,
+    current_version: String,
+    version: String,
+    date: Option<String>,
+    body: Option<String>,
+    raw_json: Value,
+}
+
+/// The updater plugin's `check`, with a client that follows the network
+/// settings. Downloading the update goes through the same client.
+#[tauri::command]
+pub async fn check_for_update<R: Runtime>(
+    webview: Webview<R>,
+    network: State<'_, Network>,
+) -> Result<Option<UpdateMetadata>, String> {
+    let update = webview
+        .updater_builder()
+        .configure_client(network.configurator())
+        .build()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(update.map(|update| UpdateMetadata {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        // As the server wrote it; RFC 3339 like the plugin's
+        date: update
+            .raw_json
+            .get("pub_date")
+            .and_then(Value::as_str)
+            .map(String::from),
+        body: update.body.clone(),
+        raw_json: update.raw_json.clone(),
+        rid: webview.resources_table().add(update),
+    }))
+}
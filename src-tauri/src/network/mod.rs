@@ -0,0 +1,250 @@
+pub mod commands;
+mod pac;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_http::reqwest::{Certificate, ClientBuilder, NoProxy, Proxy, Url};
+
+use crate::credentials::CredentialStore;
+use pac::PacScript;
+
+const SETTINGS_FILE: &str = "network-settings.json";
+/// Keyring entry of the proxy password.
+const PROXY_SECRET: &str = "proxy";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    /// `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` from the environment.
+    #[default]
+    System,
+    /// Always connect directly, whatever the environment says.
+    None,
+    Manual,
+    Pac,
+}
+
+/// How every request of the app reaches the network: API calls, attachment
+/// up- and downloads and the updater.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    pub proxy_mode: ProxyMode,
+    /// `http://`, `https://`, `socks4://` or `socks5://` with host and port.
+    pub proxy_url: String,
+    /// Hosts that bypass the manual proxy, comma separated like `NO_PROXY`.
+    /// Empty falls back to `NO_PROXY` from the environment.
+    pub no_proxy: String,
+    /// Location of the PAC file: an HTTP(S) or `file://` URL, or a path.
+    pub pac_url: String,
+    pub proxy_username: String,
+    /// Only ever sent by the frontend; it is kept in the keyring, and `None`
+    /// leaves the stored one alone.
+    #[serde(skip_serializing)]
+    pub proxy_password: Option<String>,
+    /// PEM bundle with extra roots to trust, for proxies that inspect TLS.
+    pub ca_bundle: Option<PathBuf>,
+}
+
+/// The PAC script the proxy asks. Empty, so connections go direct, until it
+/// is loaded.
+type PacSlot = Arc<RwLock<Option<PacScript>>>;
+
+/// What a `ClientBuilder` needs, prepared once per settings change so
+/// building a client cannot fail.
+#[derive(Default)]
+struct Prepared {
+    /// `None` keeps reqwest's own environment handling.
+    proxy: Option<Option<Proxy>>,
+    certificates: Vec<Certificate>,
+}
+
+pub struct Network {
+    path: PathBuf,
+    settings: RwLock<NetworkSettings>,
+    prepared: RwLock<Arc<Prepared>>,
+}
+
+impl Network {
+    /// Reads the saved settings. A broken PAC or CA file is reported and the
+    /// app starts without it, so it can still be fixed in the settings. The
+    /// PAC file is fetched in the background, so a slow host does not hold
+    /// up the start.
+    pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+        let path = config_dir.join(SETTINGS_FILE);
+
+        let settings: NetworkSettings = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        let password = app
+            .state::<CredentialStore>()
+            .load_secret(PROXY_SECRET)
+            .unwrap_or_else(|e| {
+                log::error!("Failed to load proxy password: {}", e);
+                None
+            });
+        let pac = PacSlot::default();
+        let prepared = prepare(&settings, password.as_deref(), &pac).unwrap_or_else(|e| {
+            log::error!("Failed to apply network settings: {}", e);
+            Prepared::default()
+        });
+        if settings.proxy_mode == ProxyMode::Pac && !settings.pac_url.is_empty() {
+            let location = settings.pac_url.clone();
+            tauri::async_runtime::spawn(async move {
+                match PacScript::load(&location).await {
+                    Ok(script) => *pac.write().unwrap() = Some(script),
+                    Err(e) => log::warn!("Failed to load PAC file, connecting directly: {}", e),
+                }
+            });
+        }
+
+        Ok(Self {
+            path,
+            settings: RwLock::new(settings),
+            prepared: RwLock::new(Arc::new(prepared)),
+        })
+    }
+
+    pub fn settings(&self) -> NetworkSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Checks and stores new settings. Clients built from now on use them.
+    pub async fn save<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        mut settings: NetworkSettings,
+    ) -> Result<(), String> {
+        settings.proxy_url = settings.proxy_url.trim().to_string();
+        settings.pac_url = settings.pac_url.trim().to_string();
+        settings.proxy_username = settings.proxy_username.trim().to_string();
+        settings.ca_bundle = settings
+            .ca_bundle
+            .filter(|path| !path.as_os_str().is_empty());
+
+        let credentials = app.state::<CredentialStore>();
+        let password = match settings.proxy_password.take() {
+            Some(password) => Some(password).filter(|password| !password.is_empty()),
+            None => credentials.load_secret(PROXY_SECRET)?,
+        };
+        let pac = PacSlot::default();
+        if settings.proxy_mode == ProxyMode::Pac && !settings.pac_url.is_empty() {
+            *pac.write().unwrap() = Some(PacScript::load(&settings.pac_url).await?);
+        }
+        let prepared = prepare(&settings, password.as_deref(), &pac)?;
+
+        match &password {
+            Some(password) => credentials.store_secret(PROXY_SECRET, password)?,
+            None => credentials.clear_secret(PROXY_SECRET)?,
+        }
+        let json = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        fs::write(&self.path, json).map_err(|e| e.to_string())?;
+
+        *self.settings.write().unwrap() = settings;
+        *self.prepared.write().unwrap() = Arc::new(prepared);
+        Ok(())
+    }
+
+    /// A function that applies the current settings to a `ClientBuilder`, for
+    /// clients built later, like the updater's.
+    pub fn configurator(&self) -> impl Fn(ClientBuilder) -> ClientBuilder + Send + Sync + 'static {
+        let prepared = self.prepared.read().unwrap().clone();
+        move |builder| apply(&prepared, builder)
+    }
+
+    pub fn configure(&self, builder: ClientBuilder) -> ClientBuilder {
+        apply(&self.prepared.read().unwrap(), builder)
+    }
+}
+
+fn apply(prepared: &Prepared, builder: ClientBuilder) -> ClientBuilder {
+    let builder = match &prepared.proxy {
+        None => builder,
+        Some(None) => builder.no_proxy(),
+        Some(Some(proxy)) => builder.proxy(proxy.clone()),
+    };
+    prepared
+        .certificates
+        .iter()
+        .fold(builder, |builder, certificate| {
+            builder.add_root_certificate(certificate.clone())
+        })
+}
+
+fn prepare(
+    settings: &NetworkSettings,
+    password: Option<&str>,
+    pac: &PacSlot,
+) -> Result<Prepared, String> {
+    let proxy = match settings.proxy_mode {
+        ProxyMode::System => None,
+        ProxyMode::None => Some(None),
+        ProxyMode::Manual => {
+            let url = proxy_url(&settings.proxy_url)?;
+            let no_proxy = NoProxy::from_string(&settings.no_proxy).or_else(NoProxy::from_env);
+            let proxy = Proxy::all(url)
+                .map_err(|e| e.to_string())?
+                .no_proxy(no_proxy);
+            Some(Some(authenticate(proxy, settings, password)))
+        }
+        ProxyMode::Pac => {
+            if settings.pac_url.is_empty() {
+                return Err("Enter where the PAC file is".to_string());
+            }
+            let pac = pac.clone();
+            let proxy = Proxy::custom(move |url| {
+                pac.read()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|pac| pac.proxy_for(url))
+            });
+            Some(Some(authenticate(proxy, settings, password)))
+        }
+    };
+
+    Ok(Prepared {
+        proxy,
+        certificates: match &settings.ca_bundle {
+            Some(path) => read_certificates(path)?,
+            None => Vec::new(),
+        },
+    })
+}
+
+fn proxy_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("{}: {}", url, e))?;
+    if !matches!(
+        parsed.scheme(),
+        "http" | "https" | "socks4" | "socks4a" | "socks5" | "socks5h"
+    ) || parsed.host_str().is_none()
+    {
+        return Err(format!("Not a proxy URL: {}", url));
+    }
+    Ok(parsed)
+}
+
+fn authenticate(proxy: Proxy, settings: &NetworkSettings, password: Option<&str>) -> Proxy {
+    if settings.proxy_username.is_empty() {
+        return proxy;
+    }
+    proxy.basic_auth(&settings.proxy_username, password.unwrap_or_default())
+}
+
+/// The certificates of a PEM bundle, which must hold at least one.
+pub fn read_certificates(path: &Path) -> Result<Vec<Certificate>, String> {
+    let pem = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let certificates =
+        Certificate::from_pem_bundle(&pem).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if certificates.is_empty() {
+        return Err(format!("{}: no certificates found", path.display()));
+    }
+    Ok(certificates)
+}
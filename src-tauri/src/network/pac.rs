@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use boa_engine::{Context, Source};
+use tauri_plugin_http::reqwest::{self, Url};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long an answer is reused. Not for good: `timeRange` and `dateRange`
+/// answer differently as time goes by.
+const ANSWER_TTL: Duration = Duration::from_secs(60);
+/// Longer and the script is taken to hang; the request goes direct.
+const EVAL_TIMEOUT: Duration = Duration::from_secs(5);
+/// Per loop. PAC files only walk short lists of hosts and networks.
+const LOOP_LIMIT: u64 = 100_000;
+const RECURSION_LIMIT: usize = 64;
+
+/// The helper functions PAC files may call, as browsers define them.
+/// `dnsResolve` and `myIpAddress` answer from values resolved in Rust.
+const PRELUDE: &str = r#"
+var __days = ['SUN', 'MON', 'TUE', 'WED', 'THU', 'FRI', 'SAT'];
+var __months = ['JAN', 'FEB', 'MAR', 'APR', 'MAY', 'JUN', 'JUL', 'AUG', 'SEP', 'OCT', 'NOV', 'DEC'];
+
+function isPlainHostName(host) { return host.indexOf('.') < 0; }
+function dnsDomainIs(host, domain) {
+  host = host.toLowerCase(); domain = domain.toLowerCase();
+  return host.length >= domain.length && host.substring(host.length - domain.length) === domain;
+}
+function localHostOrDomainIs(host, hostdom) {
+  return host === hostdom || hostdom.lastIndexOf(host + '.', 0) === 0;
+}
+function dnsResolve(host) { return __dns.hasOwnProperty(host) ? __dns[host] : null; }
+function isResolvable(host) { return dnsResolve(host) !== null; }
+function myIpAddress() { return __myIp; }
+function dnsDomainLevels(host) { return host.split('.').length - 1; }
+function convert_addr(ip) {
+  var b = ip.split('.');
+  return ((b[0] << 24) | (b[1] << 16) | (b[2] << 8) | (b[3] | 0)) >>> 0;
+}
+function isInNet(host, pattern, mask) {
+  var ip = /^\d+\.\d+\.\d+\.\d+$/.test(host) ? host : dnsResolve(host);
+  if (!ip) return false;
+  var m = convert_addr(mask);
+  return ((convert_addr(ip) & m) >>> 0) === ((convert_addr(pattern) & m) >>> 0);
+}
+function shExpMatch(str, exp) {
+  var re = exp.replace(/[.+^${}()|[\]\\]/g, '\\$&').replace(/\*/g, '.*').replace(/\?/g, '.');
+  return new RegExp('^' + re + '$').test(str);
+}
+function __args(args) {
+  var list = Array.prototype.slice.call(args);
+  var gmt = list[list.length - 1] === 'GMT';
+  if (gmt) list.pop();
+  return { list: list, now: new Date(), gmt: gmt };
+}
+function __inRange(value, from, to) {
+  return from <= to ? value >= from && value <= to : value >= from || value <= to;
+}
+function weekdayRange() {
+  var a = __args(arguments);
+  var day = a.gmt ? a.now.getUTCDay() : a.now.getDay();
+  var from = __days.indexOf(a.list[0]);
+  var to = a.list.length > 1 ? __days.indexOf(a.list[1]) : from;
+  return __inRange(day, from, to);
+}
+function dateRange() {
+  var a = __args(arguments);
+  var today = {
+    d: a.gmt ? a.now.getUTCDate() : a.now.getDate(),
+    m: a.gmt ? a.now.getUTCMonth() : a.now.getMonth(),
+    y: a.gmt ? a.now.getUTCFullYear() : a.now.getFullYear()
+  };
+  function parse(list) {
+    var p = {};
+    for (var i = 0; i < list.length; i++) {
+      if (typeof list[i] === 'string') p.m = __months.indexOf(list[i]);
+      else if (list[i] > 31) p.y = list[i];
+      else p.d = list[i];
+    }
+    return p;
+  }
+  // Fields a bound leaves out are taken from today
+  function key(p) {
+    return (p.y === undefined ? today.y : p.y) * 10000 +
+      (p.m === undefined ? today.m : p.m) * 100 + (p.d === undefined ? today.d : p.d);
+  }
+  if (a.list.length === 1) return key(parse(a.list)) === key({});
+  var half = a.list.length / 2;
+  return __inRange(key({}), key(parse(a.list.slice(0, half))), key(parse(a.list.slice(half))));
+}
+function timeRange() {
+  var a = __args(arguments), n = a.list;
+  var now = a.gmt
+    ? a.now.getUTCHours() * 3600 + a.now.getUTCMinutes() * 60 + a.now.getUTCSeconds()
+    : a.now.getHours() * 3600 + a.now.getMinutes() * 60 + a.now.getSeconds();
+  if (n.length === 1) return Math.floor(now / 3600) === n[0];
+  if (n.length === 2) return __inRange(now, n[0] * 3600, n[1] * 3600 + 3599);
+  if (n.length === 4) return __inRange(now, n[0] * 3600 + n[1] * 60, n[2] * 3600 + n[3] * 60 + 59);
+  return __inRange(now, n[0] * 3600 + n[1] * 60 + n[2], n[3] * 3600 + n[4] * 60 + n[5]);
+}
+"#;
+
+/// A proxy auto-config script, evaluated with boa for each origin we talk
+/// to. Answers are kept for `ANSWER_TTL`.
+pub struct PacScript {
+    script: Arc<str>,
+    uses_dns: bool,
+    uses_my_ip: bool,
+    answers: Mutex<HashMap<String, (Instant, Option<Url>)>>,
+}
+
+impl PacScript {
+    /// Loads the script from a `file://` URL, a path or an HTTP(S) URL. The
+    /// latter is fetched without a proxy, like browsers do.
+    pub async fn load(location: &str) -> Result<Self, String> {
+        let script = match Url::parse(location) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => reqwest::Client::builder()
+                .no_proxy()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .map_err(|e| e.to_string())?
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("{}: {}", location, e))?
+                .text()
+                .await
+                .map_err(|e| format!("{}: {}", location, e))?,
+            Ok(url) if url.scheme() == "file" => {
+                let path = url
+                    .to_file_path()
+                    .map_err(|_| format!("Not a file URL: {}", location))?;
+                fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            _ => fs::read_to_string(location).map_err(|e| format!("{}: {}", location, e))?,
+        };
+
+        let pac = Self {
+            uses_dns: ["dnsResolve", "isInNet", "isResolvable"]
+                .iter()
+                .any(|name| script.contains(name)),
+            uses_my_ip: script.contains("myIpAddress"),
+            script: script.into(),
+            answers: Mutex::new(HashMap::new()),
+        };
+        // Rejects scripts that do not even run before they are saved
+        blocking(|| pac.evaluate(&Url::parse("https://example.com/").unwrap()))?;
+        Ok(pac)
+    }
+
+    /// Where requests to `url` go, `None` for a direct connection.
+    pub fn proxy_for(&self, url: &Url) -> Option<Url> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((answered, answer)) = self.answers.lock().unwrap().get(&origin) {
+            if answered.elapsed() < ANSWER_TTL {
+                return answer.clone();
+            }
+        }
+
+        // reqwest asks from within the runtime, and this resolves names
+        let answer = match blocking(|| self.evaluate(url)) {
+            Ok(result) => parse_result(&result),
+            Err(e) => {
+                log::warn!("PAC script failed for {}: {}", origin, e);
+                None
+            }
+        };
+        self.answers
+            .lock()
+            .unwrap()
+            .insert(origin, (Instant::now(), answer.clone()));
+        answer
+    }
+
+    /// Runs the script on a thread of its own, so one that hangs costs that
+    /// thread but not the request.
+    fn evaluate(&self, url: &Url) -> Result<String, String> {
+        let (script, url) = (self.script.clone(), url.clone());
+        let (uses_dns, uses_my_ip) = (self.uses_dns, self.uses_my_ip);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(run(&script, uses_dns, uses_my_ip, &url));
+        });
+        receiver
+            .recv_timeout(EVAL_TIMEOUT)
+            .map_err(|_| "The PAC script did not finish in time".to_string())?
+    }
+}
+
+/// What `FindProxyForURL` answers for `url`, under boa's limits.
+fn run(script: &str, uses_dns: bool, uses_my_ip: bool, url: &Url) -> Result<String, String> {
+    let host = url.host_str().unwrap_or_default();
+    let mut dns = HashMap::new();
+    if uses_dns {
+        if let Some(ip) = resolve(host) {
+            dns.insert(host.to_string(), ip.to_string());
+        }
+    }
+    let my_ip = if uses_my_ip { my_ip_address() } else { None };
+
+    // Paths and queries are none of the script's business
+    let source = format!(
+        "{}\nvar __dns = {};\nvar __myIp = {};\n{}\n;FindProxyForURL({}, {});",
+        PRELUDE,
+        serde_json::json!(dns),
+        serde_json::json!(my_ip.map_or("127.0.0.1".to_string(), |ip| ip.to_string())),
+        script,
+        serde_json::json!(format!("{}/", url.origin().ascii_serialization())),
+        serde_json::json!(host),
+    );
+
+    let mut context = Context::default();
+    let limits = context.runtime_limits_mut();
+    limits.set_loop_iteration_limit(LOOP_LIMIT);
+    limits.set_recursion_limit(RECURSION_LIMIT);
+    let result = context
+        .eval(Source::from_bytes(&source))
+        .map_err(|e| e.to_string())?;
+    result
+        .to_string(&mut context)
+        .map(|result| result.to_std_string_escaped())
+        .map_err(|e| e.to_string())
+}
+
+/// Runs `f`, which blocks, without stalling the other tasks on this worker
+/// thread of the runtime.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+fn resolve(host: &str) -> Option<IpAddr> {
+    (host, 0)
+        .to_socket_addrs()
+        .ok()?
+        .map(|address| address.ip())
+        .find(IpAddr::is_ipv4)
+}
+
+/// The address of the interface the default route goes through. Connecting
+/// a UDP socket sends nothing.
+fn my_ip_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+/// The first usable entry of e.g. `"PROXY a:8080; SOCKS5 b:1080; DIRECT"`.
+fn parse_result(result: &str) -> Option<Url> {
+    for entry in result.split(';') {
+        let mut parts = entry.split_whitespace();
+        let Some(kind) = parts.next() else {
+            continue;
+        };
+        let scheme = match kind.to_ascii_uppercase().as_str() {
+            "DIRECT" => return None,
+            "PROXY" | "HTTP" => "http",
+            "HTTPS" => "https",
+            "SOCKS" | "SOCKS4" => "socks4",
+            "SOCKS5" => "socks5",
+            _ => continue,
+        };
+        if let Some(url) = parts
+            .next()
+            .and_then(|address| Url::parse(&format!("{}://{}", scheme, address)).ok())
+        {
+            return Some(url);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(source: &str) -> PacScript {
+        PacScript {
+            script: source.into(),
+            uses_dns: false,
+            uses_my_ip: false,
+            answers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn scripts_that_never_finish_go_direct() {
+        let url = Url::parse("https://example.com/").unwrap();
+        for source in [
+            "function FindProxyForURL(url, host) { while (true) {} }",
+            "function FindProxyForURL(url, host) { return FindProxyForURL(url, host); }",
+        ] {
+            let pac = script(source);
+            assert!(pac.evaluate(&url).is_err(), "{}", source);
+            assert_eq!(pac.proxy_for(&url), None);
+        }
+
+        let pac = script("function FindProxyForURL(url, host) { return 'PROXY proxy:3128'; }");
+        assert_eq!(
+            pac.proxy_for(&url),
+            Some(Url::parse("http://proxy:3128").unwrap())
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Notifications through the notification center. Waiting for the click
+//! blocks until the user clicks or dismisses it, with no timeout, so each
+//! notification is sent from a thread of its own and only a few of them
+//! wait.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+
+use mac_notification_sys::{Notification, NotificationResponse};
+use tauri::{AppHandle, Manager, Runtime};
+
+use super::{OnClick, TicketNotification};
+
+/// Notifications left in the notification center would otherwise keep
+/// piling up threads. Beyond this many, clicking one just brings up the app.
+const MAX_WAITING: usize = 4;
+
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+/// A slot among the threads waiting for a click, given back on drop.
+struct Waiting;
+
+impl Waiting {
+    fn take() -> Option<Self> {
+        WAITING
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |waiting| {
+                (waiting < MAX_WAITING).then_some(waiting + 1)
+            })
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        WAITING.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pub fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    notification: &TicketNotification,
+    on_click: Option<OnClick>,
+) -> Result<(), String> {
+    static APPLICATION: Once = Once::new();
+    APPLICATION.call_once(|| {
+        // Outside the bundle there is no identifier of ours to post as
+        let bundle = if tauri::is_dev() {
+            "com.apple.Terminal".to_string()
+        } else {
+            app.config().identifier.clone()
+        };
+        if let Err(e) = mac_notification_sys::set_application(&bundle) {
+            log::error!("Failed to set notification sender: {}", e);
+        }
+    });
+
+    let notification = notification.clone();
+    let waiting = on_click.as_ref().and_then(|_| Waiting::take());
+    std::thread::spawn(move || {
+        let response = Notification::new()
+            .title(&notification.title)
+            .message(&notification.message)
+            .wait_for_click(waiting.is_some())
+            .send();
+        drop(waiting);
+        match response {
+            Ok(NotificationResponse::Click) => {
+                if let Some(on_click) = on_click {
+                    on_click();
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to show notification: {}", e),
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::Ticket;
+use crate::poller::{TicketDiff, TicketList};
+
+const SETTINGS_FILE: &str = "notification-settings.json";
+
+/// The subset of the frontend's notification settings the Rust side needs.
+/// Unknown keys (sound, volume, ...) are ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub enable_new_ticket_notifications: bool,
+    pub enable_assigned_ticket_notifications: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enable_new_ticket_notifications: true,
+            enable_assigned_ticket_notifications: true,
+        }
+    }
+}
+
+/// Payload of the `notifications://ticket` event, so a live frontend can add
+/// its toast and sound on top of the native notification.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketNotification {
+    pub title: String,
+    pub message: String,
+    pub ticket_id: Option<u32>,
+    /// The account the ticket belongs to, so a click can switch to it.
+    pub account_id: Option<String>,
+    /// Name of a background account, shown in front of the title.
+    pub account: Option<String>,
+}
+
+/// Decides on new/assigned ticket notifications from the poller's diffs, so
+/// they arrive even when no window is rendering.
+pub struct Notifier {
+    settings: Mutex<NotificationSettings>,
+    path: PathBuf,
+}
+
+impl Notifier {
+    pub fn new<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+        let path = config_dir.join(SETTINGS_FILE);
+
+        let settings = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            settings: Mutex::new(settings),
+            path,
+        })
+    }
+
+    fn settings(&self) -> NotificationSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    fn save(&self, settings: NotificationSettings) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        fs::write(&self.path, json).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Called by the poller after every successful poll but the first.
+    /// `label` is set for accounts other than the active one.
+    pub fn notify_diff<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        diff: &TicketDiff,
+        account: &Account,
+        label: Option<&str>,
+    ) {
+        let settings = self.settings();
+        let user_id = account.user.id;
+        let show = |mut notification: TicketNotification| {
+            if let Some(label) = label {
+                notification.title = format!("[{}] {}", label, notification.title);
+                notification.account = Some(label.to_string());
+            }
+            notification.account_id = Some(account.id.clone());
+            show(app, notification);
+        };
+
+        if settings.enable_new_ticket_notifications {
+            let new_tickets = added_to(diff, TicketList::New).cloned().collect::<Vec<_>>();
+            if let Some(notification) = new_ticket_notification(&new_tickets) {
+                show(notification);
+            }
+        }
+
+        if settings.enable_assigned_ticket_notifications {
+            let assigned = added_to(diff, TicketList::My)
+                .filter(|t| t.my_ticket_id == Some(user_id))
+                .cloned()
+                .collect::<Vec<_>>();
+            if let Some(notification) = assigned_notification(&assigned) {
+                show(notification);
+            }
+        }
+    }
+}
+
+fn added_to(diff: &TicketDiff, list: TicketList) -> impl Iterator<Item = &Ticket> {
+    diff.added
+        .iter()
+        .filter(move |added| added.list == list)
+        .flat_map(|added| added.tickets.iter())
+}
+
+/// "#123 · [Subject] · Priority: high · Customer: ACME" like the old React toasts.
+fn describe(ticket: &Ticket, suffix: Option<&str>) -> String {
+    let mut parts = vec![format!("#{}", ticket.id)];
+    if let Some(subject) = ticket.subject.as_deref().filter(|s| !s.is_empty()) {
+        parts.push(format!("[{}]", subject));
+    }
+    if !ticket.priority.is_empty() {
+        parts.push(format!("Priority: {}", ticket.priority));
+    }
+    if !ticket.company.name.is_empty() {
+        parts.push(format!("Customer: {}", ticket.company.name));
+    }
+    if let Some(suffix) = suffix
+        .or(ticket.summary.as_deref())
+        .filter(|s| !s.is_empty())
+    {
+        parts.push(suffix.to_string());
+    }
+    parts.join(" · ")
+}
+
+fn new_ticket_notification(tickets: &[Ticket]) -> Option<TicketNotification> {
+    match tickets {
+        [] => None,
+        [ticket] => Some(TicketNotification {
+            title: "New Ticket Available".to_string(),
+            message: describe(ticket, None),
+            ticket_id: Some(ticket.id),
+            account_id: None,
+            account: None,
+        }),
+        _ => Some(TicketNotification {
+            title: format!("{} New Tickets Available", tickets.len()),
+            message: format!(
+                "{} new tickets are now available in your pool",
+                tickets.len()
+            ),
+            ticket_id: None,
+            account_id: None,
+            account: None,
+        }),
+    }
+}
+
+fn assigned_notification(tickets: &[Ticket]) -> Option<TicketNotification> {
+    match tickets {
+        [] => None,
+        [ticket] => Some(TicketNotification {
+            title: "New Ticket Assigned".to_string(),
+            message: describe(ticket, Some("has been assigned to you")),
+            ticket_id: Some(ticket.id),
+            account_id: None,
+            account: None,
+        }),
+        _ => Some(TicketNotification {
+            title: format!("{} New Tickets Assigned", tickets.len()),
+            message: format!("{} tickets have been assigned to you", tickets.len()),
+            ticket_id: None,
+            account_id: None,
+            account: None,
+        }),
+    }
+}
+
+fn show<R: Runtime>(app: &AppHandle<R>, notification: TicketNotification) {
+    let _ = app.emit("notifications://ticket", &notification);
+
+    if let Err(e) = show_native(app, &notification) {
+        log::error!("Failed to show notification: {}", e);
+    }
+}
+
+/// A notice of the app's own, like a timer paused by a shortcut. The windows
+/// don't toast it; they show the change anyway.
+pub(crate) fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, message: &str) {
+    let notification = TicketNotification {
+        title: title.to_string(),
+        message: message.to_string(),
+        ticket_id: None,
+        account_id: None,
+        account: None,
+    };
+    if let Err(e) = show_native(app, &notification) {
+        log::error!("Failed to show notification: {}", e);
+    }
+}
+
+/// Runs when a notification is clicked.
+type OnClick = Box<dyn FnOnce() + Send>;
+
+// The notification plugin does not report clicks on desktop, so each
+// platform's notifications are shown by hand to get them back
+#[cfg_attr(
+    all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ),
+    path = "xdg.rs"
+)]
+#[cfg_attr(target_os = "macos", path = "macos.rs")]
+#[cfg_attr(windows, path = "windows.rs")]
+mod native;
+
+/// Clicking one about a single ticket opens it, switching accounts if needed.
+fn show_native<R: Runtime>(
+    app: &AppHandle<R>,
+    notification: &TicketNotification,
+) -> Result<(), String> {
+    let on_click = notification.ticket_id.map(|ticket_id| {
+        let app = app.clone();
+        let account_id = notification.account_id.clone();
+        Box::new(move || {
+            if let Err(e) = crate::show_ticket_window(&app, ticket_id, account_id.as_deref()) {
+                log::error!("Failed to open ticket window: {}", e);
+            }
+        }) as OnClick
+    });
+
+    native::show(app, notification, on_click)
+}
+
+/// For notifications of the frontend's own; a click opens the ticket in the
+/// active account.
+#[tauri::command]
+pub fn show_notification<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    title: String,
+    message: String,
+    ticket_id: Option<u32>,
+) -> Result<(), String> {
+    let notification = TicketNotification {
+        title,
+        message,
+        ticket_id,
+        account_id: accounts.active().ok().map(|account| account.id.clone()),
+        account: None,
+    };
+    show_native(&app, &notification)
+}
+
+#[tauri::command]
+pub fn get_notification_settings(notifier: State<'_, Notifier>) -> NotificationSettings {
+    notifier.settings()
+}
+
+#[tauri::command]
+pub fn set_notification_settings(
+    notifier: State<'_, Notifier>,
+    settings: NotificationSettings,
+) -> Result<(), String> {
+    notifier.save(settings)
+}
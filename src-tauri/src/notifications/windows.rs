@@ -0,0 +1,41 @@
+//! Notifications as toasts, which call back into the app when clicked.
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_winrt_notification::Toast;
+
+use super::{OnClick, TicketNotification};
+
+/// Only the installed app is registered under its identifier; from a build
+/// folder Windows shows toasts as PowerShell's or not at all.
+fn app_id<R: Runtime>(app: &AppHandle<R>) -> String {
+    let in_build_folder = tauri::utils::platform::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .is_some_and(|dir| dir.ends_with(r"target\debug") || dir.ends_with(r"target\release"));
+    if in_build_folder {
+        Toast::POWERSHELL_APP_ID.to_string()
+    } else {
+        app.config().identifier.clone()
+    }
+}
+
+pub fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    notification: &TicketNotification,
+    on_click: Option<OnClick>,
+) -> Result<(), String> {
+    let mut toast = Toast::new(&app_id(app))
+        .title(&notification.title)
+        .text1(&notification.message);
+    if let Some(on_click) = on_click {
+        let mut on_click = Some(on_click);
+        toast = toast.on_activated(move |_| {
+            if let Some(on_click) = on_click.take() {
+                on_click();
+            }
+            Ok(())
+        });
+    }
+
+    toast.show().map_err(|e| format!("{:?}", e))
+}
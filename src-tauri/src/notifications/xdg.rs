@@ -0,0 +1,133 @@
+//! Notifications through the desktop's notification daemon on the session
+//! bus. One connection shows them all and hears which of them was clicked.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures_util::StreamExt;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::OnceCell;
+use zbus::zvariant::Value;
+use zbus::{proxy, Connection};
+
+use super::{OnClick, TicketNotification};
+
+/// The action of a click on the notification itself.
+const DEFAULT_ACTION: &str = "default";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// By notification id, until the notification is clicked or closed.
+type Clicks = Arc<Mutex<HashMap<u32, OnClick>>>;
+
+struct Daemon {
+    proxy: NotificationsProxy<'static>,
+    clicks: Clicks,
+}
+
+/// Connected on the first notification; tried again on the next if that fails.
+static DAEMON: OnceCell<Daemon> = OnceCell::const_new();
+
+async fn connect() -> zbus::Result<Daemon> {
+    let connection = Connection::session().await?;
+    let proxy = NotificationsProxy::new(&connection).await?;
+    // Before the first notification, so no click goes unheard
+    let mut invoked = proxy.receive_action_invoked().await?;
+    let mut closed = proxy.receive_notification_closed().await?;
+
+    let clicks = Clicks::default();
+    let pending = clicks.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(signal) = invoked.next() => {
+                    let Ok(args) = signal.args() else { continue };
+                    if args.action_key() != DEFAULT_ACTION {
+                        continue;
+                    }
+                    let on_click = pending.lock().unwrap().remove(args.id());
+                    if let Some(on_click) = on_click {
+                        on_click();
+                    }
+                }
+                Some(signal) = closed.next() => {
+                    if let Ok(args) = signal.args() {
+                        pending.lock().unwrap().remove(args.id());
+                    }
+                }
+                else => return,
+            }
+        }
+    });
+
+    Ok(Daemon { proxy, clicks })
+}
+
+async fn notify(
+    app_name: &str,
+    notification: &TicketNotification,
+    on_click: Option<OnClick>,
+) -> zbus::Result<()> {
+    let daemon = DAEMON.get_or_try_init(connect).await?;
+    let actions: &[&str] = match on_click {
+        Some(_) => &[DEFAULT_ACTION, "Open Ticket"],
+        None => &[],
+    };
+    let id = daemon
+        .proxy
+        .notify(
+            app_name,
+            0,
+            "",
+            &notification.title,
+            &notification.message,
+            actions,
+            HashMap::new(),
+            -1,
+        )
+        .await?;
+
+    if let Some(on_click) = on_click {
+        daemon.clicks.lock().unwrap().insert(id, on_click);
+    }
+    Ok(())
+}
+
+pub fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    notification: &TicketNotification,
+    on_click: Option<OnClick>,
+) -> Result<(), String> {
+    let app_name = app.config().identifier.clone();
+    let notification = notification.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = notify(&app_name, &notification, on_click).await {
+            log::error!("Failed to show notification: {}", e);
+        }
+    });
+    Ok(())
+}
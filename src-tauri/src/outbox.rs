@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::{ApiResponse, HistoryEntry};
+use crate::api::{ApiClient, ApiError};
+use crate::timer;
+
+/// A write the technician made that has to reach the server eventually.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mutation {
+    History(HistoryEntry),
+    NewTodo {
+        ticket_id: u32,
+        user_id: u32,
+        todo: String,
+    },
+    CheckTodo {
+        #[serde(default)]
+        ticket_id: Option<u32>,
+        todo_id: u32,
+        /// 1 to check, 0 to uncheck.
+        #[serde(rename = "type")]
+        checked: u8,
+    },
+    Message {
+        ticket_id: u32,
+        user_id: u32,
+        message: String,
+    },
+}
+
+impl Mutation {
+    pub fn ticket_id(&self) -> Option<u32> {
+        match self {
+            Mutation::History(entry) => Some(entry.ticket_id),
+            Mutation::NewTodo { ticket_id, .. } | Mutation::Message { ticket_id, .. } => {
+                Some(*ticket_id)
+            }
+            Mutation::CheckTodo { ticket_id, .. } => *ticket_id,
+        }
+    }
+
+    async fn send(
+        &self,
+        api: &ApiClient,
+        idempotency_key: &str,
+    ) -> Result<ApiResponse<Value>, ApiError> {
+        let (endpoint, body) = match self {
+            Mutation::History(entry) => (
+                "/saveVerlaufApi",
+                serde_json::to_value(entry).map_err(|e| ApiError::Decode(e.to_string()))?,
+            ),
+            Mutation::NewTodo {
+                ticket_id,
+                user_id,
+                todo,
+            } => (
+                "/newTodo",
+                json!({ "ticket_id": ticket_id, "user_id": user_id, "todo": todo }),
+            ),
+            Mutation::CheckTodo {
+                todo_id, checked, ..
+            } => ("/checkTodo", json!({ "todo_id": todo_id, "type": checked })),
+            Mutation::Message {
+                ticket_id,
+                user_id,
+                message,
+            } => (
+                "/sendMessage",
+                json!({ "ticket_id": ticket_id, "user_id": user_id, "message": message }),
+            ),
+        };
+
+        api.submit_with_key(endpoint, &body, idempotency_key).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub idempotency_key: String,
+    pub ticket_id: Option<u32>,
+    pub mutation: Mutation,
+    /// Milliseconds since the epoch.
+    pub created_at: i64,
+    /// Why the server rejected it; `None` while it is still pending.
+    pub error: Option<String>,
+}
+
+enum Outcome {
+    Accepted(ApiResponse<Value>),
+    Rejected(String),
+    /// Offline, or the server had trouble. Try again after the next poll.
+    Retry(ApiError),
+}
+
+fn classify(result: Result<ApiResponse<Value>, ApiError>) -> Outcome {
+    match result {
+        // `/newTodo` answers "sucess"
+        Ok(response) if matches!(response.status.as_str(), "success" | "sucess") => {
+            Outcome::Accepted(response)
+        }
+        Ok(response) => Outcome::Rejected(
+            response
+                .message
+                .or(response.result)
+                .unwrap_or_else(|| format!("Server answered \"{}\"", response.status)),
+        ),
+        // Not when signed out, timed out or throttled: the entry itself is fine
+        Err(e @ ApiError::Http { status, .. })
+            if status < 500 && !matches!(status, 401 | 408 | 429) =>
+        {
+            Outcome::Rejected(e.to_string())
+        }
+        Err(e) => Outcome::Retry(e),
+    }
+}
+
+/// Guards replays so two flushes never send the same entry twice.
+#[derive(Default)]
+pub struct Outbox {
+    flushing: tokio::sync::Mutex<()>,
+}
+
+fn emit_changed<R: Runtime>(app: &AppHandle<R>) {
+    let _ = app.emit("outbox://changed", ());
+}
+
+/// Sends pending writes in the order they were recorded. Stops at the first
+/// one that cannot get through; rejected ones are set aside for the user.
+pub async fn flush<R: Runtime>(app: AppHandle<R>, account: Arc<Account>) {
+    let Ok(_flushing) = account.outbox.flushing.try_lock() else {
+        return;
+    };
+    let user_id = account.user.id;
+    let cache = &account.cache;
+    let api = &account.api;
+
+    // Loop so entries recorded while we were sending go out in this run too
+    loop {
+        let entries = match cache.pending_mutations(user_id) {
+            Ok(entries) if !entries.is_empty() => entries,
+            Ok(_) => return,
+            Err(e) => {
+                log::error!("Failed to read outbox: {}", e);
+                return;
+            }
+        };
+
+        for entry in entries {
+            let result = match classify(entry.mutation.send(api, &entry.idempotency_key).await) {
+                Outcome::Accepted(_) => {
+                    if let Mutation::History(history) = &entry.mutation {
+                        timer::history_saved(&app, &account, history);
+                    }
+                    cache.remove_mutation(entry.id)
+                }
+                Outcome::Rejected(error) => cache.reject_mutation(entry.id, &error),
+                Outcome::Retry(e) => {
+                    log::warn!("Outbox replay paused: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = result {
+                log::error!("Failed to update outbox: {}", e);
+                return;
+            }
+            emit_changed(&app);
+        }
+    }
+}
+
+fn pending_response() -> ApiResponse<Value> {
+    ApiResponse {
+        status: "pending".to_string(),
+        result: None,
+        message: Some("Saved offline, it will be sent once the connection is back".to_string()),
+        payload: Value::Null,
+    }
+}
+
+/// Records a write and sends it right away unless older ones are still
+/// waiting. Answers with status "pending" when it stays in the outbox.
+#[tauri::command]
+pub async fn submit_mutation<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    mutation: Mutation,
+    idempotency_key: String,
+) -> Result<ApiResponse<Value>, String> {
+    let account = accounts.active()?;
+    let (cache, api) = (&account.cache, &account.api);
+    let user_id = account.user.id;
+
+    // Before enqueuing, or a running flush would send the entry as well
+    let flushing = account.outbox.flushing.lock().await;
+    let id = cache.enqueue_mutation(user_id, &idempotency_key, &mutation)?;
+    emit_changed(&app);
+
+    // Keep the order the technician made the changes in
+    if cache.has_pending_before(user_id, id)? {
+        drop(flushing);
+        tauri::async_runtime::spawn(flush(app, account.clone()));
+        return Ok(pending_response());
+    }
+
+    let outcome = classify(mutation.send(api, &idempotency_key).await);
+    let response = match outcome {
+        Outcome::Accepted(response) => {
+            cache.remove_mutation(id)?;
+            if let Mutation::History(entry) = &mutation {
+                timer::history_saved(&app, &account, entry);
+            }
+            Ok(response)
+        }
+        // The user is looking at the form, so report it there instead
+        Outcome::Rejected(error) => {
+            cache.remove_mutation(id)?;
+            Err(error)
+        }
+        Outcome::Retry(_) => Ok(pending_response()),
+    };
+    drop(flushing);
+    emit_changed(&app);
+    response
+}
+
+#[tauri::command]
+pub fn get_outbox(
+    accounts: State<'_, Accounts>,
+    ticket_id: Option<u32>,
+) -> Result<Vec<OutboxEntry>, String> {
+    match accounts.active() {
+        Ok(account) => account.cache.outbox(account.user.id, ticket_id),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Sends a rejected entry again, with the user's corrections if given.
+#[tauri::command]
+pub fn retry_outbox_entry<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    id: i64,
+    mutation: Option<Mutation>,
+) -> Result<(), String> {
+    let account = accounts.active()?;
+    account.cache.requeue_mutation(id, mutation.as_ref())?;
+    emit_changed(&app);
+    tauri::async_runtime::spawn(flush(app, account));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn discard_outbox_entry<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    id: i64,
+) -> Result<(), String> {
+    accounts.active()?.cache.remove_mutation(id)?;
+    emit_changed(&app);
+    Ok(())
+}
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Notify;
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::{Ticket, TicketFilter, TicketsResponse, User};
+use crate::notifications::Notifier;
+
+pub const DEFAULT_INTERVAL_SECS: u64 = 30;
+const MIN_INTERVAL_SECS: u64 = 5;
+
+/// The three lists `/getTickets` returns, named like their JSON keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TicketList {
+    #[serde(rename = "new_tickets")]
+    New,
+    #[serde(rename = "my_tickets")]
+    My,
+    #[serde(rename = "all_tickets")]
+    All,
+}
+
+impl TicketList {
+    pub const ALL: [TicketList; 3] = [TicketList::New, TicketList::My, TicketList::All];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TicketList::New => "new_tickets",
+            TicketList::My => "my_tickets",
+            TicketList::All => "all_tickets",
+        }
+    }
+
+    pub fn of(self, response: &TicketsResponse) -> &[Ticket] {
+        match self {
+            TicketList::New => &response.new_tickets,
+            TicketList::My => &response.my_tickets,
+            TicketList::All => &response.all_tickets,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketsAdded {
+    pub list: TicketList,
+    pub tickets: Vec<Ticket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketChange {
+    pub ticket: Ticket,
+    /// JSON names of the fields that differ from the previous poll.
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketsChanged {
+    pub list: TicketList,
+    pub changes: Vec<TicketChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketsRemoved {
+    pub list: TicketList,
+    pub ids: Vec<u32>,
+}
+
+/// Everything that happened between two polls.
+#[derive(Debug, Default)]
+pub struct TicketDiff {
+    pub added: Vec<TicketsAdded>,
+    pub changed: Vec<TicketsChanged>,
+    pub removed: Vec<TicketsRemoved>,
+}
+
+impl TicketDiff {
+    pub fn between(old: &TicketsResponse, new: &TicketsResponse) -> Self {
+        let mut diff = TicketDiff::default();
+
+        for list in TicketList::ALL {
+            let before: HashMap<u32, &Ticket> = list.of(old).iter().map(|t| (t.id, t)).collect();
+            let after: HashMap<u32, &Ticket> = list.of(new).iter().map(|t| (t.id, t)).collect();
+
+            let added: Vec<Ticket> = list
+                .of(new)
+                .iter()
+                .filter(|t| !before.contains_key(&t.id))
+                .cloned()
+                .collect();
+
+            let changes: Vec<TicketChange> = list
+                .of(new)
+                .iter()
+                .filter_map(|t| {
+                    let previous = before.get(&t.id)?;
+                    let fields = changed_fields(previous, t);
+                    (!fields.is_empty()).then(|| TicketChange {
+                        ticket: t.clone(),
+                        fields,
+                    })
+                })
+                .collect();
+
+            let ids: Vec<u32> = list
+                .of(old)
+                .iter()
+                .map(|t| t.id)
+                .filter(|id| !after.contains_key(id))
+                .collect();
+
+            if !added.is_empty() {
+                diff.added.push(TicketsAdded {
+                    list,
+                    tickets: added,
+                });
+            }
+            if !changes.is_empty() {
+                diff.changed.push(TicketsChanged { list, changes });
+            }
+            if !ids.is_empty() {
+                diff.removed.push(TicketsRemoved { list, ids });
+            }
+        }
+
+        diff
+    }
+}
+
+fn changed_fields(old: &Ticket, new: &Ticket) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    new.iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .chain(old.keys().filter(|key| !new.contains_key(*key)).cloned())
+        .collect()
+}
+
+#[derive(Default)]
+struct PollerState {
+    filter: Option<TicketFilter>,
+    interval_secs: u64,
+    paused: bool,
+    /// The account was removed; ends the loop.
+    stopped: bool,
+    snapshot: Option<TicketsResponse>,
+}
+
+/// One account's `/getTickets` poller, shared by the main window and every
+/// `ticket-*` window, so webview throttling no longer stalls updates.
+pub struct TicketPoller {
+    state: Mutex<PollerState>,
+    wake: Notify,
+}
+
+impl Default for TicketPoller {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(PollerState {
+                interval_secs: DEFAULT_INTERVAL_SECS,
+                ..Default::default()
+            }),
+            wake: Notify::new(),
+        }
+    }
+}
+
+impl TicketPoller {
+    /// Points the poller at a logged in user, or stops it on logout.
+    pub fn set_user(&self, user: Option<&User>) {
+        let mut state = self.state.lock().unwrap();
+        state.filter = user.map(TicketFilter::for_user);
+        state.snapshot = None;
+        drop(state);
+        self.wake.notify_one();
+    }
+
+    pub fn snapshot(&self) -> Option<TicketsResponse> {
+        self.state.lock().unwrap().snapshot.clone()
+    }
+
+    pub fn refresh_now(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Takes over the interval and pause of another account's poller, so a
+    /// newly added account polls like the others.
+    pub fn inherit(&self, other: &TicketPoller) {
+        let (interval_secs, paused) = {
+            let other = other.state.lock().unwrap();
+            (other.interval_secs, other.paused)
+        };
+        let mut state = self.state.lock().unwrap();
+        state.interval_secs = interval_secs;
+        state.paused = paused;
+    }
+
+    pub fn stop(&self) {
+        self.state.lock().unwrap().stopped = true;
+        self.wake.notify_one();
+    }
+
+    fn stopped(&self) -> bool {
+        self.state.lock().unwrap().stopped
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.state.lock().unwrap().interval_secs)
+    }
+}
+
+async fn poll_once<R: Runtime>(app: &AppHandle<R>, account: &Arc<Account>) {
+    let poller = &account.poller;
+    let filter = {
+        let state = poller.state.lock().unwrap();
+        match (&state.filter, state.paused) {
+            (Some(filter), false) => filter.clone(),
+            _ => return,
+        }
+    };
+
+    let response = match account.api.get_tickets(&filter).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::warn!("Failed to poll tickets of {}: {}", account.id, e);
+            return;
+        }
+    };
+
+    let (diff, first_poll) = {
+        let mut state = poller.state.lock().unwrap();
+        // The account was removed while the request was in flight
+        if state.stopped || state.filter.as_ref().map(|f| f.user_id) != Some(filter.user_id) {
+            return;
+        }
+        let previous = state.snapshot.replace(response.clone());
+        let first_poll = previous.is_none();
+        (
+            TicketDiff::between(&previous.unwrap_or_default(), &response),
+            first_poll,
+        )
+    };
+
+    account.cache.store_tickets(filter.user_id, &response);
+    account.index.index_tickets(
+        response
+            .new_tickets
+            .iter()
+            .chain(&response.my_tickets)
+            .chain(&response.all_tickets),
+    );
+
+    // Windows only show the active account; the others just notify
+    let accounts = app.state::<Accounts>();
+    let active = accounts.is_active(&account.id);
+    if active {
+        emit_diff(app, &diff);
+    }
+
+    // We are online again, send whatever piled up meanwhile
+    tauri::async_runtime::spawn(crate::outbox::flush(app.clone(), account.clone()));
+
+    // Everything is "added" on the first poll, that is not news
+    if !first_poll {
+        let label = (!active).then(|| accounts.label(app, account));
+        app.state::<Notifier>()
+            .notify_diff(app, &diff, account, label.as_deref());
+    }
+}
+
+fn emit_diff<R: Runtime>(app: &AppHandle<R>, diff: &TicketDiff) {
+    for added in &diff.added {
+        let _ = app.emit("tickets://added", added);
+    }
+    for changed in &diff.changed {
+        let _ = app.emit("tickets://changed", changed);
+    }
+    for removed in &diff.removed {
+        let _ = app.emit("tickets://removed", removed);
+    }
+}
+
+/// Started when an account is added; runs until it is removed.
+pub fn spawn<R: Runtime>(app: AppHandle<R>, account: Arc<Account>) {
+    tauri::async_runtime::spawn(async move {
+        let poller = &account.poller;
+        while !poller.stopped() {
+            poll_once(&app, &account).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(poller.interval()) => {}
+                _ = poller.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// Applies to every account.
+#[tauri::command]
+pub fn set_poll_interval(accounts: State<'_, Accounts>, seconds: u64) {
+    for account in accounts.all() {
+        account.poller.state.lock().unwrap().interval_secs = seconds.max(MIN_INTERVAL_SECS);
+        // Restart the sleep so the new interval applies right away
+        account.poller.refresh_now();
+    }
+}
+
+#[tauri::command]
+pub fn pause_polling(accounts: State<'_, Accounts>) {
+    for account in accounts.all() {
+        account.poller.state.lock().unwrap().paused = true;
+    }
+}
+
+#[tauri::command]
+pub fn resume_polling(accounts: State<'_, Accounts>) {
+    for account in accounts.all() {
+        account.poller.state.lock().unwrap().paused = false;
+        account.poller.refresh_now();
+    }
+}
+
+#[tauri::command]
+pub fn refresh_tickets_now(accounts: State<'_, Accounts>) {
+    for account in accounts.all() {
+        account.poller.refresh_now();
+    }
+}
+
+/// The active account's last poll, or what the offline cache holds until
+/// the first poll of this session finished.
+#[tauri::command]
+pub fn get_tickets_snapshot(
+    accounts: State<'_, Accounts>,
+) -> Result<Option<TicketsResponse>, String> {
+    let Ok(account) = accounts.active() else {
+        return Ok(None);
+    };
+    if let Some(snapshot) = account.poller.snapshot() {
+        return Ok(Some(snapshot));
+    }
+    account.cache.tickets(account.user.id)
+}
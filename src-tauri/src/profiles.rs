@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_http::reqwest::{self, Certificate, Url};
+
+use crate::accounts::Accounts;
+use crate::api::{ApiClient, DEFAULT_BASE_URL};
+use crate::network::{self, Network};
+
+const PROFILES_FILE: &str = "server-profiles.json";
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// One Ticketbase instance: the hosted tenant, staging, an on-prem server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerProfile {
+    /// Generated from the name when empty.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Up to and including the `/api` prefix.
+    pub base_url: String,
+    /// PEM bundle with extra roots to trust, for servers behind a private CA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_certificate: Option<PathBuf>,
+}
+
+impl ServerProfile {
+    fn hosted() -> Self {
+        Self {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Ticketbase".to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            ca_certificate: None,
+        }
+    }
+
+    fn url(&self) -> Result<Url, String> {
+        let url = Url::parse(&self.base_url).map_err(|e| format!("{}: {}", self.base_url, e))?;
+        if !matches!(url.scheme(), "https" | "http") || url.host_str().is_none() {
+            return Err(format!("Not an HTTP(S) URL: {}", self.base_url));
+        }
+        Ok(url)
+    }
+
+    fn certificates(&self) -> Result<Vec<Certificate>, String> {
+        match &self.ca_certificate {
+            Some(path) => network::read_certificates(path),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerProfiles {
+    pub profiles: Vec<ServerProfile>,
+    /// Id of the profile the login screen offers first.
+    pub active: String,
+}
+
+impl Default for ServerProfiles {
+    fn default() -> Self {
+        Self {
+            profiles: vec![ServerProfile::hosted()],
+            active: DEFAULT_PROFILE_ID.to_string(),
+        }
+    }
+}
+
+impl ServerProfiles {
+    fn get(&self, id: &str) -> Option<&ServerProfile> {
+        self.profiles.iter().find(|profile| profile.id == id)
+    }
+
+    fn unique_id(&self, name: &str) -> String {
+        let slug = name
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let slug = if slug.is_empty() {
+            "server".to_string()
+        } else {
+            slug
+        };
+
+        std::iter::once(slug.clone())
+            .chain((2..).map(|n| format!("{}-{}", slug, n)))
+            .find(|id| self.get(id).is_none())
+            .unwrap()
+    }
+}
+
+/// The configured server profiles. Each account's `ApiClient` is set up
+/// from the profile it signed in to.
+pub struct ProfileStore {
+    path: PathBuf,
+    profiles: Mutex<ServerProfiles>,
+}
+
+impl ProfileStore {
+    pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+        let path = config_dir.join(PROFILES_FILE);
+
+        let profiles = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<ServerProfiles>(&data).ok())
+            .filter(|profiles| !profiles.profiles.is_empty())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            profiles: Mutex::new(profiles),
+        })
+    }
+
+    pub fn profiles(&self) -> ServerProfiles {
+        self.profiles.lock().unwrap().clone()
+    }
+
+    pub fn profile(&self, id: &str) -> Option<ServerProfile> {
+        self.profiles.lock().unwrap().get(id).cloned()
+    }
+
+    /// A new `ApiClient` talking to the profile's server.
+    pub fn client<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Result<ApiClient, String> {
+        let profile = self
+            .profile(id)
+            .ok_or_else(|| format!("Unknown server profile: {}", id))?;
+        let api = ApiClient::new(&profile.base_url);
+        Self::configure(app, &api, &profile)?;
+        Ok(api)
+    }
+
+    fn save(&self, profiles: &ServerProfiles) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(profiles).map_err(|e| e.to_string())?;
+        fs::write(&self.path, json).map_err(|e| e.to_string())
+    }
+
+    /// Points `api` at the profile, through the proxy of the network settings.
+    pub fn configure<R: Runtime>(
+        app: &AppHandle<R>,
+        api: &ApiClient,
+        profile: &ServerProfile,
+    ) -> Result<(), String> {
+        let client = profile
+            .certificates()?
+            .into_iter()
+            .fold(
+                app.state::<Network>().configure(reqwest::Client::builder()),
+                |builder, certificate| builder.add_root_certificate(certificate),
+            )
+            .build()
+            .map_err(|e| e.to_string())?;
+        api.set_http_client(client);
+        api.set_base_url(&profile.base_url);
+        Ok(())
+    }
+
+    /// Makes `id` the profile the login screen offers first.
+    pub fn select(&self, id: &str) -> Result<ServerProfile, String> {
+        let mut profiles = self.profiles.lock().unwrap();
+        let profile = profiles
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown server profile: {}", id))?;
+
+        if profiles.active != profile.id {
+            profiles.active = profile.id.clone();
+            self.save(&profiles)?;
+        }
+        Ok(profile)
+    }
+}
+
+/// Whether `id` is safe to use in account ids and file names; `unique_id`
+/// only makes such ids.
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn normalize(mut profile: ServerProfile) -> Result<ServerProfile, String> {
+    // Empty asks for a new id
+    if !profile.id.is_empty() && !valid_id(&profile.id) {
+        return Err(format!("Invalid server profile id: {}", profile.id));
+    }
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("The server profile needs a name".to_string());
+    }
+    profile.base_url = profile.base_url.trim().trim_end_matches('/').to_string();
+    profile.url()?;
+    profile.ca_certificate = profile
+        .ca_certificate
+        .filter(|path| !path.as_os_str().is_empty());
+    // Rejects an unreadable bundle now instead of at the next login
+    profile.certificates()?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn get_server_profiles(store: State<'_, ProfileStore>) -> ServerProfiles {
+    store.profiles()
+}
+
+/// Adds a profile, or replaces the one with the same id.
+#[tauri::command]
+pub fn save_server_profile<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, ProfileStore>,
+    profile: ServerProfile,
+) -> Result<ServerProfile, String> {
+    let mut profile = normalize(profile)?;
+
+    let mut profiles = store.profiles.lock().unwrap();
+    if profile.id.is_empty() {
+        profile.id = profiles.unique_id(&profile.name);
+    }
+    match profiles.profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.profiles.push(profile.clone()),
+    }
+    store.save(&profiles)?;
+    drop(profiles);
+
+    app.state::<Accounts>().reconfigure(&app, &profile)?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_server_profile(
+    store: State<'_, ProfileStore>,
+    accounts: State<'_, Accounts>,
+    id: String,
+) -> Result<(), String> {
+    if accounts.uses_profile(&id) {
+        return Err("Sign out of the accounts on this server first".to_string());
+    }
+    let mut profiles = store.profiles.lock().unwrap();
+    if profiles.active == id {
+        return Err("The selected server profile cannot be deleted".to_string());
+    }
+    profiles.profiles.retain(|profile| profile.id != id);
+    store.save(&profiles)
+}
+
+/// Chosen on the login screen, before the credentials are sent.
+#[tauri::command]
+pub fn select_server_profile(
+    store: State<'_, ProfileStore>,
+    id: String,
+) -> Result<ServerProfile, String> {
+    store.select(&id)
+}
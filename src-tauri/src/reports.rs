@@ -0,0 +1,290 @@
+//! Report 4, Report 5 and the top users as files: CSV in the delimiter and
+//! encoding the user's Excel expects, XLSX with typed cells, or a PDF to
+//! print.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::accounts::Accounts;
+use crate::export::{self, csv, pdf, Cell, Table};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Report {
+    Report4,
+    Report5,
+    TopUsers,
+}
+
+/// What a column holds. Only numeric ones turn text into numbers, so a
+/// name like `007` keeps its zeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Number,
+}
+
+impl Report {
+    fn title(self) -> &'static str {
+        match self {
+            Report::Report4 => "Customer Reviews",
+            Report::Report5 => "Technician Statistics",
+            Report::TopUsers => "Top Users",
+        }
+    }
+
+    /// The keys the server uses, what the columns are called and hold.
+    fn columns(self) -> &'static [(&'static str, &'static str, Kind)] {
+        match self {
+            Report::Report4 => &[
+                ("Techniker", "Technician", Kind::Text),
+                ("Ticket-ID", "Ticket", Kind::Text),
+                ("Kunde", "Customer", Kind::Text),
+                ("Note", "Rating", Kind::Number),
+                ("Feedback", "Feedback", Kind::Text),
+            ],
+            Report::Report5 => &[
+                ("Techniker", "Technician", Kind::Text),
+                ("All tickets", "All Tickets", Kind::Number),
+                ("All closed tickets", "Closed", Kind::Number),
+                ("All reopened tickets", "Reopened", Kind::Number),
+                ("All reviewed tickets", "Reviewed", Kind::Number),
+                ("Percentage 1", "Reopen %", Kind::Number),
+                ("Percentage 2", "Review %", Kind::Number),
+            ],
+            Report::TopUsers => &[
+                ("id", "ID", Kind::Text),
+                ("name", "Name", Kind::Text),
+                ("total_points", "Points", Kind::Number),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Xlsx,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportExportOptions {
+    pub report: Report,
+    /// `YYYY-MM-DD`, for Report 4 and 5.
+    #[serde(default)]
+    pub start_date: String,
+    #[serde(default)]
+    pub end_date: String,
+    /// 1 to 12, for the top users.
+    #[serde(default)]
+    pub month: u32,
+    pub format: Format,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub csv: csv::Dialect,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportExportSummary {
+    pub rows: usize,
+}
+
+/// A number, but not one `f64` only knows as `NaN` or `inf`.
+fn number(text: &str) -> Option<f64> {
+    text.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// `2026-10-03`, or a datetime like `2026-10-03 14:05:00`, and nothing else.
+fn date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|datetime| datetime.date())
+        })
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|datetime| datetime.date_naive())
+        })
+}
+
+/// Numbers stay numbers whether the server sent them as such or as text,
+/// `12.50%` becomes a percentage and a date a date.
+fn cell(value: &Value, kind: Kind) -> Cell {
+    match value {
+        Value::Number(number) => number.as_f64().map(Cell::Number).unwrap_or_default(),
+        Value::String(text) => {
+            let trimmed = text.trim();
+            if kind == Kind::Number {
+                if let Some(percent) = trimmed.strip_suffix('%').and_then(|p| number(p.trim())) {
+                    return Cell::Percent(percent / 100.0);
+                }
+                if let Some(number) = number(trimmed) {
+                    return Cell::Number(number);
+                }
+            }
+            match date(trimmed) {
+                Some(date) => Cell::Date(date),
+                None => Cell::Text(text.clone()),
+            }
+        }
+        Value::Null => Cell::default(),
+        Value::Bool(value) => Cell::from(if *value { "yes" } else { "no" }),
+        other => Cell::Text(other.to_string()),
+    }
+}
+
+/// Rows of reports 4 and 5. Ticketbase sends them as a list, or as an
+/// object keyed by row number with the average rating next to the rows.
+fn report_rows(report: Value) -> Vec<Value> {
+    let Value::Object(entries) = report else {
+        return match report {
+            Value::Array(rows) => rows,
+            _ => Vec::new(),
+        };
+    };
+
+    let mut rows = Vec::new();
+    let mut average = None;
+    for (key, value) in entries {
+        match key.parse::<u64>() {
+            Ok(number) => rows.push((number, value)),
+            Err(_) if key == "average note" => average = Some(json!({ key: value })),
+            Err(_) => {}
+        }
+    }
+    // Keys come sorted as text: "1", "10", "2"
+    rows.sort_by_key(|(number, _)| *number);
+    rows.into_iter()
+        .map(|(_, row)| row)
+        .chain(average)
+        .collect()
+}
+
+/// The report's rows, and what the PDF says above them. Report 4 ends with
+/// a row that only holds the average rating.
+fn table(report: Report, rows: &[Value]) -> (Table, Vec<String>) {
+    let columns = report.columns();
+    let mut lines = Vec::new();
+    let rows: Vec<Vec<Cell>> = rows
+        .iter()
+        .filter_map(|row| {
+            if let Some(average) = row.get("average note") {
+                let average = match average {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                lines.push(format!("Average rating: {average}"));
+                return None;
+            }
+            row.is_object().then(|| {
+                columns
+                    .iter()
+                    .map(|(key, _, kind)| {
+                        row.get(key)
+                            .map(|value| cell(value, *kind))
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+        })
+        .collect();
+    let headers = columns
+        .iter()
+        .map(|(_, header, _)| header.to_string())
+        .collect();
+    (Table { headers, rows }, lines)
+}
+
+/// Fetches a report for the active account and writes it to `options.path`.
+#[tauri::command]
+pub async fn export_report(
+    accounts: State<'_, Accounts>,
+    options: ReportExportOptions,
+) -> Result<ReportExportSummary, String> {
+    let account = accounts.active()?;
+    let rows = match options.report {
+        Report::Report4 | Report::Report5 => {
+            let response = if options.report == Report::Report4 {
+                account
+                    .api
+                    .get_report4(&options.start_date, &options.end_date)
+                    .await
+            } else {
+                account
+                    .api
+                    .get_report5(&options.start_date, &options.end_date)
+                    .await
+            };
+            report_rows(response.map_err(|e| e.to_string())?.payload.report)
+        }
+        Report::TopUsers => {
+            account
+                .api
+                .get_top_users(options.month)
+                .await
+                .map_err(|e| e.to_string())?
+                .payload
+                .top_users
+        }
+    };
+
+    let (table, mut lines) = table(options.report, &rows);
+    let period = match options.report {
+        Report::TopUsers => format!("Month: {}", options.month),
+        _ => format!("Period: {} to {}", options.start_date, options.end_date),
+    };
+    lines.insert(0, period);
+
+    let contents = match options.format {
+        Format::Csv => csv::to_bytes(&table, options.csv)?,
+        Format::Xlsx => export::xlsx::to_bytes(&[(options.report.title(), &table)])?,
+        Format::Pdf => pdf::to_bytes(&pdf::Document {
+            title: options.report.title(),
+            lines: &lines,
+            table: &table,
+        }),
+    };
+    export::write(&options.path, &contents)?;
+    Ok(ReportExportSummary {
+        rows: table.rows.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_rows_keep_their_order_and_average() {
+        let report: Value = serde_json::from_str(
+            r#"{"0": {"Note": 1}, "1": {"Note": 2}, "10": {"Note": 11}, "2": {"Note": 3},
+                "average note": "4.25"}"#,
+        )
+        .unwrap();
+        let rows = report_rows(report);
+        let notes: Vec<&Value> = rows.iter().map(|row| &row["Note"]).collect();
+        assert_eq!(notes[..4], [&json!(1), &json!(2), &json!(3), &json!(11)]);
+
+        let (table, lines) = table(Report::Report4, &rows);
+        assert_eq!(table.rows.len(), 4);
+        assert_eq!(lines, ["Average rating: 4.25"]);
+    }
+
+    #[test]
+    fn report_rows_take_lists_as_they_are() {
+        let rows = vec![json!({ "Note": 2 }), json!({ "Note": 1 })];
+        assert_eq!(report_rows(Value::Array(rows.clone())), rows);
+        assert!(report_rows(Value::Null).is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+use tauri::State;
+
+use super::{DocKind, SearchHit};
+use crate::accounts::Accounts;
+
+const DEFAULT_LIMIT: usize = 50;
+
+/// Ranked hits for `query` across everything the active account synced so
+/// far. Works offline.
+#[tauri::command]
+pub fn search(
+    accounts: State<'_, Accounts>,
+    query: String,
+    kinds: Option<Vec<DocKind>>,
+    ticket_id: Option<u32>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    accounts.active()?.index.search(
+        &query,
+        &kinds.unwrap_or_default(),
+        ticket_id,
+        limit.unwrap_or(DEFAULT_LIMIT),
+    )
+}
@@ -0,0 +1,534 @@
+pub mod commands;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery, TermSetQuery};
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TantivyDocument, TextFieldIndexing, TextOptions, Value as _,
+    INDEXED, STORED, STRING,
+};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    TextAnalyzer,
+};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, Term};
+
+use crate::api::models::{Ticket, TicketHistory, TicketMessage};
+
+pub const INDEX_DIR: &str = "search-index";
+const WRITER_HEAP_BYTES: usize = 20_000_000;
+const SNIPPET_CHARS: usize = 160;
+
+/// Every text is indexed twice, so "Drucker" finds "Druckers" and
+/// "printers" finds "printer" no matter which language a ticket is in.
+const LANGUAGES: [(&str, Language); 2] = [("de", Language::German), ("en", Language::English)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocKind {
+    Ticket,
+    History,
+    Message,
+    Wiki,
+}
+
+impl DocKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DocKind::Ticket => "ticket",
+            DocKind::History => "history",
+            DocKind::Message => "message",
+            DocKind::Wiki => "wiki",
+        }
+    }
+}
+
+/// One searchable thing, before it is turned into a tantivy document.
+#[derive(Hash)]
+struct Entry {
+    kind: &'static str,
+    id: u64,
+    ticket_id: Option<u32>,
+    title: String,
+    body: String,
+}
+
+impl Entry {
+    fn key(&self) -> String {
+        format!("{}:{}", self.kind, self.id)
+    }
+
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: String,
+    /// Id of the ticket, history entry, message or wiki article.
+    pub id: u64,
+    pub ticket_id: Option<u32>,
+    pub title: String,
+    /// HTML escaped, matches wrapped in `<b>`.
+    pub snippet: String,
+    pub score: f32,
+}
+
+struct Fields {
+    key: Field,
+    kind: Field,
+    id: Field,
+    ticket_id: Field,
+    title: Field,
+    body: Field,
+    /// `(title, body)` per entry of `LANGUAGES`.
+    stemmed: Vec<(Field, Field)>,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Fields) {
+        let mut builder = Schema::builder();
+        let key = builder.add_text_field("key", STRING);
+        let kind = builder.add_text_field("kind", STRING | STORED);
+        let id = builder.add_u64_field("id", STORED);
+        let ticket_id = builder.add_u64_field("ticket_id", INDEXED | STORED);
+        let title = builder.add_text_field("title", STORED);
+        let body = builder.add_text_field("body", STORED);
+
+        let stemmed = LANGUAGES
+            .iter()
+            .map(|(code, _)| {
+                let options = TextOptions::default().set_indexing_options(
+                    TextFieldIndexing::default()
+                        .set_tokenizer(&tokenizer_name(code))
+                        .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+                );
+                (
+                    builder.add_text_field(&format!("title_{code}"), options.clone()),
+                    builder.add_text_field(&format!("body_{code}"), options),
+                )
+            })
+            .collect();
+
+        let fields = Fields {
+            key,
+            kind,
+            id,
+            ticket_id,
+            title,
+            body,
+            stemmed,
+        };
+        (builder.build(), fields)
+    }
+
+    fn document(&self, entry: &Entry) -> TantivyDocument {
+        let mut document = doc!(
+            self.key => entry.key(),
+            self.kind => entry.kind,
+            self.id => entry.id,
+            self.title => entry.title.as_str(),
+            self.body => entry.body.as_str(),
+        );
+        if let Some(ticket_id) = entry.ticket_id {
+            document.add_u64(self.ticket_id, ticket_id.into());
+        }
+        for (title, body) in &self.stemmed {
+            document.add_text(*title, &entry.title);
+            document.add_text(*body, &entry.body);
+        }
+        document
+    }
+}
+
+fn tokenizer_name(code: &str) -> String {
+    format!("stem_{code}")
+}
+
+fn analyzer(language: Language) -> TextAnalyzer {
+    TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .filter(Stemmer::new(language))
+        // After stemming, so "Grüße" and "Grusse" still meet
+        .filter(AsciiFoldingFilter)
+        .build()
+}
+
+/// Drops markup from wiki articles and replies written in the rich editor.
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn open_index(path: &Path, schema: Schema) -> tantivy::Result<Index> {
+    fs::create_dir_all(path)?;
+    match Index::open_or_create(MmapDirectory::open(path)?, schema.clone()) {
+        Err(tantivy::TantivyError::SchemaError(_)) => {
+            // Written by an older version, it is rebuilt from the next syncs
+            fs::remove_dir_all(path)?;
+            fs::create_dir_all(path)?;
+            Index::create_in_dir(path, schema)
+        }
+        result => result,
+    }
+}
+
+/// Full-text index over one account's tickets, history entries, messages and
+/// wiki articles. The API commands and the poller feed it as data comes
+/// in, the `search` command queries it.
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+    /// What was last indexed per key, so unchanged polls do not commit.
+    fingerprints: Mutex<HashMap<String, u64>>,
+}
+
+impl SearchIndex {
+    /// Opens the index kept in `dir`, an account's data directory.
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        Self::open_in(&dir.join(INDEX_DIR))
+    }
+
+    fn open_in(path: &Path) -> Result<Self, String> {
+        let (schema, fields) = Fields::schema();
+
+        let index = open_index(path, schema).map_err(|e| e.to_string())?;
+        for (code, language) in LANGUAGES {
+            index
+                .tokenizers()
+                .register(&tokenizer_name(code), analyzer(language));
+        }
+
+        let writer = index
+            .writer_with_num_threads(1, WRITER_HEAP_BYTES)
+            .map_err(|e| e.to_string())?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+            fingerprints: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Applies one batch of changes and commits. Like the offline cache,
+    /// failures are only logged so indexing never fails a request.
+    fn write(&self, what: &str, update: impl FnOnce(&IndexWriter) -> tantivy::Result<bool>) {
+        let mut writer = self.writer.lock().unwrap();
+        let result = update(&writer).and_then(|changed| {
+            if changed {
+                writer.commit()?;
+                self.reader.reload()?;
+            }
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to index {}: {}", what, e);
+            let _ = writer.rollback();
+            // Whatever we believed was indexed may not be anymore
+            self.fingerprints.lock().unwrap().clear();
+        }
+    }
+
+    /// Adds or replaces entries, skipping those that did not change.
+    fn upsert(&self, what: &str, entries: Vec<Entry>) {
+        self.write(what, |writer| {
+            let mut fingerprints = self.fingerprints.lock().unwrap();
+            let mut changed = false;
+            for entry in entries {
+                let key = entry.key();
+                let fingerprint = entry.fingerprint();
+                if fingerprints.get(&key) == Some(&fingerprint) {
+                    continue;
+                }
+                writer.delete_term(Term::from_field_text(self.fields.key, &key));
+                writer.add_document(self.fields.document(&entry))?;
+                fingerprints.insert(key, fingerprint);
+                changed = true;
+            }
+            Ok(changed)
+        });
+    }
+
+    /// Replaces all entries of `kind`, or only those of one ticket. Used for
+    /// lists the server returns as a whole, so deleted items disappear too.
+    fn replace(&self, what: &str, kind: DocKind, ticket_id: Option<u32>, entries: Vec<Entry>) {
+        let unchanged = {
+            let fingerprints = self.fingerprints.lock().unwrap();
+            let scope = scope_key(kind, ticket_id);
+            fingerprints.get(&scope) == Some(&list_fingerprint(&entries))
+        };
+        if unchanged {
+            return;
+        }
+
+        self.write(what, |writer| {
+            writer.delete_query(self.scope_query(kind, ticket_id))?;
+            for entry in &entries {
+                writer.add_document(self.fields.document(entry))?;
+            }
+            self.fingerprints
+                .lock()
+                .unwrap()
+                .insert(scope_key(kind, ticket_id), list_fingerprint(&entries));
+            Ok(true)
+        });
+    }
+
+    fn scope_query(&self, kind: DocKind, ticket_id: Option<u32>) -> Box<dyn Query> {
+        let kind = TermQuery::new(
+            Term::from_field_text(self.fields.kind, kind.as_str()),
+            IndexRecordOption::Basic,
+        );
+        match ticket_id {
+            Some(ticket_id) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, Box::new(kind) as Box<dyn Query>),
+                (Occur::Must, Box::new(self.ticket_query(ticket_id))),
+            ])),
+            None => Box::new(kind),
+        }
+    }
+
+    fn ticket_query(&self, ticket_id: u32) -> TermQuery {
+        TermQuery::new(
+            Term::from_field_u64(self.fields.ticket_id, ticket_id.into()),
+            IndexRecordOption::Basic,
+        )
+    }
+
+    pub fn index_tickets<'a>(&self, tickets: impl IntoIterator<Item = &'a Ticket>) {
+        let entries = tickets
+            .into_iter()
+            .map(|ticket| Entry {
+                kind: DocKind::Ticket.as_str(),
+                id: ticket.id.into(),
+                ticket_id: Some(ticket.id),
+                title: [ticket.subject.as_deref(), ticket.summary.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" - "),
+                body: plain_text(&ticket.description),
+            })
+            .collect();
+        self.upsert("tickets", entries);
+    }
+
+    pub fn index_histories(&self, ticket_id: u32, histories: &[TicketHistory]) {
+        let entries = histories
+            .iter()
+            .map(|history| Entry {
+                kind: DocKind::History.as_str(),
+                id: history.id.into(),
+                ticket_id: Some(ticket_id),
+                title: history.status_name.clone().unwrap_or_default(),
+                body: plain_text(history.technician_reply.as_deref().unwrap_or_default()),
+            })
+            .collect();
+        self.replace("history", DocKind::History, Some(ticket_id), entries);
+    }
+
+    pub fn index_messages(&self, ticket_id: u32, messages: &[TicketMessage]) {
+        let entries = messages
+            .iter()
+            .map(|message| Entry {
+                kind: DocKind::Message.as_str(),
+                id: message.id.into(),
+                ticket_id: Some(ticket_id),
+                title: message
+                    .user
+                    .as_ref()
+                    .map(|user| user.name.clone())
+                    .unwrap_or_default(),
+                body: message.message.clone(),
+            })
+            .collect();
+        self.replace("messages", DocKind::Message, Some(ticket_id), entries);
+    }
+
+    /// Takes `/getWikiData` as is: categories holding folders holding articles.
+    pub fn index_wiki(&self, categories: &[Value]) {
+        let entries = categories
+            .iter()
+            .flat_map(|category| as_list(&category["folder"]))
+            .flat_map(|folder| as_list(&folder["article"]))
+            .filter_map(|article| {
+                Some(Entry {
+                    kind: DocKind::Wiki.as_str(),
+                    id: article["id"].as_u64()?,
+                    ticket_id: None,
+                    title: article["name"].as_str().unwrap_or_default().to_string(),
+                    body: plain_text(article["article"].as_str().unwrap_or_default()),
+                })
+            })
+            .collect();
+        self.replace("wiki", DocKind::Wiki, None, entries);
+    }
+
+    /// Drops everything, e.g. on logout.
+    pub fn clear(&self) -> Result<(), String> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.delete_all_documents().map_err(|e| e.to_string())?;
+        writer.commit().map_err(|e| e.to_string())?;
+        self.reader.reload().map_err(|e| e.to_string())?;
+        self.fingerprints.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// Best matches first. `kinds` and `ticket_id` narrow the search down,
+    /// e.g. to the history of the ticket that is open.
+    pub fn search(
+        &self,
+        text: &str,
+        kinds: &[DocKind],
+        ticket_id: Option<u32>,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>, String> {
+        let searchable = self
+            .fields
+            .stemmed
+            .iter()
+            .flat_map(|(title, body)| [*title, *body])
+            .collect();
+        let mut parser = QueryParser::for_index(&self.index, searchable);
+        parser.set_conjunction_by_default();
+        for (title, _) in &self.fields.stemmed {
+            parser.set_field_boost(*title, 2.0);
+        }
+        // Half typed queries like `"drucker` should still find something
+        let (text_query, _errors) = parser.parse_query_lenient(text);
+
+        let mut clauses = vec![(Occur::Must, text_query.box_clone())];
+        if !kinds.is_empty() {
+            let terms = kinds
+                .iter()
+                .map(|kind| Term::from_field_text(self.fields.kind, kind.as_str()));
+            clauses.push((Occur::Must, Box::new(TermSetQuery::new(terms))));
+        }
+        if let Some(ticket_id) = ticket_id {
+            clauses.push((Occur::Must, Box::new(self.ticket_query(ticket_id))));
+        }
+        let query = BooleanQuery::new(clauses);
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(|e| e.to_string())?;
+
+        let mut snippets = self
+            .fields
+            .stemmed
+            .iter()
+            .map(|(_, body)| SnippetGenerator::create(&searcher, &*text_query, *body))
+            .collect::<tantivy::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        for generator in &mut snippets {
+            generator.set_max_num_chars(SNIPPET_CHARS);
+        }
+
+        top_docs
+            .into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+                let text = |field| {
+                    document
+                        .get_first(field)
+                        .and_then(|value| value.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let body = text(self.fields.body);
+
+                // Whichever language's stems matched more words makes the snippet
+                let snippet = snippets
+                    .iter()
+                    .map(|generator| generator.snippet(&body))
+                    .max_by_key(|snippet| snippet.highlighted().len())
+                    .filter(|snippet| !snippet.is_empty())
+                    .map(|snippet| snippet.to_html())
+                    .unwrap_or_else(|| {
+                        html_escape(&body.chars().take(SNIPPET_CHARS).collect::<String>())
+                    });
+
+                Ok(SearchHit {
+                    kind: text(self.fields.kind),
+                    id: document
+                        .get_first(self.fields.id)
+                        .and_then(|value| value.as_u64())
+                        .unwrap_or_default(),
+                    ticket_id: document
+                        .get_first(self.fields.ticket_id)
+                        .and_then(|value| value.as_u64())
+                        .and_then(|id| u32::try_from(id).ok()),
+                    title: text(self.fields.title),
+                    snippet,
+                    score,
+                })
+            })
+            .collect()
+    }
+}
+
+fn as_list(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+fn scope_key(kind: DocKind, ticket_id: Option<u32>) -> String {
+    match ticket_id {
+        Some(ticket_id) => format!("{}@{}", kind.as_str(), ticket_id),
+        None => kind.as_str().to_string(),
+    }
+}
+
+fn list_fingerprint(entries: &[Entry]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    entries.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
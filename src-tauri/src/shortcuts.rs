@@ -0,0 +1,257 @@
+//! System-wide shortcuts, so the timer can be paused or a ticket opened from
+//! inside a customer's remote session without switching to the app.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_global_shortcut::{GlobalShortcut, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::accounts::Accounts;
+use crate::{notifications, timer};
+
+const SETTINGS_FILE: &str = "shortcuts.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    PauseResume,
+    NewTicket,
+    OpenTicket,
+}
+
+impl ShortcutAction {
+    const ALL: [ShortcutAction; 3] = [
+        ShortcutAction::PauseResume,
+        ShortcutAction::NewTicket,
+        ShortcutAction::OpenTicket,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::PauseResume => "Pause/resume timer",
+            ShortcutAction::NewTicket => "New ticket",
+            ShortcutAction::OpenTicket => "Open ticket",
+        }
+    }
+}
+
+/// Accelerators like `CommandOrControl+Alt+Shift+P`; an empty one is off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShortcutSettings {
+    pub pause_resume: String,
+    pub new_ticket: String,
+    pub open_ticket: String,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            pause_resume: "CommandOrControl+Alt+Shift+P".to_string(),
+            new_ticket: "CommandOrControl+Alt+Shift+N".to_string(),
+            open_ticket: "CommandOrControl+Alt+Shift+O".to_string(),
+        }
+    }
+}
+
+impl ShortcutSettings {
+    fn accelerator(&self, action: ShortcutAction) -> &str {
+        match action {
+            ShortcutAction::PauseResume => &self.pause_resume,
+            ShortcutAction::NewTicket => &self.new_ticket,
+            ShortcutAction::OpenTicket => &self.open_ticket,
+        }
+    }
+}
+
+/// How registering a shortcut went, for Settings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    pub registered: bool,
+    /// Why it isn't registered, unless it is turned off.
+    pub error: Option<String>,
+}
+
+pub struct Shortcuts {
+    path: PathBuf,
+    settings: Mutex<ShortcutSettings>,
+    /// Ours among the registered shortcuts, by id.
+    registered: Mutex<HashMap<u32, (Shortcut, ShortcutAction)>>,
+    status: Mutex<Vec<ShortcutStatus>>,
+}
+
+impl Shortcuts {
+    pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+        let path = config_dir.join(SETTINGS_FILE);
+
+        let settings = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            settings: Mutex::new(settings),
+            registered: Mutex::new(HashMap::new()),
+            status: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Registers the saved shortcuts, at startup.
+    pub fn register_all<R: Runtime>(&self, app: &AppHandle<R>) {
+        let settings = self.settings.lock().unwrap().clone();
+        self.register(app, &settings);
+    }
+
+    pub fn status(&self) -> Vec<ShortcutStatus> {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Saves the shortcuts and registers them instead of the previous ones.
+    /// One that can't be registered is kept, with the reason in its status.
+    pub fn configure<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        settings: ShortcutSettings,
+    ) -> Result<Vec<ShortcutStatus>, String> {
+        let data = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        fs::write(&self.path, data).map_err(|e| e.to_string())?;
+        self.register(app, &settings);
+        *self.settings.lock().unwrap() = settings;
+        Ok(self.status())
+    }
+
+    fn register<R: Runtime>(&self, app: &AppHandle<R>, settings: &ShortcutSettings) {
+        // Missing where there is no display server to register them with
+        let global = app.try_state::<GlobalShortcut<R>>();
+        let mut registered = self.registered.lock().unwrap();
+        if let Some(global) = &global {
+            for (shortcut, _) in registered.values() {
+                let _ = global.unregister(*shortcut);
+            }
+        }
+        registered.clear();
+
+        let mut status = Vec::new();
+        let mut parsed: Vec<(Shortcut, ShortcutAction)> = Vec::new();
+        for action in ShortcutAction::ALL {
+            let accelerator = settings.accelerator(action).trim();
+            let mut entry = ShortcutStatus {
+                action,
+                accelerator: accelerator.to_string(),
+                registered: false,
+                error: None,
+            };
+            if accelerator.is_empty() {
+                status.push(entry);
+                continue;
+            }
+
+            let result = accelerator
+                .parse::<Shortcut>()
+                .map_err(|e| format!("Not a valid shortcut: {}", e))
+                .and_then(
+                    |shortcut| match parsed.iter().find(|(other, _)| *other == shortcut) {
+                        Some((_, other)) => Err(format!("Already used for {}", other.label())),
+                        None => Ok(shortcut),
+                    },
+                );
+            let result = result.and_then(|shortcut| {
+                parsed.push((shortcut, action));
+                let global = global
+                    .as_ref()
+                    .ok_or("Global shortcuts are not available on this system")?;
+                global.register(shortcut).map_err(|e| {
+                    format!(
+                        "Could not be registered, another application may use it: {}",
+                        e
+                    )
+                })?;
+                Ok(shortcut)
+            });
+            match result {
+                Ok(shortcut) => {
+                    registered.insert(shortcut.id(), (shortcut, action));
+                    entry.registered = true;
+                }
+                Err(e) => entry.error = Some(e),
+            }
+            status.push(entry);
+        }
+        *self.status.lock().unwrap() = status;
+    }
+
+    fn action(&self, shortcut: &Shortcut) -> Option<ShortcutAction> {
+        let registered = self.registered.lock().unwrap();
+        registered.get(&shortcut.id()).map(|(_, action)| *action)
+    }
+}
+
+/// The global shortcut plugin's handler, for all of our shortcuts.
+pub fn handle<R: Runtime>(app: &AppHandle<R>, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let Some(action) = app.state::<Shortcuts>().action(shortcut) else {
+        return;
+    };
+    let result = match action {
+        ShortcutAction::PauseResume => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = pause_or_resume(&app).await {
+                    notifications::notify(&app, "Timer unchanged", &e);
+                }
+            });
+            Ok(())
+        }
+        ShortcutAction::NewTicket => crate::show_new_ticket_window(app),
+        ShortcutAction::OpenTicket => crate::show_tool_window(
+            app,
+            "quick-open",
+            "/?quickOpen=true",
+            "Open Ticket",
+            (520.0, 380.0),
+        ),
+    };
+    if let Err(e) = result {
+        log::error!("Failed to run the {} shortcut: {}", action.label(), e);
+    }
+}
+
+/// Confirms with a notification, since the app is likely not in view.
+async fn pause_or_resume<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let account = app.state::<Accounts>().active()?;
+    let timer = timer::active(&account).ok_or("No ticket timer is running or paused")?;
+    timer::set_running(app, &account, &timer, !timer.running).await?;
+    let title = if timer.running {
+        "Timer paused"
+    } else {
+        "Timer resumed"
+    };
+    notifications::notify(app, title, &format!("Ticket #{}", timer.ticket_id));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_shortcuts(shortcuts: State<'_, Shortcuts>) -> Vec<ShortcutStatus> {
+    shortcuts.status()
+}
+
+#[tauri::command]
+pub fn set_shortcuts<R: Runtime>(
+    app: AppHandle<R>,
+    shortcuts: State<'_, Shortcuts>,
+    settings: ShortcutSettings,
+) -> Result<Vec<ShortcutStatus>, String> {
+    shortcuts.configure(&app, settings)
+}
@@ -0,0 +1,276 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_opener::OpenerExt;
+
+const ROOT_DIR: &str = "open-files";
+const MANIFEST_FILE: &str = "manifest.json";
+const PARTIAL_DIR: &str = "partial";
+const SESSION_PREFIX: &str = "session-";
+/// Partial downloads nobody resumed for this long are dropped.
+const PARTIAL_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    path: PathBuf,
+    session: String,
+    /// Milliseconds since the epoch.
+    created_at: u128,
+}
+
+/// Every file handed to an external viewer, kept on disk so a crashed
+/// session's files are still found and removed on the next start.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    files: Vec<ManifestEntry>,
+}
+
+/// Owns the files attachments are opened from: one directory per app run
+/// under the app cache dir, plus the partial downloads that can be resumed.
+pub struct TempFiles {
+    root: PathBuf,
+    session: String,
+    manifest: Mutex<Manifest>,
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+impl TempFiles {
+    pub fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let root = app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| e.to_string())?
+            .join(ROOT_DIR);
+        let session = format!("{}{}-{}", SESSION_PREFIX, now_millis(), std::process::id());
+        fs::create_dir_all(root.join(&session)).map_err(|e| e.to_string())?;
+        fs::create_dir_all(root.join(PARTIAL_DIR)).map_err(|e| e.to_string())?;
+
+        let manifest = fs::read(root.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+
+        let files = Self {
+            root,
+            session,
+            manifest: Mutex::new(manifest),
+        };
+        // Whatever earlier runs left behind, including ones that crashed
+        files.cleanup(false);
+        Ok(files)
+    }
+
+    pub fn session_dir(&self) -> PathBuf {
+        self.root.join(&self.session)
+    }
+
+    pub fn partial_dir(&self) -> PathBuf {
+        self.root.join(PARTIAL_DIR)
+    }
+
+    fn save(&self, manifest: &Manifest) {
+        let path = self.root.join(MANIFEST_FILE);
+        let tmp = path.with_extension("json.tmp");
+        // Write and rename, so a crash never leaves half a manifest
+        let result = serde_json::to_vec_pretty(manifest)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&tmp, json).map_err(|e| e.to_string()))
+            .and_then(|()| fs::rename(&tmp, &path).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            log::error!("Failed to write temp file manifest: {}", e);
+        }
+    }
+
+    /// Records a file of this session before anything opens it.
+    pub fn register(&self, path: &Path) -> Result<PathBuf, String> {
+        let path = path.canonicalize().map_err(|e| e.to_string())?;
+        let mut manifest = self.manifest.lock().unwrap();
+        if !manifest.files.iter().any(|entry| entry.path == path) {
+            manifest.files.push(ManifestEntry {
+                path: path.clone(),
+                session: self.session.clone(),
+                created_at: now_millis(),
+            });
+            self.save(&manifest);
+        }
+        Ok(path)
+    }
+
+    /// Whether `path` is one of ours, so the frontend cannot have us open
+    /// arbitrary files.
+    fn owns(&self, path: &Path) -> bool {
+        let root = self
+            .root
+            .canonicalize()
+            .unwrap_or_else(|_| self.root.clone());
+        path.canonicalize()
+            .is_ok_and(|path| path.starts_with(&root) && path.is_file())
+    }
+
+    /// Deletes the files of earlier sessions, and of this one as well with
+    /// `include_current`, unless a viewer still has them open. Those stay in
+    /// the manifest and are tried again next time.
+    pub fn cleanup(&self, include_current: bool) {
+        let mut manifest = self.manifest.lock().unwrap();
+        manifest.files.retain(|entry| {
+            if entry.session == self.session && !include_current {
+                return true;
+            }
+            !remove_unless_open(&entry.path)
+        });
+
+        // Files a crash kept out of the manifest, and the emptied directories
+        let kept: Vec<&Path> = manifest
+            .files
+            .iter()
+            .map(|entry| entry.path.as_path())
+            .collect();
+        if let Ok(dirs) = fs::read_dir(&self.root) {
+            for dir in dirs.flatten() {
+                let name = dir.file_name();
+                let Some(name) = name.to_str() else { continue };
+                if !name.starts_with(SESSION_PREFIX) || (name == self.session && !include_current) {
+                    continue;
+                }
+                sweep(&dir.path(), &kept);
+            }
+        }
+
+        sweep_partial(&self.partial_dir());
+        self.save(&manifest);
+    }
+}
+
+/// True once `path` is gone.
+fn remove_unless_open(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return true;
+    };
+    if in_use(&path) {
+        return false;
+    }
+    // Windows refuses while a viewer has it open, which is what we want
+    fs::remove_file(&path).is_ok()
+}
+
+fn sweep(dir: &Path, kept: &[&Path]) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                sweep(&path, kept);
+            } else if !kept
+                .iter()
+                .any(|kept| path.canonicalize().is_ok_and(|p| p == *kept))
+            {
+                remove_unless_open(&path);
+            }
+        }
+    }
+    // Only succeeds once it is empty
+    let _ = fs::remove_dir(dir);
+}
+
+fn sweep_partial(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            sweep_partial(&path);
+            let _ = fs::remove_dir(&path);
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > PARTIAL_MAX_AGE);
+        if stale {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Whether another program still has `path` open, e.g. the PDF viewer.
+#[cfg(target_os = "linux")]
+fn in_use(path: &Path) -> bool {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return false;
+    };
+    processes
+        .flatten()
+        .filter(|process| {
+            process
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .filter_map(|process| fs::read_dir(process.path().join("fd")).ok())
+        .flat_map(|fds| fds.flatten())
+        .any(|fd| fs::read_link(fd.path()).is_ok_and(|target| target == path))
+}
+
+#[cfg(target_os = "macos")]
+fn in_use(path: &Path) -> bool {
+    std::process::Command::new("lsof")
+        .arg("-t")
+        .arg("--")
+        .arg(path)
+        .output()
+        .is_ok_and(|output| !output.stdout.is_empty())
+}
+
+#[cfg(windows)]
+fn in_use(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // No sharing at all fails while anyone else has the file open
+    fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .open(path)
+        .is_err()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn in_use(_path: &Path) -> bool {
+    false
+}
+
+/// Opens a downloaded attachment with the default application, or shows it
+/// in the file manager if there is none.
+#[tauri::command]
+pub fn open_attachment<R: Runtime>(
+    app: AppHandle<R>,
+    temp_files: State<'_, TempFiles>,
+    path: PathBuf,
+) -> Result<(), String> {
+    if !temp_files.owns(&path) {
+        return Err(format!("Not a downloaded attachment: {}", path.display()));
+    }
+    let path = temp_files.register(&path)?;
+
+    let opener = app.opener();
+    match opener.open_path(path.to_string_lossy(), None::<&str>) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            log::error!("Failed to open {}: {}", path.display(), e);
+            opener.reveal_item_in_dir(&path).map_err(|e| e.to_string())
+        }
+    }
+}
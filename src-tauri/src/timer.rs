@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::{HistoryEntry, PlayerStatus};
+use crate::cache::now_millis;
+
+// Ticketbase's player codes
+pub const PLAY: u8 = 1;
+pub const PAUSE: u8 = 2;
+pub const RESUME: u8 = 3;
+pub const STOP: u8 = 4;
+
+const TICK: Duration = Duration::from_secs(1);
+/// Running timers are checked against the server this often, in ticks.
+const RECONCILE_TICKS: u32 = 60;
+
+pub fn is_running(play_status: u8) -> bool {
+    matches!(play_status, PLAY | RESUME)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerAction {
+    Play,
+    Pause,
+    Resume,
+    Stop,
+}
+
+impl TimerAction {
+    pub fn play_status(self) -> u8 {
+        match self {
+            TimerAction::Play => PLAY,
+            TimerAction::Pause => PAUSE,
+            TimerAction::Resume => RESUME,
+            TimerAction::Stop => STOP,
+        }
+    }
+}
+
+/// A timer that is running or paused, as the windows show it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerState {
+    pub ticket_id: u32,
+    pub play_status: u8,
+    pub running: bool,
+    /// When this run was started, in milliseconds since the epoch.
+    pub started_at: i64,
+    pub elapsed_ms: i64,
+}
+
+/// Sent every second while a timer runs, and right after any change, to
+/// the main window and all ticket windows alike.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerTick {
+    pub account_id: String,
+    pub timers: Vec<TimerState>,
+}
+
+/// What `/getPlayerStatus` reports for an open timer.
+#[derive(Debug, Clone)]
+pub struct ServerTimer {
+    pub play_status: u8,
+    pub elapsed_ms: i64,
+    /// How far ours may differ before the server's total is taken over.
+    pub tolerance_ms: i64,
+}
+
+impl ServerTimer {
+    /// `None` when the server has no running or paused timer.
+    pub fn from_status(status: Option<&PlayerStatus>) -> Option<Self> {
+        let status = status.filter(|status| matches!(status.play_status, PLAY | PAUSE | RESUME))?;
+        // `total_time` only has minutes, `total_time_raw` is "HH:MM:SS"
+        let raw_seconds = status.total_time_raw.as_deref().and_then(|raw| {
+            let mut parts = raw.split(':').map(|part| part.trim().parse::<i64>().ok());
+            match (parts.next()?, parts.next()?, parts.next()?, parts.next()) {
+                (Some(hours), Some(minutes), Some(seconds), None) => {
+                    Some(hours * 3600 + minutes * 60 + seconds)
+                }
+                _ => None,
+            }
+        });
+        Some(match raw_seconds {
+            Some(seconds) => Self {
+                play_status: status.play_status,
+                elapsed_ms: seconds * 1000,
+                tolerance_ms: 5_000,
+            },
+            None => Self {
+                play_status: status.play_status,
+                elapsed_ms: status.total_time * 60_000,
+                tolerance_ms: 60_000,
+            },
+        })
+    }
+}
+
+/// Sends the account's open timers to all windows, if it is the one they show.
+pub fn emit_tick<R: Runtime>(app: &AppHandle<R>, account: &Account) {
+    if !app.state::<Accounts>().is_active(&account.id) {
+        return;
+    }
+    match account.cache.open_timers(account.user.id) {
+        Ok(timers) => {
+            let _ = app.emit(
+                "timer://tick",
+                TimerTick {
+                    account_id: account.id.clone(),
+                    timers,
+                },
+            );
+        }
+        Err(e) => log::error!("Failed to read timers: {}", e),
+    }
+}
+
+/// Records a change the server accepted for the signed in user.
+pub fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    ticket_id: u32,
+    action: TimerAction,
+) {
+    record_at(app, account, ticket_id, action, now_millis());
+}
+
+/// Records a change that took effect at `at`, in milliseconds since the epoch.
+pub fn record_at<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    ticket_id: u32,
+    action: TimerAction,
+    at: i64,
+) {
+    if let Err(e) = account
+        .cache
+        .record_timer(account.user.id, ticket_id, action, at)
+    {
+        log::error!("Failed to record timer: {}", e);
+    }
+    emit_tick(app, account);
+}
+
+/// The running timer, or else the one paused last.
+pub fn active(account: &Account) -> Option<TimerState> {
+    let timers = account
+        .cache
+        .open_timers(account.user.id)
+        .unwrap_or_default();
+    let running = timers.iter().find(|timer| timer.running).cloned();
+    running.or_else(|| timers.into_iter().max_by_key(|timer| timer.started_at))
+}
+
+/// Pauses or resumes one of the signed in user's timers on the server.
+pub async fn set_running<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    timer: &TimerState,
+    running: bool,
+) -> Result<(), String> {
+    let user_id = account.user.id;
+    let (response, action) = if running {
+        (
+            account.api.resume(timer.ticket_id, user_id, PAUSE).await,
+            TimerAction::Resume,
+        )
+    } else {
+        (
+            account
+                .api
+                .pause(timer.ticket_id, user_id, timer.play_status)
+                .await,
+            TimerAction::Pause,
+        )
+    };
+    let response = response.map_err(|e| e.to_string())?;
+    if response.status != "success" {
+        return Err(response.message.unwrap_or(response.status));
+    }
+    record(app, account, timer.ticket_id, action);
+    Ok(())
+}
+
+/// Saving a history entry finishes the work on the ticket on the server.
+pub fn history_saved<R: Runtime>(app: &AppHandle<R>, account: &Account, entry: &HistoryEntry) {
+    if entry.user_id == account.user.id {
+        record(app, account, entry.ticket_id, TimerAction::Stop);
+    }
+}
+
+/// Takes over a `/getPlayerStatus` answer for the signed in user.
+pub fn reconcile<R: Runtime>(
+    app: &AppHandle<R>,
+    account: &Account,
+    ticket_id: u32,
+    status: Option<&PlayerStatus>,
+) {
+    let server = ServerTimer::from_status(status);
+    if let Err(e) = account
+        .cache
+        .reconcile_timer(account.user.id, ticket_id, server.as_ref())
+    {
+        log::error!("Failed to reconcile timer: {}", e);
+    }
+    emit_tick(app, account);
+}
+
+/// Ticks while timers run, whether or not a window is open, and now and then
+/// asks the server about every open timer of every account.
+pub fn spawn<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticks = 0;
+        loop {
+            tokio::time::sleep(TICK).await;
+            ticks += 1;
+            let accounts = app.state::<Accounts>();
+
+            if ticks == RECONCILE_TICKS {
+                ticks = 0;
+                for account in accounts.all() {
+                    let timers = account
+                        .cache
+                        .open_timers(account.user.id)
+                        .unwrap_or_default();
+                    for timer in timers {
+                        // Offline the local record just goes on
+                        if let Ok(response) = account
+                            .api
+                            .get_player_status(timer.ticket_id, account.user.id)
+                            .await
+                        {
+                            reconcile(
+                                &app,
+                                &account,
+                                timer.ticket_id,
+                                response.payload.player_status.as_ref(),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Ok(account) = accounts.active() {
+                let running = account
+                    .cache
+                    .open_timers(account.user.id)
+                    .map(|timers| timers.iter().any(|timer| timer.running))
+                    .unwrap_or(false);
+                if running {
+                    emit_tick(&app, &account);
+                }
+            }
+        }
+    });
+}
+
+/// The ticket's timer if it is running or paused.
+#[tauri::command]
+pub fn get_timer(
+    accounts: State<'_, Accounts>,
+    ticket_id: u32,
+) -> Result<Option<TimerState>, String> {
+    let account = accounts.active()?;
+    account.cache.timer(account.user.id, ticket_id)
+}
+
+#[tauri::command]
+pub fn get_timers(accounts: State<'_, Accounts>) -> Result<Vec<TimerState>, String> {
+    match accounts.active() {
+        Ok(account) => account.cache.open_timers(account.user.id),
+        Err(_) => Ok(Vec::new()),
+    }
+}
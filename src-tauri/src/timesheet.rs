@@ -0,0 +1,365 @@
+//! Timesheets from the work histories of a period, summed up per day, ticket
+//! and company, or as one calendar event per stretch of work.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::accounts::{Account, Accounts};
+use crate::api::models::{Ticket, TicketHistory};
+use crate::export::{self, ics, Cell, Table};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Xlsx,
+    Ics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Date,
+    Ticket,
+    Summary,
+    Company,
+    Entries,
+    Minutes,
+    Hours,
+    Notes,
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Date => "Date",
+            Column::Ticket => "Ticket",
+            Column::Summary => "Summary",
+            Column::Company => "Company",
+            Column::Entries => "Entries",
+            Column::Minutes => "Minutes",
+            Column::Hours => "Hours",
+            Column::Notes => "Notes",
+        }
+    }
+}
+
+fn default_columns() -> Vec<Column> {
+    vec![
+        Column::Date,
+        Column::Ticket,
+        Column::Summary,
+        Column::Company,
+        Column::Minutes,
+        Column::Hours,
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+/// Rounds each row, or each event in a calendar, to a multiple of
+/// `minutes`; 0 keeps whole minutes.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Rounding {
+    pub minutes: u32,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    fn minutes(self, seconds: i64) -> i64 {
+        let step = i64::from(self.minutes.max(1)) * 60;
+        let steps = match self.mode {
+            RoundingMode::Nearest => (seconds + step / 2) / step,
+            RoundingMode::Up => (seconds + step - 1) / step,
+            RoundingMode::Down => seconds / step,
+        };
+        steps * step / 60
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetOptions {
+    /// `YYYY-MM-DD`, both inclusive.
+    pub start_date: String,
+    pub end_date: String,
+    pub format: Format,
+    pub path: PathBuf,
+    #[serde(default = "default_columns")]
+    pub columns: Vec<Column>,
+    #[serde(default)]
+    pub rounding: Rounding,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetSummary {
+    /// Rows in a table, events in a calendar.
+    pub rows: usize,
+    pub minutes: i64,
+}
+
+/// A history entry that booked time.
+struct WorkEntry {
+    history_id: u32,
+    ticket_id: u32,
+    start: DateTime<Utc>,
+    seconds: i64,
+    note: Option<String>,
+}
+
+impl WorkEntry {
+    /// Without `service_start`/`service_end` the time is taken as worked in
+    /// one go up to when the entry was written.
+    fn from_history(history: &TicketHistory) -> Option<Self> {
+        let service = match (history.service_start, history.service_end) {
+            (Some(start), Some(end)) if end > start => Some((start, end)),
+            _ => None,
+        };
+        let seconds = history
+            .total_time
+            .filter(|seconds| *seconds > 0)
+            .or(service.map(|(start, end)| end - start))?;
+        let start = match service {
+            Some((start, _)) => DateTime::from_timestamp(start, 0)?,
+            None => parse_timestamp(&history.created_at)? - TimeDelta::seconds(seconds),
+        };
+        Some(Self {
+            history_id: history.id,
+            ticket_id: history.ticket_id,
+            start,
+            seconds,
+            note: history
+                .technician_reply
+                .clone()
+                .filter(|reply| !reply.trim().is_empty()),
+        })
+    }
+
+    fn day(&self) -> NaiveDate {
+        self.start.with_timezone(&Local).date_naive()
+    }
+}
+
+/// Laravel's `2026-10-13 11:00:00` in UTC, or RFC 3339 from its JSON casts.
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").map(|time| time.and_utc())
+        })
+        .ok()
+}
+
+/// What the rows show of a ticket.
+#[derive(Default)]
+struct TicketInfo {
+    summary: String,
+    company: String,
+}
+
+impl From<Ticket> for TicketInfo {
+    fn from(ticket: Ticket) -> Self {
+        Self {
+            summary: ticket.summary.or(ticket.subject).unwrap_or_default(),
+            company: ticket.company.name,
+        }
+    }
+}
+
+/// From the cache where possible, the server otherwise.
+async fn ticket_info(account: &Account, ticket_id: u32) -> TicketInfo {
+    if let Ok(Some(ticket)) = account.cache.ticket(ticket_id) {
+        return ticket.into();
+    }
+    match account.api.get_ticket_by_id(ticket_id).await {
+        Ok(response) => match response.payload.tickets.map(Ticket::from) {
+            Some(ticket) => {
+                account.cache.store_ticket(&ticket);
+                account.index.index_tickets([&ticket]);
+                ticket.into()
+            }
+            None => TicketInfo::default(),
+        },
+        Err(_) => TicketInfo::default(),
+    }
+}
+
+struct Row<'a> {
+    day: NaiveDate,
+    ticket_id: u32,
+    ticket: &'a TicketInfo,
+    entries: usize,
+    seconds: i64,
+    notes: Vec<&'a str>,
+}
+
+fn table(
+    entries: &[WorkEntry],
+    tickets: &HashMap<u32, TicketInfo>,
+    options: &TimesheetOptions,
+) -> (Table, i64) {
+    let unknown = TicketInfo::default();
+    let mut rows: BTreeMap<(NaiveDate, &str, u32), Row> = BTreeMap::new();
+    for entry in entries {
+        let ticket = tickets.get(&entry.ticket_id).unwrap_or(&unknown);
+        let row = rows
+            .entry((entry.day(), ticket.company.as_str(), entry.ticket_id))
+            .or_insert_with(|| Row {
+                day: entry.day(),
+                ticket_id: entry.ticket_id,
+                ticket,
+                entries: 0,
+                seconds: 0,
+                notes: Vec::new(),
+            });
+        row.entries += 1;
+        row.seconds += entry.seconds;
+        row.notes.extend(entry.note.as_deref());
+    }
+
+    let mut total = 0;
+    let rows = rows
+        .into_values()
+        .map(|row| {
+            let minutes = options.rounding.minutes(row.seconds);
+            total += minutes;
+            options
+                .columns
+                .iter()
+                .map(|column| match column {
+                    Column::Date => Cell::from(row.day),
+                    Column::Ticket => Cell::from(row.ticket_id),
+                    Column::Summary => Cell::from(row.ticket.summary.as_str()),
+                    Column::Company => Cell::from(row.ticket.company.as_str()),
+                    Column::Entries => Cell::Number(row.entries as f64),
+                    Column::Minutes => Cell::Number(minutes as f64),
+                    Column::Hours => Cell::Number((minutes as f64 / 60.0 * 100.0).round() / 100.0),
+                    Column::Notes => Cell::from(row.notes.join("\n")),
+                })
+                .collect()
+        })
+        .collect();
+    let headers = options
+        .columns
+        .iter()
+        .map(|column| column.header().to_string())
+        .collect();
+    (Table { headers, rows }, total)
+}
+
+fn events(
+    account: &Account,
+    entries: &[WorkEntry],
+    tickets: &HashMap<u32, TicketInfo>,
+    rounding: Rounding,
+) -> Vec<ics::Event> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let minutes = rounding.minutes(entry.seconds);
+            if minutes == 0 {
+                return None;
+            }
+            let ticket = tickets.get(&entry.ticket_id);
+            let mut summary = format!("#{}", entry.ticket_id);
+            if let Some(ticket) = ticket {
+                for part in [&ticket.summary, &ticket.company] {
+                    if !part.is_empty() {
+                        summary.push_str(" · ");
+                        summary.push_str(part);
+                    }
+                }
+            }
+            Some(ics::Event {
+                uid: format!(
+                    "history-{}-{}@ticketbase-desktop",
+                    entry.history_id, account.id
+                ),
+                time: ics::Time::Span(entry.start, entry.start + TimeDelta::minutes(minutes)),
+                summary,
+                location: None,
+                description: entry.note.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Writes the signed in user's timesheet for a period to `options.path`.
+#[tauri::command]
+pub async fn export_timesheet(
+    accounts: State<'_, Accounts>,
+    options: TimesheetOptions,
+) -> Result<TimesheetSummary, String> {
+    let account = accounts.active()?;
+    let response = account
+        .api
+        .get_correct_watch_histories_for_period(
+            account.user.id,
+            &options.start_date,
+            &options.end_date,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut entries: Vec<WorkEntry> = response
+        .payload
+        .histories
+        .into_iter()
+        .filter_map(|history| serde_json::from_value::<TicketHistory>(history).ok())
+        .filter_map(|history| WorkEntry::from_history(&history))
+        .collect();
+    entries.sort_by_key(|entry| entry.start);
+
+    let mut tickets = HashMap::new();
+    for entry in &entries {
+        if let Entry::Vacant(vacant) = tickets.entry(entry.ticket_id) {
+            vacant.insert(ticket_info(&account, entry.ticket_id).await);
+        }
+    }
+
+    let name = format!("Timesheet {} – {}", options.start_date, options.end_date);
+    let (contents, summary) = match options.format {
+        Format::Ics => {
+            let events = events(&account, &entries, &tickets, options.rounding);
+            let minutes = events
+                .iter()
+                .map(|event| match event.time {
+                    ics::Time::Span(start, end) => (end - start).num_minutes(),
+                    ics::Time::Day(_) => 0,
+                })
+                .sum();
+            let summary = TimesheetSummary {
+                rows: events.len(),
+                minutes,
+            };
+            (ics::to_string(&name, &events).into_bytes(), summary)
+        }
+        Format::Csv | Format::Xlsx => {
+            let (table, minutes) = table(&entries, &tickets, &options);
+            let summary = TimesheetSummary {
+                rows: table.rows.len(),
+                minutes,
+            };
+            let contents = match options.format {
+                Format::Csv => export::csv::to_string(&table).into_bytes(),
+                _ => export::xlsx::to_bytes(&[("Timesheet", &table)])?,
+            };
+            (contents, summary)
+        }
+    };
+    export::write(&options.path, &contents)?;
+    Ok(summary)
+}
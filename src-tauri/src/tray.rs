@@ -0,0 +1,353 @@
+//! The tray icon, which keeps the app running with its windows closed: the
+//! timed ticket and its time, how many tickets are new and assigned, and
+//! quick actions.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, Window, WindowEvent};
+
+use crate::accounts::Accounts;
+use crate::timer::{self, PAUSE};
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+const REFRESH: Duration = Duration::from_secs(1);
+/// Assigned tickets listed under "Open ticket".
+const LISTED_TICKETS: usize = 10;
+
+/// A stop chosen in the tray, until the ticket window asks for it. Stopping
+/// saves a history entry, which only the window's dialog can write.
+#[derive(Default)]
+pub struct Tray {
+    stop_requested: Mutex<Option<u32>>,
+}
+
+/// What the menu shows, apart from the elapsed time, which changes too often
+/// to rebuild the menu for.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Status {
+    signed_in: bool,
+    timer: Option<ActiveTimer>,
+    new_tickets: usize,
+    assigned_tickets: usize,
+    /// The first few assigned tickets, with their titles.
+    my_tickets: Vec<(u32, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ActiveTimer {
+    ticket_id: u32,
+    title: String,
+    running: bool,
+}
+
+/// Adds the tray icon and keeps it up to date. Without a tray, as on some
+/// Linux desktops, closing the main window still quits.
+pub fn create<R: Runtime>(app: &AppHandle<R>) {
+    let (status, elapsed_ms) = read(app);
+    let built = menu(app, &status, elapsed_ms).and_then(|(menu, time)| {
+        let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+            .menu(&menu)
+            .tooltip(tooltip(&status, elapsed_ms))
+            .show_menu_on_left_click(false)
+            .on_menu_event(handle_menu_event)
+            .on_tray_icon_event(|tray, event| {
+                if let TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    show_main_window(tray.app_handle());
+                }
+            });
+        if let Some(icon) = app.default_window_icon() {
+            tray = tray.icon(icon.clone());
+        }
+        let tray = tray.build(app).map_err(|e| e.to_string())?;
+        Ok((tray, time))
+    });
+
+    match built {
+        Ok((tray, time)) => spawn(tray, status, time),
+        Err(e) => log::error!("Failed to create the tray icon: {}", e),
+    }
+}
+
+/// Hides the main window instead of closing it while the tray icon is there.
+pub fn hide_on_close<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.label() == MAIN_WINDOW && window.app_handle().tray_by_id(TRAY_ID).is_some() {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Rebuilds the menu when what it shows changes, and otherwise only updates
+/// the elapsed time.
+fn spawn<R: Runtime>(tray: TrayIcon<R>, mut status: Status, mut time: Option<MenuItem<R>>) {
+    tauri::async_runtime::spawn(async move {
+        let app = tray.app_handle().clone();
+        loop {
+            tokio::time::sleep(REFRESH).await;
+            let (current, elapsed_ms) = read(&app);
+            if current != status {
+                match menu(&app, &current, elapsed_ms) {
+                    Ok((menu, item)) => {
+                        let _ = tray.set_menu(Some(menu));
+                        time = item;
+                        status = current;
+                    }
+                    Err(e) => log::error!("Failed to update the tray menu: {}", e),
+                }
+            } else if let Some(time) = &time {
+                let _ = time.set_text(time_text(&status, elapsed_ms));
+            }
+            let _ = tray.set_tooltip(Some(tooltip(&status, elapsed_ms)));
+            let _ = tray.set_title(status.timer.as_ref().map(|_| format_elapsed(elapsed_ms)));
+        }
+    });
+}
+
+/// The active account's timer and tickets, and how long the timer has run.
+fn read<R: Runtime>(app: &AppHandle<R>) -> (Status, i64) {
+    let Ok(account) = app.state::<Accounts>().active() else {
+        return (Status::default(), 0);
+    };
+    let tickets = account
+        .poller
+        .snapshot()
+        .or_else(|| account.cache.tickets(account.user.id).ok().flatten())
+        .unwrap_or_default();
+    let timer = timer::active(&account);
+
+    let title = |ticket_id: u32| {
+        tickets
+            .my_tickets
+            .iter()
+            .chain(&tickets.new_tickets)
+            .chain(&tickets.all_tickets)
+            .find(|ticket| ticket.id == ticket_id)
+            .cloned()
+            .or_else(|| account.cache.ticket(ticket_id).ok().flatten())
+            .and_then(|ticket| ticket.summary.or(ticket.subject))
+            .unwrap_or_default()
+    };
+
+    let status = Status {
+        signed_in: true,
+        timer: timer.as_ref().map(|timer| ActiveTimer {
+            ticket_id: timer.ticket_id,
+            title: title(timer.ticket_id),
+            running: timer.running,
+        }),
+        new_tickets: tickets.new_tickets.len(),
+        assigned_tickets: tickets.my_tickets.len(),
+        my_tickets: tickets
+            .my_tickets
+            .iter()
+            .take(LISTED_TICKETS)
+            .map(|ticket| {
+                let title = ticket.summary.as_deref().or(ticket.subject.as_deref());
+                (ticket.id, title.unwrap_or_default().to_string())
+            })
+            .collect(),
+    };
+    (status, timer.map_or(0, |timer| timer.elapsed_ms))
+}
+
+/// The menu, and the item showing the elapsed time if a timer is open.
+fn menu<R: Runtime>(
+    app: &AppHandle<R>,
+    status: &Status,
+    elapsed_ms: i64,
+) -> Result<(Menu<R>, Option<MenuItem<R>>), String> {
+    let item = |id: &str, text: &str, enabled: bool| {
+        MenuItem::with_id(app, id, text, enabled, None::<&str>).map_err(|e| e.to_string())
+    };
+    let separator = || PredefinedMenuItem::separator(app).map_err(|e| e.to_string());
+    let menu = Menu::new(app).map_err(|e| e.to_string())?;
+    let append = |entry: &dyn IsMenuItem<R>| menu.append(entry).map_err(|e| e.to_string());
+
+    let mut time = None;
+    match &status.timer {
+        Some(timer) => {
+            append(&item(
+                "timer-ticket",
+                &ticket_text(timer.ticket_id, &timer.title),
+                false,
+            )?)?;
+            let elapsed = item("timer-time", &time_text(status, elapsed_ms), false)?;
+            append(&elapsed)?;
+            time = Some(elapsed);
+            if timer.running {
+                append(&item("timer-pause", "Pause", true)?)?;
+            } else {
+                append(&item("timer-resume", "Resume", true)?)?;
+            }
+            append(&item("timer-stop", "Stop…", true)?)?;
+        }
+        None => append(&item("timer-none", "No timer running", false)?)?,
+    }
+    append(&separator()?)?;
+
+    if status.signed_in {
+        append(&item("ticket-counts", &counts_text(status), false)?)?;
+
+        let tickets = status
+            .my_tickets
+            .iter()
+            .map(|(id, title)| item(&format!("open-ticket:{id}"), &ticket_text(*id, title), true))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut entries: Vec<&dyn IsMenuItem<R>> =
+            tickets.iter().map(|ticket| ticket as _).collect();
+        let other_separator = separator()?;
+        let other = item("open-ticket", "Other ticket #…", true)?;
+        if !entries.is_empty() {
+            entries.push(&other_separator);
+        }
+        entries.push(&other);
+        let open =
+            Submenu::with_items(app, "Open ticket", true, &entries).map_err(|e| e.to_string())?;
+        append(&open)?;
+        append(&item("new-ticket", "New ticket", true)?)?;
+    } else {
+        append(&item("ticket-counts", "Not signed in", false)?)?;
+    }
+    append(&separator()?)?;
+    append(&item("show", "Show Ticketbase", true)?)?;
+    append(&item("quit", "Quit", true)?)?;
+    Ok((menu, time))
+}
+
+fn ticket_text(ticket_id: u32, title: &str) -> String {
+    if title.trim().is_empty() {
+        format!("#{ticket_id}")
+    } else {
+        format!("#{ticket_id} {}", title.trim())
+    }
+}
+
+fn time_text(status: &Status, elapsed_ms: i64) -> String {
+    match &status.timer {
+        Some(timer) if timer.running => format!("Running · {}", format_elapsed(elapsed_ms)),
+        _ => format!("Paused · {}", format_elapsed(elapsed_ms)),
+    }
+}
+
+fn counts_text(status: &Status) -> String {
+    format!(
+        "{} new · {} assigned",
+        status.new_tickets, status.assigned_tickets
+    )
+}
+
+fn tooltip(status: &Status, elapsed_ms: i64) -> String {
+    let mut lines = vec!["Ticketbase".to_string()];
+    if let Some(timer) = &status.timer {
+        lines.push(format!(
+            "{} – {}",
+            ticket_text(timer.ticket_id, &timer.title),
+            time_text(status, elapsed_ms)
+        ));
+    }
+    if status.signed_in {
+        lines.push(counts_text(status));
+    }
+    lines.join("\n")
+}
+
+fn format_elapsed(elapsed_ms: i64) -> String {
+    let seconds = elapsed_ms.max(0) / 1000;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let id = event.id().as_ref();
+    match id {
+        "timer-pause" | "timer-resume" | "timer-stop" => {
+            let app = app.clone();
+            let id = id.to_string();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = timer_action(&app, &id).await {
+                    log::error!(
+                        "Failed to {} the timer: {}",
+                        id.trim_start_matches("timer-"),
+                        e
+                    );
+                }
+            });
+        }
+        "open-ticket" => {
+            show_main_window(app);
+            let _ = app.emit_to(MAIN_WINDOW, "tray://open-ticket", ());
+        }
+        "new-ticket" => {
+            show_main_window(app);
+            let _ = app.emit_to(MAIN_WINDOW, "tray://new-ticket", ());
+        }
+        "show" => show_main_window(app),
+        "quit" => app.exit(0),
+        _ => {
+            if let Some(ticket_id) = id
+                .strip_prefix("open-ticket:")
+                .and_then(|id| id.parse().ok())
+            {
+                if let Err(e) = crate::show_ticket_window(app, ticket_id, None) {
+                    log::error!("Failed to open ticket {}: {}", ticket_id, e);
+                }
+            }
+        }
+    }
+}
+
+/// Acts on the timer the menu showed, unless it changed in the meantime.
+async fn timer_action<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<(), String> {
+    let account = app.state::<Accounts>().active()?;
+    let Some(timer) = timer::active(&account) else {
+        return Ok(());
+    };
+    match id {
+        "timer-pause" if timer.running => timer::set_running(app, &account, &timer, false).await,
+        "timer-resume" if timer.play_status == PAUSE => {
+            timer::set_running(app, &account, &timer, true).await
+        }
+        "timer-stop" => {
+            *app.state::<Tray>().stop_requested.lock().unwrap() = Some(timer.ticket_id);
+            crate::show_ticket_window(app, timer.ticket_id, None)?;
+            // A window that was already open doesn't ask again by itself
+            let _ = app.emit("tray://stop-timer", timer.ticket_id);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Whether the tray asked to stop the ticket's timer; asking again says no.
+#[tauri::command]
+pub fn take_stop_request(tray: State<'_, Tray>, ticket_id: u32) -> bool {
+    let mut requested = tray.stop_requested.lock().unwrap();
+    if *requested == Some(ticket_id) {
+        *requested = None;
+        true
+    } else {
+        false
+    }
+}
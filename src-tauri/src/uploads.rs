@@ -0,0 +1,261 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tauri_plugin_http::reqwest::{multipart, Body};
+use tokio_util::io::ReaderStream;
+
+use crate::accounts::Accounts;
+use crate::api::models::NewTicket;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_ATTACHMENT_BYTES: u64 = 50 * 1024 * 1024;
+const MAX_TOTAL_BYTES: u64 = 200 * 1024 * 1024;
+
+/// `application/*` types we accept besides office documents.
+const ALLOWED_APPLICATION: &[&str] = &[
+    "pdf",
+    "json",
+    "xml",
+    "zip",
+    "gzip",
+    "x-gzip",
+    "x-tar",
+    "x-7z-compressed",
+    "msword",
+    "vnd.ms-excel",
+    "vnd.ms-powerpoint",
+    "vnd.ms-outlook",
+    "rtf",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    id: String,
+    /// Position in the list of attachments.
+    file: usize,
+    name: String,
+    sent: u64,
+    total: u64,
+}
+
+/// A file that passed the checks and can be sent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    pub mime: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedTicket {
+    /// `None` if the server did not say; the ticket exists either way.
+    pub ticket_id: Option<u32>,
+    pub message: Option<String>,
+    pub attachments: Vec<String>,
+}
+
+fn allowed(mime: &str) -> bool {
+    let (kind, subtype) = mime.split_once('/').unwrap_or_default();
+    match kind {
+        "image" | "text" | "audio" | "video" => true,
+        "application" => {
+            ALLOWED_APPLICATION.contains(&subtype)
+                || subtype.starts_with("vnd.openxmlformats-officedocument.")
+                || subtype.starts_with("vnd.oasis.opendocument.")
+        }
+        "message" => subtype == "rfc822",
+        _ => false,
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn inspect(path: &Path) -> Result<Attachment, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("Not a file: {}", path.display()))?;
+    let metadata = fs::metadata(path).map_err(|e| format!("{}: {}", name, e))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", name));
+    }
+
+    let size = metadata.len();
+    if size == 0 {
+        return Err(format!("{} is empty", name));
+    }
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{} is {}, attachments may be at most {}",
+            name,
+            megabytes(size),
+            megabytes(MAX_ATTACHMENT_BYTES)
+        ));
+    }
+
+    let mime = mime_guess::from_path(path)
+        .first()
+        .map(|mime| mime.essence_str().to_string())
+        .ok_or_else(|| format!("{}: unknown file type", name))?;
+    if !allowed(&mime) {
+        return Err(format!(
+            "{}: files of type {} cannot be attached",
+            name, mime
+        ));
+    }
+
+    Ok(Attachment {
+        path: path.to_path_buf(),
+        name,
+        size,
+        mime,
+    })
+}
+
+fn inspect_all(paths: &[PathBuf]) -> Result<Vec<Attachment>, String> {
+    let attachments = paths
+        .iter()
+        .map(|path| inspect(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let total: u64 = attachments.iter().map(|attachment| attachment.size).sum();
+    if total > MAX_TOTAL_BYTES {
+        return Err(format!(
+            "Attachments add up to {}, at most {} can be sent with one ticket",
+            megabytes(total),
+            megabytes(MAX_TOTAL_BYTES)
+        ));
+    }
+    Ok(attachments)
+}
+
+/// Reports how far the request body got through one attachment.
+struct Upload<R: Runtime> {
+    app: AppHandle<R>,
+    progress: Progress,
+    last_progress: Option<Instant>,
+}
+
+impl<R: Runtime> Upload<R> {
+    fn sent(&mut self, read: usize) {
+        self.progress.sent += read as u64;
+        let done = self.progress.sent >= self.progress.total;
+        if done
+            || self
+                .last_progress
+                .is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL)
+        {
+            self.last_progress = Some(Instant::now());
+            let _ = self.app.emit("upload://progress", self.progress.clone());
+        }
+    }
+}
+
+fn part<R: Runtime>(
+    app: &AppHandle<R>,
+    upload_id: &str,
+    index: usize,
+    attachment: &Attachment,
+) -> Result<multipart::Part, String> {
+    let file = File::open(&attachment.path).map_err(|e| format!("{}: {}", attachment.name, e))?;
+    let mut upload = Upload {
+        app: app.clone(),
+        progress: Progress {
+            id: upload_id.to_string(),
+            file: index,
+            name: attachment.name.clone(),
+            sent: 0,
+            total: attachment.size,
+        },
+        last_progress: None,
+    };
+
+    // Read on the runtime's terms, chunk by chunk as the request asks for them
+    let chunks = ReaderStream::with_capacity(tokio::fs::File::from_std(file), CHUNK_SIZE).inspect(
+        move |chunk| {
+            if let Ok(chunk) = chunk {
+                upload.sent(chunk.len());
+            }
+        },
+    );
+    multipart::Part::stream_with_length(Body::wrap_stream(chunks), attachment.size)
+        .file_name(attachment.name.clone())
+        .mime_str(&attachment.mime)
+        .map_err(|e| e.to_string())
+}
+
+/// The new ticket's id, wherever this server version put it.
+fn created_ticket_id(payload: &Value) -> Option<u32> {
+    [
+        "/ticket_id",
+        "/id",
+        "/data/ticket_id",
+        "/data/id",
+        "/ticket/id",
+    ]
+    .into_iter()
+    .filter_map(|pointer| payload.pointer(pointer))
+    .find_map(|id| id.as_u64().or_else(|| id.as_str()?.parse().ok()))
+    .and_then(|id| u32::try_from(id).ok())
+}
+
+/// Checks files before they are offered for upload, so the form can list
+/// them with their size and reject what would be refused on submit.
+#[tauri::command]
+pub fn inspect_attachments(paths: Vec<PathBuf>) -> Result<Vec<Attachment>, String> {
+    inspect_all(&paths)
+}
+
+/// Creates a ticket, streaming the attachments from disk. Progress goes out
+/// as `upload://progress` events tagged with `upload_id`.
+#[tauri::command]
+pub async fn create_ticket<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: State<'_, Accounts>,
+    upload_id: String,
+    ticket: NewTicket,
+    attachments: Vec<PathBuf>,
+) -> Result<CreatedTicket, String> {
+    let api = &accounts.active()?.api;
+    let attachments = inspect_all(&attachments)?;
+
+    let response = if attachments.is_empty() {
+        api.create_ticket(&ticket).await
+    } else {
+        let parts = attachments
+            .iter()
+            .enumerate()
+            .map(|(index, attachment)| part(&app, &upload_id, index, attachment))
+            .collect::<Result<Vec<_>, _>>()?;
+        api.create_ticket_with_attachments(&ticket, parts).await
+    }
+    .map_err(|e| e.to_string())?;
+
+    if response.status != "success" {
+        return Err(response
+            .message
+            .or(response.result)
+            .unwrap_or_else(|| "Failed to create ticket".to_string()));
+    }
+
+    Ok(CreatedTicket {
+        ticket_id: created_ticket_id(&response.payload),
+        message: response.message,
+        attachments: attachments
+            .into_iter()
+            .map(|attachment| attachment.name)
+            .collect(),
+    })
+}
@@ -0,0 +1,257 @@
+//! Timers pausing while the session is locked or idle. A private dbus-daemon
+//! stands in for both the system and the session bus, with a fake logind on
+//! it; without dbus-daemon installed the test is skipped.
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::io::Read;
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use tauri::async_runtime::block_on;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{OwnedFd, OwnedObjectPath};
+use zbus::{interface, Connection};
+
+use common::{mock_server, Bus, TestApp};
+
+const SESSION: &str = "/org/freedesktop/login1/session/test";
+
+#[derive(Default)]
+struct Manager {
+    /// Our end of each inhibitor lock handed out, with its `what` and `mode`.
+    inhibitors: Vec<(String, String, UnixStream)>,
+}
+
+#[interface(name = "org.freedesktop.login1.Manager")]
+impl Manager {
+    fn inhibit(&mut self, what: &str, _who: &str, _why: &str, mode: &str) -> OwnedFd {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        self.inhibitors
+            .push((what.to_string(), mode.to_string(), ours));
+        std::os::fd::OwnedFd::from(theirs).into()
+    }
+
+    fn get_session(&self, _session_id: &str) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(SESSION).unwrap()
+    }
+
+    #[zbus(name = "GetSessionByPID")]
+    fn get_session_by_pid(&self, _pid: u32) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(SESSION).unwrap()
+    }
+
+    #[zbus(signal)]
+    async fn prepare_for_sleep(emitter: &SignalEmitter<'_>, start: bool) -> zbus::Result<()>;
+}
+
+#[derive(Default)]
+struct Session {
+    idle: bool,
+    idle_since: u64,
+}
+
+#[interface(name = "org.freedesktop.login1.Session")]
+impl Session {
+    #[zbus(signal)]
+    async fn lock(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn unlock(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn locked_hint(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn idle_hint(&self) -> bool {
+        self.idle
+    }
+
+    #[zbus(property)]
+    fn idle_since_hint(&self) -> u64 {
+        self.idle_since
+    }
+}
+
+/// Registers the fake logind on `bus`.
+fn logind(bus: &Bus) -> Connection {
+    block_on(async {
+        zbus::connection::Builder::address(bus.address.as_str())?
+            .name("org.freedesktop.login1")?
+            .serve_at("/org/freedesktop/login1", Manager::default())?
+            .serve_at(SESSION, Session::default())?
+            .build()
+            .await
+    })
+    .expect("fake logind registers")
+}
+
+/// Sends the session's `Lock` or `Unlock` signal.
+fn signal_session(logind: &Connection, lock: bool) {
+    block_on(async {
+        let session = logind
+            .object_server()
+            .interface::<_, Session>(SESSION)
+            .await?;
+        match lock {
+            true => Session::lock(session.signal_emitter()).await,
+            false => Session::unlock(session.signal_emitter()).await,
+        }
+    })
+    .unwrap();
+}
+
+/// Sends `PrepareForSleep`.
+fn signal_sleep(logind: &Connection, start: bool) {
+    block_on(async {
+        let manager = logind
+            .object_server()
+            .interface::<_, Manager>("/org/freedesktop/login1")
+            .await?;
+        Manager::prepare_for_sleep(manager.signal_emitter(), start).await
+    })
+    .unwrap();
+}
+
+/// Inhibitor locks handed out so far, oldest first.
+fn inhibitors(logind: &Connection) -> Vec<(String, String, UnixStream)> {
+    block_on(async {
+        let manager = logind
+            .object_server()
+            .interface::<_, Manager>("/org/freedesktop/login1")
+            .await
+            .unwrap();
+        let inhibitors = std::mem::take(&mut manager.get_mut().await.inhibitors);
+        inhibitors
+    })
+}
+
+/// Sets the session's idle hint as logind does, `since` in milliseconds.
+fn set_idle(logind: &Connection, idle: bool, since: i64) {
+    block_on(async {
+        let session = logind
+            .object_server()
+            .interface::<_, Session>(SESSION)
+            .await?;
+        {
+            let mut state = session.get_mut().await;
+            state.idle = idle;
+            state.idle_since = since as u64 * 1000;
+        }
+        let state = session.get().await;
+        state
+            .idle_since_hint_changed(session.signal_emitter())
+            .await?;
+        state.idle_hint_changed(session.signal_emitter()).await
+    })
+    .unwrap();
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+/// Retries `condition` for a few seconds, as the watcher subscribes in the
+/// background.
+fn eventually(mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "condition not met in time");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn timer(app: &TestApp) -> Value {
+    app.invoke("get_timer", json!({ "ticketId": 101 })).unwrap()
+}
+
+#[test]
+fn timers_pause_while_away() {
+    let Some(bus) = Bus::start() else {
+        eprintln!("dbus-daemon not found, skipping");
+        return;
+    };
+    // The only test in this binary, so nothing else reads these meanwhile
+    std::env::set_var("DBUS_SYSTEM_BUS_ADDRESS", &bus.address);
+    std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus.address);
+    std::env::remove_var("XDG_SESSION_ID");
+    let logind = logind(&bus);
+
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let away = app.events("timer://away");
+    let ticket = json!({ "ticketId": 101, "userId": 1 });
+    app.invoke("play", ticket.clone()).unwrap();
+
+    // Locking the screen pauses the timer
+    eventually(|| {
+        signal_session(&logind, true);
+        timer(&app)["running"] == false
+    });
+    assert!(away.try_recv().is_err());
+
+    // Back at the desk, the user is asked about the gap
+    signal_session(&logind, false);
+    let gap = away.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(gap["reason"], "locked");
+    assert_eq!(gap["timers"][0]["accountId"], "mock-1");
+    assert_eq!(gap["timers"][0]["ticketId"], 101);
+    assert_eq!(app.invoke("get_away_gap", json!({})).unwrap(), gap);
+
+    // Keeping it resumes the timer with the time away counted
+    app.invoke("resolve_away_gap", json!({ "keep": true }))
+        .unwrap();
+    assert_eq!(
+        away.recv_timeout(Duration::from_secs(5)).unwrap(),
+        Value::Null
+    );
+    let resumed = timer(&app);
+    assert_eq!(resumed["playStatus"], 3);
+    let gap_ms = gap["returnedAt"].as_i64().unwrap() - gap["since"].as_i64().unwrap();
+    assert!(resumed["elapsedMs"].as_i64().unwrap() >= gap_ms);
+    assert_eq!(app.invoke("get_away_gap", json!({})).unwrap(), Value::Null);
+
+    // Idle for ten minutes already: neither side counts them
+    set_idle(&logind, true, now_millis() - 10 * 60_000);
+    eventually(|| timer(&app)["running"] == false);
+    let status = app.invoke("get_player_status", ticket.clone()).unwrap();
+    assert_eq!(status["playerStatus"]["total_time"], 0);
+
+    set_idle(&logind, false, 0);
+    let gap = away.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(gap["reason"], "idle");
+
+    // Discarding resumes without the time away
+    app.invoke("resolve_away_gap", json!({ "keep": false }))
+        .unwrap();
+    let resumed = timer(&app);
+    assert_eq!(resumed["running"], true);
+    assert!(resumed["elapsedMs"].as_i64().unwrap() < 60_000);
+
+    // Suspend waits for the pause: the delay lock is let go only after it
+    let mut taken = inhibitors(&logind);
+    assert_eq!(taken.len(), 1);
+    let (what, mode, mut delay) = taken.remove(0);
+    assert_eq!((what.as_str(), mode.as_str()), ("sleep", "delay"));
+    signal_sleep(&logind, true);
+    delay
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(delay.read(&mut [0; 1]).unwrap(), 0);
+    assert_eq!(timer(&app)["running"], false);
+
+    // Awake again, the next suspend is held off as well
+    signal_sleep(&logind, false);
+    let gap = away.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(gap["reason"], "sleep");
+    eventually(|| !inhibitors(&logind).is_empty());
+}
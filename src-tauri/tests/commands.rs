@@ -0,0 +1,930 @@
+//! Commands invoked by name through the IPC layer, on tauri's mock runtime
+//! and against the mock Ticketbase server, so no display or network is needed.
+
+mod common;
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+
+use common::{mock_server, TestApp};
+use ticketbase_mock::Fault;
+
+#[test]
+fn builds_with_plugins_and_state() {
+    let app = TestApp::new();
+
+    let profiles = app.invoke("get_server_profiles", json!({})).unwrap();
+    assert_eq!(profiles["active"], "default");
+    assert_eq!(app.invoke("get_accounts", json!({})).unwrap(), json!([]));
+    assert_eq!(
+        app.invoke("get_throttle_state", json!({})).unwrap()["throttled"],
+        false
+    );
+}
+
+#[test]
+fn invokes_commands_by_name() {
+    let app = TestApp::new();
+
+    assert_eq!(
+        app.invoke("greet", json!({ "name": "Anna" })).unwrap(),
+        "Hello, Anna! You've been greeted from Rust!"
+    );
+    assert!(app.invoke("no_such_command", json!({})).is_err());
+    // Missing arguments are rejected before the command runs
+    assert!(app.invoke("greet", json!({})).is_err());
+}
+
+#[test]
+fn opens_ticket_window_once_per_ticket() {
+    let app = TestApp::new();
+
+    app.invoke("open_ticket_window", json!({ "ticketId": 42 }))
+        .unwrap();
+    assert_eq!(app.ticket_windows(), ["ticket-42"]);
+
+    // A second call focuses the existing window instead of opening another
+    app.invoke("open_ticket_window", json!({ "ticketId": 42 }))
+        .unwrap();
+    assert_eq!(app.ticket_windows(), ["ticket-42"]);
+
+    app.invoke("open_ticket_window", json!({ "ticketId": 7 }))
+        .unwrap();
+    assert_eq!(app.ticket_windows(), ["ticket-42", "ticket-7"]);
+}
+
+#[test]
+fn ticket_window_for_unknown_account_is_not_opened() {
+    let app = TestApp::new();
+
+    let result = app.invoke(
+        "open_ticket_window",
+        json!({ "ticketId": 42, "accountId": "mock-99" }),
+    );
+    assert_eq!(result, Err(json!("Unknown account: mock-99")));
+    assert!(app.ticket_windows().is_empty());
+}
+
+#[test]
+fn login_rejects_wrong_password() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.invoke(
+        "save_server_profile",
+        json!({ "profile": { "id": "mock", "name": "Mock", "baseUrl": server.url() } }),
+    )
+    .unwrap();
+
+    let result = app.invoke(
+        "login",
+        json!({ "email": "anna@example.com", "password": "wrong", "profileId": "mock" }),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn server_profile_ids_stay_out_of_paths() {
+    let app = TestApp::new();
+    let server = mock_server();
+
+    for id in ["../../outside", "Mock", "mock/1", "mock.bin"] {
+        let result = app.invoke(
+            "save_server_profile",
+            json!({ "profile": { "id": id, "name": "Mock", "baseUrl": server.url() } }),
+        );
+        assert!(result.is_err(), "{} was accepted", id);
+    }
+    let profiles = app.invoke("get_server_profiles", json!({})).unwrap();
+    assert_eq!(profiles["profiles"].as_array().unwrap().len(), 1);
+
+    let saved = app
+        .invoke(
+            "save_server_profile",
+            json!({ "profile": { "id": "", "name": "../Mock Server", "baseUrl": server.url() } }),
+        )
+        .unwrap();
+    assert_eq!(saved["id"], "mock-server");
+}
+
+#[test]
+fn signed_in_account_reads_tickets() {
+    let app = TestApp::new();
+    let server = mock_server();
+
+    let account = app.sign_in(&server, "anna@example.com");
+    assert_eq!(account["id"], "mock-1");
+    assert_eq!(account["active"], true);
+    assert_eq!(account["baseUrl"], server.url());
+
+    let filter = json!({ "user_id": 1, "user_group_id": account["user"]["user_group_id"] });
+    let tickets = app
+        .invoke("get_tickets", json!({ "filter": filter }))
+        .unwrap();
+    assert_eq!(tickets["all_tickets"].as_array().unwrap().len(), 4);
+    assert_eq!(tickets["my_tickets"][0]["id"], 102);
+
+    // A read, so a server error is retried instead of reaching the frontend
+    server.fail("getUserStatus", Fault::Status(503), 1);
+    let status = app
+        .invoke("get_user_status", json!({ "userId": 1 }))
+        .unwrap();
+    assert_eq!(status["data"]["activity"]["activeStatus"], true);
+    assert_eq!(server.request_count("getUserStatus"), 2);
+
+    let ticket = app
+        .invoke("get_ticket_by_id", json!({ "ticketId": 102 }))
+        .unwrap();
+    assert_eq!(ticket["id"], 102);
+    assert!(ticket["company"]["name"].is_string());
+}
+
+#[test]
+fn rate_limits_hold_requests_back_and_are_reported() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let throttle = app.events("api://throttle");
+
+    server.fail("getUserStatus", Fault::RateLimited(1), 1);
+    let started = Instant::now();
+    let status = app
+        .invoke("get_user_status", json!({ "userId": 1 }))
+        .unwrap();
+    assert_eq!(status["data"]["activity"]["activeStatus"], true);
+    // Sent again once the server's Retry-After had passed
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.request_count("getUserStatus"), 2);
+
+    assert_eq!(
+        throttle.recv_timeout(Duration::from_secs(5)).unwrap(),
+        json!({ "throttled": true, "retryInMs": 1000, "endpoint": "/api/getUserStatus" })
+    );
+    assert_eq!(
+        throttle.recv_timeout(Duration::from_secs(5)).unwrap()["throttled"],
+        false
+    );
+    assert_eq!(
+        app.invoke("get_throttle_state", json!({})).unwrap()["throttled"],
+        false
+    );
+}
+
+#[test]
+fn customers_only_see_their_company() {
+    let app = TestApp::new();
+    let server = mock_server();
+
+    let account = app.sign_in(&server, "maria@muster.example");
+    let filter = json!({ "user_id": 3, "user_group_id": account["user"]["user_group_id"] });
+    let tickets = app
+        .invoke("get_tickets", json!({ "filter": filter }))
+        .unwrap();
+    assert_eq!(tickets["all_tickets"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn switching_accounts_emits_change() {
+    let app = TestApp::new();
+    let server = mock_server();
+    let changes = app.events("accounts://changed");
+
+    let anna = app.sign_in(&server, "anna@example.com");
+    let next = changes.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(next, json!({ "active": anna["id"] }));
+
+    let jonas = app.sign_in(&server, "jonas@example.com");
+    let next = changes.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(next, json!({ "active": jonas["id"] }));
+
+    let accounts = app.invoke("get_accounts", json!({})).unwrap();
+    assert_eq!(accounts.as_array().unwrap().len(), 2);
+
+    app.invoke("switch_account", json!({ "id": anna["id"] }))
+        .unwrap();
+    let next = changes.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(next, json!({ "active": anna["id"] }));
+
+    // Switching to the account that is already active changes nothing
+    app.invoke("switch_account", json!({ "id": anna["id"] }))
+        .unwrap();
+    assert!(changes.recv_timeout(Duration::from_millis(200)).is_err());
+}
+
+#[test]
+fn sessions_stay_private() {
+    let app = TestApp::new();
+    let server = mock_server();
+
+    let account = app.sign_in(&server, "anna@example.com");
+    let accounts = app.invoke("get_accounts", json!({})).unwrap();
+    let active = app.invoke("load_session", json!({})).unwrap();
+    assert!(account.get("token").is_none());
+    assert!(accounts[0].get("token").is_none());
+    assert_eq!(active["id"], account["id"]);
+    assert!(active.get("token").is_none());
+
+    // No keyring in tests, so the session went to the encrypted file
+    let data_dir = app.app.path().app_data_dir().unwrap();
+    let session = data_dir.join(format!("session-{}.bin", account["id"].as_str().unwrap()));
+    assert!(session.exists());
+    assert!(data_dir.join("install.key").exists());
+    #[cfg(unix)]
+    for file in [session, data_dir.join("install.key")] {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+    }
+}
+
+#[test]
+fn accounts_that_fail_to_load_keep_their_data() {
+    let app = TestApp::new();
+    let server = mock_server();
+    let account = app.sign_in(&server, "anna@example.com");
+    let id = account["id"].as_str().unwrap().to_string();
+
+    let data_dir = app.app.path().app_data_dir().unwrap();
+    let account_dir = data_dir.join("accounts").join(&id);
+    let index = app
+        .app
+        .path()
+        .app_config_dir()
+        .unwrap()
+        .join("accounts.json");
+    assert!(account_dir.exists());
+
+    // As if the keyring were locked at the next start
+    let session = data_dir.join(format!("session-{}.bin", id));
+    let saved = std::fs::read(&session).unwrap();
+    std::fs::remove_file(&session).unwrap();
+    let app = app.restart();
+    assert_eq!(app.invoke("get_accounts", json!({})).unwrap(), json!([]));
+    assert!(account_dir.exists());
+    let listed: Value = serde_json::from_slice(&std::fs::read(&index).unwrap()).unwrap();
+    assert_eq!(listed["accounts"], json!([id]));
+
+    // Back once the session can be read again
+    std::fs::write(&session, saved).unwrap();
+    let app = app.restart();
+    assert_eq!(app.invoke("get_accounts", json!({})).unwrap()[0]["id"], id);
+
+    // A broken account list loses nothing either
+    std::fs::write(&index, b"{\"accounts\": [").unwrap();
+    let app = app.restart();
+    assert_eq!(app.invoke("get_accounts", json!({})).unwrap()[0]["id"], id);
+    assert!(account_dir.exists());
+}
+
+#[test]
+fn timer_follows_player_commands() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let ticks = app.events("timer://tick");
+    let ticket = json!({ "ticketId": 101, "userId": 1 });
+
+    app.invoke("play", ticket.clone()).unwrap();
+    let tick = ticks.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(tick["accountId"], "mock-1");
+    assert_eq!(tick["timers"][0]["ticketId"], 101);
+    assert_eq!(tick["timers"][0]["running"], true);
+
+    app.invoke("pause", ticket.clone()).unwrap();
+    let timer = app.invoke("get_timer", json!({ "ticketId": 101 })).unwrap();
+    assert_eq!(timer["running"], false);
+    assert_eq!(timer["playStatus"], 2);
+
+    // The server's status is taken over, here unchanged
+    app.invoke("get_player_status", ticket.clone()).unwrap();
+    assert_eq!(
+        app.invoke("get_timer", json!({ "ticketId": 101 })).unwrap()["playStatus"],
+        2
+    );
+
+    app.invoke("stop", ticket).unwrap();
+    assert_eq!(
+        app.invoke("get_timer", json!({ "ticketId": 101 })).unwrap(),
+        Value::Null
+    );
+    assert_eq!(app.invoke("get_timers", json!({})).unwrap(), json!([]));
+}
+
+fn history(text: &str) -> Value {
+    json!({ "kind": "history", "ticket_id": 101, "user_id": 1, "verlauf_text": text, "status_id": 13 })
+}
+
+/// Polls `done` for up to five seconds, asking for a poll (and with it an
+/// outbox replay) each time.
+fn wait_for_replay(app: &TestApp, done: impl Fn() -> bool) {
+    for _ in 0..25 {
+        if done() {
+            return;
+        }
+        app.invoke("refresh_tickets_now", json!({})).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    panic!("outbox was not replayed");
+}
+
+#[test]
+fn outbox_sends_an_entry_once_while_a_replay_runs() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+
+    server.fail("saveVerlaufApi", Fault::Status(503), 1);
+    let first = app
+        .invoke(
+            "submit_mutation",
+            json!({ "mutation": history("Erster Eintrag"), "idempotencyKey": "first" }),
+        )
+        .unwrap();
+    assert_eq!(first["status"], "pending");
+
+    // Hold the replay of the first entry on the server while the second comes in
+    server.fail(
+        "saveVerlaufApi",
+        Fault::Delay(Duration::from_millis(800)),
+        1,
+    );
+    let polls = server.request_count("getTickets");
+    app.invoke("refresh_tickets_now", json!({})).unwrap();
+    while server.request_count("getTickets") == polls {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    std::thread::sleep(Duration::from_millis(200));
+
+    let second = app
+        .invoke(
+            "submit_mutation",
+            json!({ "mutation": history("Zweiter Eintrag"), "idempotencyKey": "second" }),
+        )
+        .unwrap();
+    assert_eq!(second["status"], "success");
+
+    // The failed try and one each, not a second send of the new entry
+    assert_eq!(server.request_count("saveVerlaufApi"), 3);
+    assert_eq!(app.invoke("get_outbox", json!({})).unwrap(), json!([]));
+}
+
+#[test]
+fn outbox_keeps_entries_the_server_could_not_take_yet() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+
+    for (key, status) in [401, 408].into_iter().enumerate() {
+        server.fail("saveVerlaufApi", Fault::Status(status), 1);
+        let response = app
+            .invoke(
+                "submit_mutation",
+                json!({ "mutation": history("Später"), "idempotencyKey": format!("later-{}", key) }),
+            )
+            .unwrap();
+        assert_eq!(response["status"], "pending", "after {}", status);
+    }
+    assert_eq!(
+        app.invoke("get_outbox", json!({}))
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    wait_for_replay(&app, || {
+        app.invoke("get_outbox", json!({})).unwrap() == json!([])
+    });
+
+    // A validation error is the user's to fix, right in the form
+    server.fail("saveVerlaufApi", Fault::Status(422), 1);
+    let result = app.invoke(
+        "submit_mutation",
+        json!({ "mutation": history("Ungültig"), "idempotencyKey": "invalid" }),
+    );
+    assert!(result.is_err());
+    assert_eq!(app.invoke("get_outbox", json!({})).unwrap(), json!([]));
+}
+
+#[test]
+fn signing_out_keeps_unsent_changes_unless_told() {
+    let app = TestApp::new();
+    let server = mock_server();
+    let account = app.sign_in(&server, "anna@example.com");
+
+    server.fail("saveVerlaufApi", Fault::Status(503), 100);
+    app.invoke(
+        "submit_mutation",
+        json!({ "mutation": history("Offline notiert"), "idempotencyKey": "unsent" }),
+    )
+    .unwrap();
+    let accounts = app.invoke("get_accounts", json!({})).unwrap();
+    assert_eq!(accounts[0]["unsent"], 1);
+
+    let id = &account["id"];
+    let result = app.invoke(
+        "remove_account",
+        json!({ "id": id, "discardUnsent": false }),
+    );
+    assert_eq!(
+        result,
+        Err(json!("Changes of Anna Berger have not been sent yet"))
+    );
+    assert!(app
+        .invoke("clear_session", json!({ "discardUnsent": false }))
+        .is_err());
+    assert_eq!(
+        app.invoke("get_outbox", json!({})).unwrap()[0]["idempotencyKey"],
+        "unsent"
+    );
+
+    app.invoke("remove_account", json!({ "id": id, "discardUnsent": true }))
+        .unwrap();
+    assert_eq!(app.invoke("get_accounts", json!({})).unwrap(), json!([]));
+}
+
+#[test]
+fn outbox_replays_in_order_after_an_outage() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let writes = ["saveVerlaufApi", "newTodo", "sendMessage"];
+    for endpoint in writes {
+        server.fail(endpoint, Fault::Status(503), usize::MAX);
+    }
+
+    let mutations = [
+        history("Drucker geprüft"),
+        json!({ "kind": "new_todo", "ticket_id": 101, "user_id": 1, "todo": "Toner bestellen" }),
+        json!({ "kind": "message", "ticket_id": 101, "user_id": 1, "message": "Toner kommt morgen" }),
+    ];
+    for (key, mutation) in mutations.iter().enumerate() {
+        let response = app
+            .invoke(
+                "submit_mutation",
+                json!({ "mutation": mutation, "idempotencyKey": format!("outage-{}", key) }),
+            )
+            .unwrap();
+        assert_eq!(response["status"], "pending");
+    }
+    let outbox = app.invoke("get_outbox", json!({})).unwrap();
+    let kinds: Vec<&Value> = outbox
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| &entry["mutation"]["kind"])
+        .collect();
+    assert_eq!(kinds, ["history", "new_todo", "message"]);
+
+    for endpoint in writes {
+        server.recover(endpoint);
+    }
+    wait_for_replay(&app, || {
+        app.invoke("get_outbox", json!({})).unwrap() == json!([])
+    });
+
+    let accepted: Vec<String> = server
+        .requests()
+        .into_iter()
+        .filter(|(endpoint, status)| writes.contains(&endpoint.as_str()) && *status == 200)
+        .map(|(endpoint, _)| endpoint)
+        .collect();
+    assert_eq!(accepted, writes);
+}
+
+#[test]
+fn exports_timesheet_for_period() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let dir = app.app.path().app_cache_dir().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    let export = |format: &str, path: &std::path::Path| {
+        app.invoke(
+            "export_timesheet",
+            json!({ "options": {
+                "startDate": "2026-10-01",
+                "endDate": "2026-10-31",
+                "format": format,
+                "path": path,
+                "columns": ["ticket", "minutes"],
+                "rounding": { "minutes": 15, "mode": "up" },
+            } }),
+        )
+    };
+
+    // 40 and 15 minutes booked on two days, rounded up to quarter hours
+    let csv = dir.join("timesheet.csv");
+    let summary = export("csv", &csv).unwrap();
+    assert_eq!(summary, json!({ "rows": 2, "minutes": 60 }));
+    assert_eq!(
+        std::fs::read_to_string(&csv).unwrap(),
+        "\u{feff}Ticket,Minutes\r\n104,45\r\n102,15\r\n"
+    );
+
+    let xlsx = dir.join("timesheet.xlsx");
+    export("xlsx", &xlsx).unwrap();
+    assert!(std::fs::read(&xlsx).unwrap().starts_with(b"PK"));
+
+    // One event per stretch of work
+    let ics = dir.join("timesheet.ics");
+    export("ics", &ics).unwrap();
+    let calendar = std::fs::read_to_string(&ics).unwrap();
+    assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+    assert!(calendar.contains("UID:history-502-mock-1@ticketbase-desktop"));
+}
+
+#[test]
+fn exports_reports() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let dir = app.app.path().app_cache_dir().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    let export = |report: &str, format: &str, path: &std::path::Path| {
+        app.invoke(
+            "export_report",
+            json!({ "options": {
+                "report": report,
+                "startDate": "2026-10-01",
+                "endDate": "2026-10-31",
+                "month": 10,
+                "format": format,
+                "path": path,
+                "csv": { "delimiter": ";", "encoding": "windows-1252" },
+            } }),
+        )
+    };
+
+    // The average rating row is not a review
+    let csv = dir.join("reviews.csv");
+    assert_eq!(
+        export("report4", "csv", &csv).unwrap(),
+        json!({ "rows": 1 })
+    );
+    assert_eq!(
+        std::fs::read(&csv).unwrap(),
+        b"Technician;Ticket;Customer;Rating;Feedback\r\nAnna Berger;104;Beispiel KG;5;Schnell gel\xf6st\r\n"
+    );
+
+    // Feedback stays text, even when it looks like a formula or starts with a date
+    for feedback in ["=1+2", "2026-10-03 wieder da"] {
+        app.invoke(
+            "rate_ticket",
+            json!({ "ticketId": 104, "userId": 1, "rating": 4, "feedback": feedback }),
+        )
+        .unwrap();
+    }
+    export("report4", "csv", &csv).unwrap();
+    let reviews = String::from_utf8_lossy(&std::fs::read(&csv).unwrap()).into_owned();
+    assert!(reviews.ends_with(
+        "Anna Berger;104;Beispiel KG;4;'=1+2\r\nAnna Berger;104;Beispiel KG;4;2026-10-03 wieder da\r\n"
+    ));
+
+    // A quote would end up inside every quoted field
+    let result = app.invoke(
+        "export_report",
+        json!({ "options": {
+            "report": "report4",
+            "startDate": "2026-10-01",
+            "endDate": "2026-10-31",
+            "month": 10,
+            "format": "csv",
+            "path": csv,
+            "csv": { "delimiter": "\"", "encoding": "utf-8" },
+        } }),
+    );
+    assert_eq!(result, Err(json!("'\"' cannot be used as CSV delimiter")));
+
+    let xlsx = dir.join("statistics.xlsx");
+    export("report5", "xlsx", &xlsx).unwrap();
+    assert!(std::fs::read(&xlsx).unwrap().starts_with(b"PK"));
+
+    let pdf = dir.join("top.pdf");
+    assert_eq!(
+        export("topUsers", "pdf", &pdf).unwrap(),
+        json!({ "rows": 2 })
+    );
+    let pdf = std::fs::read(&pdf).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+}
+
+#[test]
+fn serves_appointments_as_calendar_feed() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let fetch = |url: &str| {
+        let url = url.strip_prefix("http://").unwrap();
+        let (host, path) = url.split_once('/').unwrap();
+        let mut stream = std::net::TcpStream::connect(host).unwrap();
+        write!(stream, "GET /{path} HTTP/1.1\r\nHost: {host}\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        // Unfolded, so long lines can be matched
+        response.replace("\r\n ", "")
+    };
+
+    let status = app
+        .invoke("set_calendar_feed", json!({ "enabled": true, "port": 0 }))
+        .unwrap();
+    let url = status["url"].as_str().unwrap().to_string();
+    assert!(url.starts_with("http://127.0.0.1:"));
+
+    // Only the own scheduled tickets, with where to go and whom to call
+    let response = fetch(&url);
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: text/calendar"));
+    assert_eq!(response.matches("BEGIN:VEVENT").count(), 1);
+    assert!(response.contains("UID:ticket-102-mock-1@ticketbase-desktop"));
+    assert!(response.contains("LOCATION:Muster AG\\, Hauptsitz"));
+    assert!(response.contains("Location: Hauptsitz"));
+
+    // A new URL shuts out the old one
+    let status = app.invoke("reset_calendar_feed_url", json!({})).unwrap();
+    assert_ne!(status["url"], json!(url));
+    assert!(fetch(&url).starts_with("HTTP/1.1 404"));
+    assert!(fetch(status["url"].as_str().unwrap()).starts_with("HTTP/1.1 200"));
+
+    let status = app
+        .invoke("set_calendar_feed", json!({ "enabled": false, "port": 0 }))
+        .unwrap();
+    assert_eq!(status["url"], Value::Null);
+
+    let dir = app.app.path().app_cache_dir().unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("appointments.ics");
+    assert_eq!(
+        app.invoke("export_appointments", json!({ "path": path }))
+            .unwrap(),
+        json!(1)
+    );
+    assert!(std::fs::read_to_string(&path).unwrap().contains("DTSTART:"));
+}
+
+#[test]
+fn reports_shortcuts_that_cannot_be_registered() {
+    let app = TestApp::new();
+
+    // The mock runtime has no display server, so nothing gets registered
+    let status = app.invoke("get_shortcuts", json!({})).unwrap();
+    assert_eq!(status[0]["action"], "pauseResume");
+    assert_eq!(status[0]["accelerator"], "CommandOrControl+Alt+Shift+P");
+    assert_eq!(status[0]["registered"], false);
+    assert_eq!(
+        status[0]["error"],
+        "Global shortcuts are not available on this system"
+    );
+
+    let status = app
+        .invoke(
+            "set_shortcuts",
+            json!({ "settings": {
+                "pauseResume": "Ctrl+Alt+P",
+                "newTicket": "Ctrl+Alt+P",
+                "openTicket": "Ctrl+Alt+Nope",
+            } }),
+        )
+        .unwrap();
+    assert_eq!(status[1]["error"], "Already used for Pause/resume timer");
+    assert!(status[2]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Not a valid shortcut"));
+
+    // Turned off, which is not an error
+    app.invoke(
+        "set_shortcuts",
+        json!({ "settings": { "pauseResume": "", "newTicket": "", "openTicket": "" } }),
+    )
+    .unwrap();
+    let status = app.invoke("get_shortcuts", json!({})).unwrap();
+    assert_eq!(status[0]["accelerator"], "");
+    assert_eq!(status[0]["error"], Value::Null);
+}
+
+#[test]
+fn opens_deep_links_after_checking_them() {
+    let app = TestApp::new();
+
+    app.invoke(
+        "open_deep_link",
+        json!({ "link": "ticketbase://ticket/42" }),
+    )
+    .unwrap();
+    assert_eq!(app.ticket_windows(), ["ticket-42"]);
+
+    // Broken links are turned down before any window opens
+    for link in [
+        "ticketbase://ticket/abc",
+        "ticketbase://ticket/7?as=admin",
+        "ticketbase://new?priority=urgent",
+        "https://ticket/7",
+    ] {
+        assert!(app
+            .invoke("open_deep_link", json!({ "link": link }))
+            .is_err());
+    }
+    assert_eq!(app.ticket_windows(), ["ticket-42"]);
+    assert!(app.app.get_webview_window("new-ticket").is_none());
+
+    app.invoke(
+        "open_deep_link",
+        json!({ "link": "ticketbase://new?company=Muster%20AG&description=Drucker+defekt&priority=high" }),
+    )
+    .unwrap();
+    assert!(app.app.get_webview_window("new-ticket").is_some());
+
+    // The form takes the draft once
+    assert_eq!(
+        app.invoke("take_new_ticket_draft", json!({})).unwrap(),
+        json!({ "company": "Muster AG", "description": "Drucker defekt", "priority": "VERY_HIGH" })
+    );
+    assert_eq!(
+        app.invoke("take_new_ticket_draft", json!({})).unwrap(),
+        Value::Null
+    );
+}
+
+/// The fixture attachment of ticket 101.
+const ATTACHMENT: &str = "Testseite: alle Farben leer.\n";
+
+#[test]
+fn downloads_resume_after_the_connection_drops() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let download = |id: &str| {
+        app.invoke(
+            "download_attachment",
+            json!({ "downloadId": id, "ticketId": 101, "filename": "druckbild.txt" }),
+        )
+    };
+    let part = app
+        .app
+        .path()
+        .app_cache_dir()
+        .unwrap()
+        .join("open-files/partial/mock-1/101/druckbild.txt.part");
+
+    server.fail("getDataUrl", Fault::Truncate(10), 1);
+    assert!(download("first").is_err());
+    assert_eq!(std::fs::read(&part).unwrap(), &ATTACHMENT.as_bytes()[..10]);
+
+    // Only the rest is asked for, and the whole file checks out
+    let file = download("second").unwrap();
+    assert_eq!(
+        server.requests().last(),
+        Some(&("getDataUrl".to_string(), 206))
+    );
+    assert_eq!(file["size"], ATTACHMENT.len());
+    assert_eq!(
+        std::fs::read_to_string(file["path"].as_str().unwrap()).unwrap(),
+        ATTACHMENT
+    );
+    let sha256: String = Sha256::digest(ATTACHMENT)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert_eq!(file["sha256"], sha256);
+    assert!(!part.exists());
+}
+
+#[test]
+fn downloads_that_fail_the_checksum_are_discarded() {
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+    let download = |id: &str| {
+        app.invoke(
+            "download_attachment",
+            json!({ "downloadId": id, "ticketId": 101, "filename": "druckbild.txt" }),
+        )
+    };
+
+    server.fail("getDataUrl", Fault::Corrupt, 1);
+    assert_eq!(
+        download("corrupt"),
+        Err(json!("Checksum mismatch for druckbild.txt"))
+    );
+    let partial = app
+        .app
+        .path()
+        .app_cache_dir()
+        .unwrap()
+        .join("open-files/partial/mock-1/101");
+    assert_eq!(std::fs::read_dir(&partial).unwrap().count(), 0);
+
+    // Nothing of the bad copy is resumed
+    let file = download("again").unwrap();
+    assert_eq!(
+        server.requests().last(),
+        Some(&("getDataUrl".to_string(), 200))
+    );
+    assert_eq!(
+        std::fs::read_to_string(file["path"].as_str().unwrap()).unwrap(),
+        ATTACHMENT
+    );
+}
+
+#[test]
+fn attachments_are_checked_before_upload() {
+    const MB: u64 = 1024 * 1024;
+    let app = TestApp::new();
+    let dir = app.app.path().app_cache_dir().unwrap().join("attachments");
+    std::fs::create_dir_all(&dir).unwrap();
+    // Sparse, so the large ones take no space
+    let file = |name: &str, size: u64| {
+        let path = dir.join(name);
+        std::fs::File::create(&path).unwrap().set_len(size).unwrap();
+        path
+    };
+    let inspect = |paths: &[PathBuf]| app.invoke("inspect_attachments", json!({ "paths": paths }));
+
+    let attachments = inspect(&[file("notizen.txt", 1024), file("scan.pdf", 2048)]).unwrap();
+    assert_eq!(attachments[0]["name"], "notizen.txt");
+    assert_eq!(attachments[0]["size"], 1024);
+    assert_eq!(attachments[0]["mime"], "text/plain");
+    assert_eq!(attachments[1]["mime"], "application/pdf");
+
+    // One file that would be refused fails the whole selection
+    let notes = file("notizen.txt", 1024);
+    for (path, error) in [
+        (file("leer.txt", 0), "leer.txt is empty"),
+        (
+            file("setup.exe", 10),
+            "setup.exe: files of type application/octet-stream cannot be attached",
+        ),
+        (file("daten.zzqx", 10), "daten.zzqx: unknown file type"),
+        (dir.clone(), "attachments is not a file"),
+        (
+            file("video.mp4", 50 * MB + 1),
+            "video.mp4 is 50.0 MB, attachments may be at most 50.0 MB",
+        ),
+    ] {
+        assert_eq!(inspect(&[notes.clone(), path]), Err(json!(error)));
+    }
+
+    let videos: Vec<PathBuf> = (1..=5)
+        .map(|n| file(&format!("video-{}.mp4", n), 45 * MB))
+        .collect();
+    assert_eq!(
+        inspect(&videos),
+        Err(json!(
+            "Attachments add up to 225.0 MB, at most 200.0 MB can be sent with one ticket"
+        ))
+    );
+}
+
+#[test]
+fn searches_what_was_synced() {
+    let app = TestApp::new();
+    let server = mock_server();
+    let account = app.sign_in(&server, "anna@example.com");
+    let filter = json!({ "user_id": 1, "user_group_id": account["user"]["user_group_id"] });
+    app.invoke("get_tickets", json!({ "filter": filter }))
+        .unwrap();
+    app.invoke("get_ticket_messages", json!({ "ticketId": 102 }))
+        .unwrap();
+    app.invoke("get_wiki_data", json!({})).unwrap();
+    let search = |args: Value| app.invoke("search", args).unwrap();
+
+    // Stemmed, so the plural finds the ticket about one printer
+    let hits = search(json!({ "query": "Druckers" }));
+    assert_eq!(hits[0]["kind"], "ticket");
+    assert_eq!(hits[0]["ticketId"], 101);
+    assert!(hits[0]["snippet"]
+        .as_str()
+        .unwrap()
+        .contains("<b>Drucker</b>"));
+
+    let hits = search(json!({ "query": "vpn", "kinds": ["wiki"] }));
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+    assert_eq!(hits[0]["title"], "VPN-Verbindung bricht ab");
+    assert_eq!(hits[0]["ticketId"], Value::Null);
+
+    // Narrowed down to one ticket
+    let hits = search(json!({ "query": "vpn", "ticketId": 102 }));
+    assert!(!hits.as_array().unwrap().is_empty());
+    assert!(hits
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|hit| hit["ticketId"] == 102));
+
+    let hits = search(json!({ "query": "nachmittags", "kinds": ["message"] }));
+    assert_eq!(hits[0]["id"], 801);
+    assert_eq!(hits[0]["ticketId"], 102);
+
+    // Half typed queries still find something, blank ones nothing
+    assert!(!search(json!({ "query": "\"drucker" }))
+        .as_array()
+        .unwrap()
+        .is_empty());
+    assert_eq!(search(json!({ "query": "   " })), json!([]));
+}
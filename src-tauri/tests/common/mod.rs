@@ -0,0 +1,195 @@
+//! The app on tauri's mock runtime, shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Once};
+
+use keyring::credential::{Credential, CredentialBuilderApi};
+use serde_json::{json, Value};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, Listener, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use ticketbase_mock::{Fixtures, MockServer};
+
+/// Fails every keyring access, so sessions go to the encrypted file fallback
+/// instead of the developer's real keychain.
+struct NoKeyring;
+
+impl CredentialBuilderApi for NoKeyring {
+    fn build(&self, _: Option<&str>, _: &str, _: &str) -> keyring::Result<Box<Credential>> {
+        Err(keyring::Error::NoStorageAccess(
+            "no keyring in tests".into(),
+        ))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The app with its main window. Every instance gets its own identifier and
+/// with it its own data dirs, which are removed again on drop.
+pub struct TestApp {
+    pub app: App<MockRuntime>,
+    pub window: WebviewWindow<MockRuntime>,
+    /// Set by `restart`, which hands the data dirs to the next instance.
+    keep_data: bool,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        Self::open(format!(
+            "dev.jackolix.ticketbase-desktop.test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    /// Quits and starts again on the same data dirs, like the next launch.
+    pub fn restart(mut self) -> Self {
+        let identifier = self.app.config().identifier.clone();
+        self.keep_data = true;
+        drop(self);
+        Self::open(identifier)
+    }
+
+    fn open(identifier: String) -> Self {
+        static KEYRING: Once = Once::new();
+        KEYRING.call_once(|| keyring::set_default_credential_builder(Box::new(NoKeyring)));
+
+        let mut context = mock_context(noop_assets());
+        context.config_mut().identifier = identifier;
+        // The updater refuses to start without its public key
+        let config: Value = serde_json::from_str(include_str!("../../tauri.conf.json")).unwrap();
+        context
+            .config_mut()
+            .plugins
+            .0
+            .insert("updater".to_string(), config["plugins"]["updater"].clone());
+
+        let app = ticketsystem_desktop_lib::builder::<MockRuntime>()
+            .build(context)
+            .expect("app builds on the mock runtime");
+        let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+            .build()
+            .unwrap();
+        Self {
+            app,
+            window,
+            keep_data: false,
+        }
+    }
+
+    /// Calls a command the way the frontend's `invoke` does.
+    pub fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Value> {
+        get_ipc_response(
+            &self.window,
+            InvokeRequest {
+                cmd: cmd.to_string(),
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                url: "tauri://localhost".parse().unwrap(),
+                body: InvokeBody::Json(args),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            },
+        )
+        .map(|body| body.deserialize().unwrap())
+    }
+
+    /// Payloads of `event` from now on.
+    pub fn events(&self, event: &str) -> mpsc::Receiver<Value> {
+        let (sender, receiver) = mpsc::channel();
+        self.app.listen_any(event, move |event| {
+            let _ = sender.send(serde_json::from_str(event.payload()).unwrap());
+        });
+        receiver
+    }
+
+    pub fn ticket_windows(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .app
+            .webview_windows()
+            .into_keys()
+            .filter(|label| label.starts_with("ticket-"))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    /// Points a profile at `server` and signs `email` in as the login screen
+    /// does, returning the new account.
+    pub fn sign_in(&self, server: &MockServer, email: &str) -> Value {
+        self.invoke(
+            "save_server_profile",
+            json!({ "profile": { "id": "mock", "name": "Mock", "baseUrl": server.url() } }),
+        )
+        .unwrap();
+        self.invoke(
+            "login",
+            json!({ "email": email, "password": "secret", "profileId": "mock" }),
+        )
+        .unwrap()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        if self.keep_data {
+            return;
+        }
+        let path = self.app.path();
+        for dir in [
+            path.app_data_dir(),
+            path.app_local_data_dir(),
+            path.app_config_dir(),
+            path.app_cache_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+pub fn mock_server() -> MockServer {
+    tauri::async_runtime::block_on(MockServer::start("127.0.0.1:0", Fixtures::bundled()))
+        .expect("mock server starts")
+}
+
+/// A dbus-daemon of our own, stopped on drop.
+pub struct Bus {
+    daemon: Child,
+    pub address: String,
+}
+
+impl Bus {
+    pub fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
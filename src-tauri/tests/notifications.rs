@@ -0,0 +1,141 @@
+//! Clicks on native notifications, through a fake notification daemon on a
+//! private dbus-daemon; without dbus-daemon installed the test is skipped.
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tauri::async_runtime::block_on;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::OwnedValue;
+use zbus::{interface, Connection};
+
+use common::{mock_server, Bus, TestApp};
+
+const PATH: &str = "/org/freedesktop/Notifications";
+
+#[derive(Default)]
+struct Daemon {
+    /// Actions of each shown notification; its id is the index plus one.
+    shown: Vec<Vec<String>>,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl Daemon {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &mut self,
+        _app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        _summary: String,
+        _body: String,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        self.shown.push(actions);
+        self.shown.len() as u32
+    }
+
+    #[zbus(signal)]
+    async fn action_invoked(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn notification_closed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+}
+
+/// Registers the fake daemon on `bus`.
+fn daemon(bus: &Bus) -> Connection {
+    block_on(async {
+        zbus::connection::Builder::address(bus.address.as_str())?
+            .name("org.freedesktop.Notifications")?
+            .serve_at(PATH, Daemon::default())?
+            .build()
+            .await
+    })
+    .expect("fake notification daemon registers")
+}
+
+fn shown(daemon: &Connection) -> Vec<Vec<String>> {
+    block_on(async {
+        let daemon = daemon
+            .object_server()
+            .interface::<_, Daemon>(PATH)
+            .await
+            .unwrap();
+        let shown = daemon.get().await.shown.clone();
+        shown
+    })
+}
+
+/// Clicks notification `id`, or closes it without a click.
+fn signal(daemon: &Connection, id: u32, click: bool) {
+    block_on(async {
+        let daemon = daemon.object_server().interface::<_, Daemon>(PATH).await?;
+        match click {
+            true => Daemon::action_invoked(daemon.signal_emitter(), id, "default").await,
+            false => Daemon::notification_closed(daemon.signal_emitter(), id, 2).await,
+        }
+    })
+    .unwrap();
+}
+
+fn eventually(mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "condition not met in time");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn clicks_open_the_notified_ticket() {
+    let Some(bus) = Bus::start() else {
+        eprintln!("dbus-daemon not found, skipping");
+        return;
+    };
+    // The only test in this binary, so nothing else reads these meanwhile
+    std::env::set_var("DBUS_SYSTEM_BUS_ADDRESS", &bus.address);
+    std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus.address);
+    let daemon = daemon(&bus);
+
+    let app = TestApp::new();
+    let server = mock_server();
+    app.sign_in(&server, "anna@example.com");
+
+    for ticket_id in [Some(101), None, Some(102)] {
+        // One at a time, so the ids are known
+        let count = shown(&daemon).len();
+        app.invoke(
+            "show_notification",
+            json!({ "title": "Rückruf", "message": "Kunde wartet", "ticketId": ticket_id }),
+        )
+        .unwrap();
+        eventually(|| shown(&daemon).len() > count);
+    }
+    let actions = shown(&daemon);
+    assert_eq!(actions[0], ["default", "Open Ticket"]);
+    assert!(actions[1].is_empty());
+
+    signal(&daemon, 1, true);
+    eventually(|| app.ticket_windows() == ["ticket-101"]);
+
+    // Closed without a click, a late action is ignored
+    signal(&daemon, 3, false);
+    signal(&daemon, 3, true);
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(app.ticket_windows(), ["ticket-101"]);
+}
@@ -1,4 +1,5 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, ReactNode } from "react";
+import { listen } from "@tauri-apps/api/event";
 import "./App.css";
 import { AuthProvider, useAuth } from "./contexts/AuthContext";
 import { ThemeProvider } from "./contexts/ThemeContext";
@@ -16,6 +17,9 @@ import { Reports } from "./components/reports/Reports";
 import { WikiSearch } from "./components/WikiSearch";
 import { TodayView } from "./components/today/TodayView";
 import { TicketWindow } from "./components/tickets/TicketWindow";
+import { QuickOpenWindow } from "./components/tickets/QuickOpenWindow";
+import { AwayGapDialog } from "./components/tickets/AwayGapDialog";
+import { OpenTicketDialog } from "./components/tickets/OpenTicketDialog";
 import { UpdateNotification } from "./components/ui/UpdateNotification";
 import { DebugPanel } from "./components/debug/DebugPanel";
 import { Toaster } from "./components/ui/sonner";
@@ -29,10 +33,16 @@ import {
 } from "@/components/ui/breadcrumb";
 import { Ticket } from "./types/api";
 import { apiClient } from "./lib/api";
-import { WindowManager } from "./lib/windowManager";
+
+// Remounts the ticket views when another account becomes active, so nothing
+// of the previous account's state carries over
+function AccountTicketsProvider({ children }: { children: ReactNode }) {
+  const { activeAccount } = useAuth();
+  return <TicketsProvider key={activeAccount?.id ?? 'none'}>{children}</TicketsProvider>;
+}
 
 function AppContent() {
-  const { isAuthenticated, isLoading } = useAuth();
+  const { isAuthenticated, isLoading, isAddingAccount } = useAuth();
   const { setActiveTab, tickets, allTicketsForSearch, filterState } = useTickets();
   const [currentView, setCurrentView] = useState("dashboard");
   const [selectedTicket, setSelectedTicket] = useState<Ticket | null>(null);
@@ -58,9 +68,6 @@ function AppContent() {
 
     if (isAuthenticated) {
       checkUrlForTicket();
-
-      // Clean up old temp files on app startup
-      WindowManager.cleanupOldTempFiles();
     }
   }, [isAuthenticated]);
 
@@ -77,60 +84,23 @@ function AppContent() {
     return () => window.removeEventListener('keydown', handleKeyDown);
   }, [showDebugPanel]);
 
-  // Cleanup temp files on app shutdown
+  // "New ticket" in the tray shows this window on the new ticket form
   useEffect(() => {
-    const handleBeforeUnload = () => {
-      WindowManager.cleanupAllTempFiles();
+    const unlisten = listen('tray://new-ticket', () => handleViewChange("new-ticket"));
+    return () => {
+      unlisten.then((fn) => fn());
     };
-
-    window.addEventListener('beforeunload', handleBeforeUnload);
-    return () => window.removeEventListener('beforeunload', handleBeforeUnload);
   }, []);
 
   const loadTicketById = async (ticketId: number) => {
     setIsLoadingTicket(true);
     try {
-      const response = await apiClient.getTicketById(ticketId);
-      if (response.result === 'success' && response.tickets) {
-        const rawTicket = response.tickets;
-        
-        const transformedTicket: Ticket = {
-          id: rawTicket.id,
-          description: rawTicket.description || '',
-          status: rawTicket.status?.name || '',
-          status_id: rawTicket.status_id || 0,
-          summary: rawTicket.summary || '',
-          ticketCreator: rawTicket.userone?.name || '',
-          ticketUser: rawTicket.ticketuser?.name || '',
-          ticketUserPhone: rawTicket.ticketuser?.phone || '',
-          ticketTerminatedUser: '',
-          attachments: [],
-          subject: rawTicket.servicedetail?.name || '',
-          priority: rawTicket.priority || '',
-          index: rawTicket.priority_index || 0,
-          my_ticket_id: rawTicket.my_ticket_id || 0,
-          location_id: rawTicket.location_id || 0,
-          company: {
-            id: rawTicket.companyone?.id || 0,
-            name: rawTicket.companyone?.name || '',
-            number: rawTicket.companyone?.number || '',
-            companyMail: rawTicket.companyone?.email || '',
-            companyPhone: rawTicket.companyone?.phone || '',
-            companyZip: rawTicket.companyone?.zip || '',
-            companyAdress: rawTicket.companyone?.address || '',
-          },
-          dyn_template_id: rawTicket.dyn_template_id || 0,
-          created_at: rawTicket.created_at || '',
-          ticket_start: '',
-          ticketMessagesCount: 0,
-          template_data: rawTicket.template_data || '',
-          pool_name: '',
-        };
-        
-        setSelectedTicket(transformedTicket);
+      const ticket = await apiClient.getTicketById(ticketId);
+      if (ticket) {
+        setSelectedTicket(ticket);
         setCurrentView("tickets");
       } else {
-        console.error('Failed to load ticket:', response);
+        console.error('Ticket not found:', ticketId);
         // Fallback to dashboard if ticket not found
         setCurrentView("dashboard");
         setSelectedTicket(null);
@@ -208,7 +178,7 @@ function AppContent() {
     );
   }
 
-  if (!isAuthenticated) {
+  if (!isAuthenticated || isAddingAccount) {
     return (
       <>
         <CustomLoginForm />
@@ -280,6 +250,8 @@ function AppContent() {
         </div>
       </SidebarInset>
       <UpdateNotification />
+      <AwayGapDialog />
+      <OpenTicketDialog />
       <DebugPanel
         isVisible={showDebugPanel && process.env.NODE_ENV === 'development'}
         onClose={() => setShowDebugPanel(false)}
@@ -299,6 +271,27 @@ function App() {
   const ticketMatch = hash.match(/^#\/ticket\/(\d+)$/);
   const ticketId = ticketMatch ? ticketMatch[1] : null;
   
+  // The small windows of the global shortcuts
+  const params = new URLSearchParams(window.location.search);
+  if (params.get('newTicketWindow') === 'true' || params.get('quickOpen') === 'true') {
+    return (
+      <ThemeProvider>
+        <UpdaterProvider>
+          <AuthProvider>
+            {params.get('quickOpen') === 'true' ? (
+              <QuickOpenWindow />
+            ) : (
+              <div className="min-h-screen bg-background p-4">
+                <NewTicketForm />
+              </div>
+            )}
+            <Toaster />
+          </AuthProvider>
+        </UpdaterProvider>
+      </ThemeProvider>
+    );
+  }
+
   if (isTicketWindow && ticketId) {
     return (
       <ThemeProvider>
@@ -307,6 +300,7 @@ function App() {
             <TicketsProvider>
               <NotificationProvider>
                 <TicketWindow ticketId={ticketId} />
+                <AwayGapDialog />
                 <Toaster />
               </NotificationProvider>
             </TicketsProvider>
@@ -320,11 +314,11 @@ function App() {
     <ThemeProvider>
       <UpdaterProvider>
         <AuthProvider>
-          <TicketsProvider>
+          <AccountTicketsProvider>
             <NotificationProvider>
               <AppContent />
             </NotificationProvider>
-          </TicketsProvider>
+          </AccountTicketsProvider>
         </AuthProvider>
       </UpdaterProvider>
     </ThemeProvider>
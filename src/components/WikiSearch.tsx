@@ -25,6 +25,7 @@ export function WikiSearch() {
   const [articles, setArticles] = useState<WikiArticle[]>([]);
   const [searchQuery, setSearchQuery] = useState('');
   const [debouncedQuery, setDebouncedQuery] = useState('');
+  const [rankedIds, setRankedIds] = useState<number[]>([]);
   const [isLoading, setIsLoading] = useState(true);
   const [selectedArticle, setSelectedArticle] = useState<WikiArticle | null>(null);
 
@@ -43,27 +44,52 @@ export function WikiSearch() {
     };
   }, [searchQuery]);
 
-  // Memoized filtered articles
+  // Article ids ranked by the full-text index, which also matches word forms
+  useEffect(() => {
+    if (debouncedQuery.trim() === '') {
+      setRankedIds([]);
+      return;
+    }
+
+    let cancelled = false;
+    apiClient.search(debouncedQuery, { kinds: ['wiki'] })
+      .then((hits) => {
+        if (!cancelled) setRankedIds(hits.map((hit) => hit.id));
+      })
+      .catch((error) => console.error('Failed to search wiki index:', error));
+
+    return () => {
+      cancelled = true;
+    };
+  }, [debouncedQuery]);
+
+  // Memoized filtered articles, best index matches first
   const filteredArticles = useMemo(() => {
     if (debouncedQuery.trim() === '') {
       return articles;
     }
     const query = debouncedQuery.toLowerCase();
-    return articles.filter(
+    const ranked = rankedIds
+      .map(id => articles.find(article => article.id === id))
+      .filter((article): article is WikiArticle => article !== undefined);
+    const substringMatches = articles.filter(
       article =>
-        article.title.toLowerCase().includes(query) ||
-        article.content.toLowerCase().includes(query) ||
-        article.category.toLowerCase().includes(query) ||
-        article.folder.toLowerCase().includes(query)
+        !rankedIds.includes(article.id) && (
+          article.title.toLowerCase().includes(query) ||
+          article.content.toLowerCase().includes(query) ||
+          article.category.toLowerCase().includes(query) ||
+          article.folder.toLowerCase().includes(query)
+        )
     );
-  }, [debouncedQuery, articles]);
+    return [...ranked, ...substringMatches];
+  }, [debouncedQuery, articles, rankedIds]);
 
   const fetchWikiData = useCallback(async (forceRefresh = false) => {
     const cacheKey = 'wiki_articles';
 
     // Try to get from cache first
     if (!forceRefresh) {
-      const cachedData = cache.get<WikiArticle[]>(cacheKey);
+      const cachedData = await cache.get<WikiArticle[]>(cacheKey);
       if (cachedData) {
         setArticles(cachedData);
         setIsLoading(false);
@@ -6,6 +6,7 @@ import { Input } from "@/components/ui/input"
 import { Label } from "@/components/ui/label"
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { useAuth } from '@/contexts/AuthContext';
+import { ServerProfileSelect } from './ServerProfileSelect';
 import { Eye, EyeOff, Loader2, AlertCircle } from 'lucide-react';
 
 export function CustomLoginForm({
@@ -16,7 +17,8 @@ export function CustomLoginForm({
   const [password, setPassword] = useState('');
   const [showPassword, setShowPassword] = useState(false);
   const [error, setError] = useState('');
-  const { login, isLoading } = useAuth();
+  const [profileId, setProfileId] = useState('');
+  const { login, isLoading, isAddingAccount, setIsAddingAccount } = useAuth();
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -27,7 +29,7 @@ export function CustomLoginForm({
       return;
     }
 
-    const success = await login(email, password);
+    const success = await login(email, password, profileId || undefined);
     if (!success) {
       setError('Invalid email or password');
     }
@@ -49,9 +51,11 @@ export function CustomLoginForm({
                     <path d="M20 6L9 17l-5-5" stroke="currentColor" strokeWidth="2" fill="none" strokeLinecap="round" strokeLinejoin="round"/>
                   </svg>
                 </div>
-                <h1 className="text-2xl font-bold">Welcome back</h1>
+                <h1 className="text-2xl font-bold">{isAddingAccount ? 'Add account' : 'Welcome back'}</h1>
                 <p className="text-muted-foreground text-balance">
-                  Sign in to your Ticket System account
+                  {isAddingAccount
+                    ? 'Sign in to another account, you stay signed in to the others'
+                    : 'Sign in to your Ticket System account'}
                 </p>
               </div>
 
@@ -62,6 +66,8 @@ export function CustomLoginForm({
                 </Alert>
               )}
 
+              <ServerProfileSelect value={profileId} onChange={setProfileId} disabled={isLoading} />
+
               <div className="grid gap-3">
                 <Label htmlFor="email">Email</Label>
                 <Input
@@ -119,6 +125,18 @@ export function CustomLoginForm({
                   'Sign in'
                 )}
               </Button>
+
+              {isAddingAccount && (
+                <Button
+                  type="button"
+                  variant="outline"
+                  className="w-full"
+                  onClick={() => setIsAddingAccount(false)}
+                  disabled={isLoading}
+                >
+                  Cancel
+                </Button>
+              )}
             </div>
           </form>
         </CardContent>
@@ -0,0 +1,211 @@
+import { useState, useEffect } from 'react';
+import { open } from '@tauri-apps/plugin-dialog';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Alert, AlertDescription } from '@/components/ui/alert';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { apiClient } from '@/lib/api';
+import { ServerProfile } from '@/types/api';
+import { Server, Pencil, Trash2, Plus, AlertCircle } from 'lucide-react';
+
+const EMPTY_PROFILE: ServerProfile = { id: '', name: '', baseUrl: 'https://', caCertificate: undefined };
+
+interface ServerProfileSelectProps {
+  value: string;
+  onChange: (id: string) => void;
+  disabled?: boolean;
+}
+
+// Picks the Ticketbase server to sign in to and edits the list of servers
+export function ServerProfileSelect({ value, onChange, disabled }: ServerProfileSelectProps) {
+  const [profiles, setProfiles] = useState<ServerProfile[]>([]);
+  const [isManaging, setIsManaging] = useState(false);
+  const [editing, setEditing] = useState<ServerProfile | null>(null);
+  const [error, setError] = useState('');
+
+  const loadProfiles = async () => {
+    try {
+      const loaded = await apiClient.getServerProfiles();
+      setProfiles(loaded.profiles);
+      if (!value || !loaded.profiles.some(profile => profile.id === value)) {
+        onChange(loaded.active);
+      }
+    } catch (error) {
+      console.error('Failed to load server profiles:', error);
+    }
+  };
+
+  useEffect(() => {
+    loadProfiles();
+  }, []);
+
+  const handleBrowseCertificate = async () => {
+    const selected = await open({
+      multiple: false,
+      directory: false,
+      filters: [{ name: 'PEM certificates', extensions: ['pem', 'crt', 'cer'] }],
+    });
+    if (typeof selected === 'string' && editing) {
+      setEditing({ ...editing, caCertificate: selected });
+    }
+  };
+
+  const handleSave = async () => {
+    if (!editing) return;
+    try {
+      const saved = await apiClient.saveServerProfile(editing);
+      setEditing(null);
+      setError('');
+      await loadProfiles();
+      onChange(saved.id);
+    } catch (error) {
+      setError(error instanceof Error ? error.message : String(error));
+    }
+  };
+
+  const handleDelete = async (id: string) => {
+    try {
+      await apiClient.deleteServerProfile(id);
+      setError('');
+      await loadProfiles();
+    } catch (error) {
+      setError(error instanceof Error ? error.message : String(error));
+    }
+  };
+
+  return (
+    <div className="grid gap-3">
+      <Label htmlFor="server">Server</Label>
+      <div className="flex gap-2">
+        <Select value={value} onValueChange={onChange} disabled={disabled}>
+          <SelectTrigger id="server" className="flex-1">
+            <SelectValue placeholder="Select a server" />
+          </SelectTrigger>
+          <SelectContent>
+            {profiles.map(profile => (
+              <SelectItem key={profile.id} value={profile.id}>
+                {profile.name}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+        <Button
+          type="button"
+          variant="outline"
+          size="icon"
+          onClick={() => setIsManaging(true)}
+          disabled={disabled}
+          title="Manage servers"
+        >
+          <Server className="h-4 w-4" />
+        </Button>
+      </div>
+
+      <Dialog
+        open={isManaging}
+        onOpenChange={(open) => {
+          setIsManaging(open);
+          setEditing(null);
+          setError('');
+        }}
+      >
+        <DialogContent className="sm:max-w-[500px]">
+          <DialogHeader>
+            <DialogTitle>Servers</DialogTitle>
+            <DialogDescription>
+              Ticketbase instances you can sign in to, e.g. staging or an on-premises server.
+            </DialogDescription>
+          </DialogHeader>
+
+          {error && (
+            <Alert variant="destructive">
+              <AlertCircle className="h-4 w-4" />
+              <AlertDescription>{error}</AlertDescription>
+            </Alert>
+          )}
+
+          {editing ? (
+            <div className="space-y-4 py-2">
+              <div className="space-y-2">
+                <Label htmlFor="server-name">Name</Label>
+                <Input
+                  id="server-name"
+                  value={editing.name}
+                  onChange={(e) => setEditing({ ...editing, name: e.target.value })}
+                  placeholder="Staging"
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="server-url">API URL</Label>
+                <Input
+                  id="server-url"
+                  value={editing.baseUrl}
+                  onChange={(e) => setEditing({ ...editing, baseUrl: e.target.value })}
+                  placeholder="https://tickets.example.com/api"
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="server-ca">CA certificate (optional)</Label>
+                <div className="flex gap-2">
+                  <Input
+                    id="server-ca"
+                    value={editing.caCertificate ?? ''}
+                    onChange={(e) => setEditing({ ...editing, caCertificate: e.target.value || undefined })}
+                    placeholder="Path to a PEM file"
+                  />
+                  <Button type="button" variant="outline" onClick={handleBrowseCertificate}>
+                    Browse
+                  </Button>
+                </div>
+              </div>
+              <DialogFooter>
+                <Button type="button" variant="outline" onClick={() => setEditing(null)}>
+                  Cancel
+                </Button>
+                <Button type="button" onClick={handleSave}>
+                  Save
+                </Button>
+              </DialogFooter>
+            </div>
+          ) : (
+            <div className="space-y-2 py-2">
+              {profiles.map(profile => (
+                <div key={profile.id} className="flex items-center gap-2 p-2 border rounded-md">
+                  <div className="flex-1 min-w-0">
+                    <div className="text-sm font-medium truncate">{profile.name}</div>
+                    <div className="text-xs text-muted-foreground truncate">{profile.baseUrl}</div>
+                  </div>
+                  <Button type="button" variant="ghost" size="icon" onClick={() => setEditing(profile)}>
+                    <Pencil className="h-4 w-4" />
+                  </Button>
+                  <Button
+                    type="button"
+                    variant="ghost"
+                    size="icon"
+                    onClick={() => handleDelete(profile.id)}
+                    disabled={profile.id === value}
+                  >
+                    <Trash2 className="h-4 w-4" />
+                  </Button>
+                </div>
+              ))}
+              <Button type="button" variant="outline" className="w-full" onClick={() => setEditing(EMPTY_PROFILE)}>
+                <Plus className="h-4 w-4 mr-2" />
+                Add server
+              </Button>
+            </div>
+          )}
+        </DialogContent>
+      </Dialog>
+    </div>
+  );
+}
@@ -42,7 +42,7 @@ export function Dashboard({ onTicketSelect }: DashboardProps) {
 
     // Try to get from cache first
     if (!forceRefresh) {
-      const cachedData = cache.get<typeof tickets>(cacheKey);
+      const cachedData = await cache.get<typeof tickets>(cacheKey);
       if (cachedData) {
         setTickets(cachedData);
 
@@ -1,10 +1,13 @@
 import {
   BadgeCheck,
   Bell,
+  Check,
   ChevronsUpDown,
   CreditCard,
   LogOut,
   Sparkles,
+  UserPlus,
+  X,
 } from "lucide-react"
 
 import {
@@ -27,6 +30,7 @@ import {
   SidebarMenuItem,
   useSidebar,
 } from "@/components/ui/sidebar"
+import { useAuth } from "@/contexts/AuthContext"
 
 export function NavUser({
   user,
@@ -38,6 +42,7 @@ export function NavUser({
   }
 }) {
   const { isMobile } = useSidebar()
+  const { accounts, switchAccount, removeAccount, setIsAddingAccount } = useAuth()
 
   return (
     <SidebarMenu>
@@ -79,6 +84,41 @@ export function NavUser({
               </div>
             </DropdownMenuLabel>
             <DropdownMenuSeparator />
+            <DropdownMenuLabel className="text-xs text-muted-foreground">Accounts</DropdownMenuLabel>
+            <DropdownMenuGroup>
+              {accounts.map(account => (
+                <DropdownMenuItem
+                  key={account.id}
+                  onSelect={() => {
+                    if (!account.active) switchAccount(account.id).catch(console.error)
+                  }}
+                >
+                  {account.active ? <Check /> : <span className="size-4" />}
+                  <div className="grid flex-1 text-left text-sm leading-tight">
+                    <span className="truncate">{account.user.name}</span>
+                    <span className="truncate text-xs text-muted-foreground">{account.server}</span>
+                  </div>
+                  {!account.active && (
+                    <button
+                      type="button"
+                      title="Sign out of this account"
+                      className="text-muted-foreground hover:text-foreground"
+                      onClick={(e) => {
+                        e.stopPropagation()
+                        removeAccount(account.id).catch(console.error)
+                      }}
+                    >
+                      <X className="size-4" />
+                    </button>
+                  )}
+                </DropdownMenuItem>
+              ))}
+              <DropdownMenuItem onSelect={() => setIsAddingAccount(true)}>
+                <UserPlus />
+                Add account
+              </DropdownMenuItem>
+            </DropdownMenuGroup>
+            <DropdownMenuSeparator />
             <DropdownMenuGroup>
               <DropdownMenuItem>
                 <Sparkles />
@@ -0,0 +1,117 @@
+import { useState } from 'react';
+import { save } from '@tauri-apps/plugin-dialog';
+import { Button } from '@/components/ui/button';
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuLabel,
+  DropdownMenuRadioGroup,
+  DropdownMenuRadioItem,
+  DropdownMenuSeparator,
+  DropdownMenuTrigger,
+} from '@/components/ui/dropdown-menu';
+import { apiClient } from '@/lib/api';
+import { CsvEncoding, ReportExportOptions } from '@/types/api';
+import { Download, Loader2 } from 'lucide-react';
+
+const extensions: Record<ReportExportOptions['format'], { label: string; extension: string }> = {
+  csv: { label: 'CSV', extension: 'csv' },
+  xlsx: { label: 'Excel (XLSX)', extension: 'xlsx' },
+  pdf: { label: 'PDF', extension: 'pdf' },
+};
+
+interface CsvDialect {
+  delimiter: string;
+  encoding: CsvEncoding;
+}
+
+const loadDialect = (): CsvDialect => {
+  try {
+    const stored = localStorage.getItem('report-csv-dialect');
+    if (stored) {
+      return JSON.parse(stored);
+    }
+  } catch {
+    // Fall back to the default
+  }
+  return { delimiter: ',', encoding: 'utf-8-bom' };
+};
+
+interface ReportExportProps {
+  report: ReportExportOptions['report'];
+  // Name of the saved file, without date or extension
+  filename: string;
+  startDate?: string;
+  endDate?: string;
+  month?: number;
+  onError: (error: string) => void;
+}
+
+// Exports a report as CSV, Excel or PDF. The CSV delimiter and encoding are
+// remembered, since they depend on the Excel the file is opened in.
+export function ReportExport({ report, filename, startDate, endDate, month, onError }: ReportExportProps) {
+  const [dialect, setDialect] = useState<CsvDialect>(loadDialect);
+  const [isExporting, setIsExporting] = useState(false);
+
+  const updateDialect = (update: Partial<CsvDialect>) => {
+    const next = { ...dialect, ...update };
+    setDialect(next);
+    localStorage.setItem('report-csv-dialect', JSON.stringify(next));
+  };
+
+  const handleExport = async (format: ReportExportOptions['format']) => {
+    const { label, extension } = extensions[format];
+    const period = month ? `${new Date().getFullYear()}-${String(month).padStart(2, '0')}` : `${startDate}_${endDate}`;
+    const path = await save({
+      defaultPath: `${filename}_${period}.${extension}`,
+      filters: [{ name: label, extensions: [extension] }],
+    });
+    if (!path) {
+      return;
+    }
+
+    setIsExporting(true);
+    try {
+      await apiClient.exportReport({ report, startDate, endDate, month, format, path, csv: dialect });
+    } catch (error) {
+      console.error('Failed to export report:', error);
+      onError(`Failed to export report: ${error}`);
+    } finally {
+      setIsExporting(false);
+    }
+  };
+
+  return (
+    <DropdownMenu>
+      <DropdownMenuTrigger asChild>
+        <Button variant="outline" disabled={isExporting} className="flex items-center gap-2">
+          {isExporting ? <Loader2 className="h-4 w-4 animate-spin" /> : <Download className="h-4 w-4" />}
+          Export
+        </Button>
+      </DropdownMenuTrigger>
+      <DropdownMenuContent align="start" className="w-56">
+        <DropdownMenuItem onSelect={() => handleExport('xlsx')}>Excel (XLSX)</DropdownMenuItem>
+        <DropdownMenuItem onSelect={() => handleExport('pdf')}>PDF</DropdownMenuItem>
+        <DropdownMenuItem onSelect={() => handleExport('csv')}>CSV</DropdownMenuItem>
+        <DropdownMenuSeparator />
+        <DropdownMenuLabel>CSV delimiter</DropdownMenuLabel>
+        <DropdownMenuRadioGroup value={dialect.delimiter} onValueChange={(delimiter) => updateDialect({ delimiter })}>
+          <DropdownMenuRadioItem value="," onSelect={(e) => e.preventDefault()}>Comma</DropdownMenuRadioItem>
+          <DropdownMenuRadioItem value=";" onSelect={(e) => e.preventDefault()}>Semicolon</DropdownMenuRadioItem>
+          <DropdownMenuRadioItem value={'\t'} onSelect={(e) => e.preventDefault()}>Tab</DropdownMenuRadioItem>
+        </DropdownMenuRadioGroup>
+        <DropdownMenuSeparator />
+        <DropdownMenuLabel>CSV encoding</DropdownMenuLabel>
+        <DropdownMenuRadioGroup
+          value={dialect.encoding}
+          onValueChange={(encoding) => updateDialect({ encoding: encoding as CsvEncoding })}
+        >
+          <DropdownMenuRadioItem value="utf-8-bom" onSelect={(e) => e.preventDefault()}>UTF-8 for Excel</DropdownMenuRadioItem>
+          <DropdownMenuRadioItem value="utf-8" onSelect={(e) => e.preventDefault()}>UTF-8</DropdownMenuRadioItem>
+          <DropdownMenuRadioItem value="windows-1252" onSelect={(e) => e.preventDefault()}>Windows-1252</DropdownMenuRadioItem>
+        </DropdownMenuRadioGroup>
+      </DropdownMenuContent>
+    </DropdownMenu>
+  );
+}
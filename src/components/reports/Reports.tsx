@@ -7,10 +7,11 @@ import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@
 import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Badge } from '@/components/ui/badge';
 import { apiClient } from '@/lib/api';
+import { ReportExport } from './ReportExport';
+import { TimesheetExport } from './TimesheetExport';
 import { 
   Calendar,
   BarChart3,
-  Download,
   Loader2,
   TrendingUp,
   Users,
@@ -126,24 +127,6 @@ export function Reports() {
     }
   };
 
-  const exportToCSV = (data: any[], filename: string) => {
-    if (!data || data.length === 0) return;
-
-    const headers = Object.keys(data[0]).join(',');
-    const rows = data.map(row => Object.values(row).join(','));
-    const csv = [headers, ...rows].join('\n');
-
-    const blob = new Blob([csv], { type: 'text/csv' });
-    const url = window.URL.createObjectURL(blob);
-    const a = document.createElement('a');
-    a.href = url;
-    a.download = `${filename}_${new Date().toISOString().split('T')[0]}.csv`;
-    document.body.appendChild(a);
-    a.click();
-    document.body.removeChild(a);
-    window.URL.revokeObjectURL(url);
-  };
-
   const months = [
     { value: 1, label: 'January' },
     { value: 2, label: 'February' },
@@ -221,10 +204,11 @@ export function Reports() {
       </Card>
 
       <Tabs defaultValue="report4" className="space-y-6">
-        <TabsList className="grid w-full grid-cols-3">
+        <TabsList className="grid w-full grid-cols-4">
           <TabsTrigger value="report4">Customer Reviews</TabsTrigger>
           <TabsTrigger value="report5">Technician Statistics</TabsTrigger>
           <TabsTrigger value="topusers">Top Users</TabsTrigger>
+          <TabsTrigger value="timesheet">Timesheet</TabsTrigger>
         </TabsList>
 
         <TabsContent value="report4" className="space-y-4">
@@ -253,14 +237,13 @@ export function Reports() {
                   Generate Report
                 </Button>
                 {report4Data && (
-                  <Button
-                    variant="outline"
-                    onClick={() => exportToCSV(report4Data.report, 'customer_review_report')}
-                    className="flex items-center gap-2"
-                  >
-                    <Download className="h-4 w-4" />
-                    Export CSV
-                  </Button>
+                  <ReportExport
+                    report="report4"
+                    filename="customer_review_report"
+                    startDate={startDate}
+                    endDate={endDate}
+                    onError={setError}
+                  />
                 )}
               </div>
 
@@ -340,14 +323,13 @@ export function Reports() {
                   Generate Report
                 </Button>
                 {report5Data && (
-                  <Button
-                    variant="outline"
-                    onClick={() => exportToCSV(report5Data.report, 'technician_statistics_report')}
-                    className="flex items-center gap-2"
-                  >
-                    <Download className="h-4 w-4" />
-                    Export CSV
-                  </Button>
+                  <ReportExport
+                    report="report5"
+                    filename="technician_statistics_report"
+                    startDate={startDate}
+                    endDate={endDate}
+                    onError={setError}
+                  />
                 )}
               </div>
 
@@ -450,14 +432,14 @@ export function Reports() {
                   </Button>
                 </div>
                 {topUsers.length > 0 && (
-                  <Button
-                    variant="outline"
-                    onClick={() => exportToCSV(topUsers, 'top_users_report')}
-                    className="flex items-center gap-2"
-                  >
-                    <Download className="h-4 w-4" />
-                    Export CSV
-                  </Button>
+                  <div className="flex items-end">
+                    <ReportExport
+                      report="topUsers"
+                      filename="top_users_report"
+                      month={selectedMonth}
+                      onError={setError}
+                    />
+                  </div>
                 )}
               </div>
 
@@ -492,6 +474,9 @@ export function Reports() {
             </CardContent>
           </Card>
         </TabsContent>
+        <TabsContent value="timesheet" className="space-y-4">
+          <TimesheetExport startDate={startDate} endDate={endDate} />
+        </TabsContent>
       </Tabs>
     </div>
   );
@@ -0,0 +1,181 @@
+import { useState } from 'react';
+import { save } from '@tauri-apps/plugin-dialog';
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
+import { Button } from '@/components/ui/button';
+import { Label } from '@/components/ui/label';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import { apiClient } from '@/lib/api';
+import { TimesheetColumn, TimesheetOptions, TimesheetSummary } from '@/types/api';
+import { Clock, Download, Loader2 } from 'lucide-react';
+
+const columns: { id: TimesheetColumn; label: string }[] = [
+  { id: 'date', label: 'Date' },
+  { id: 'ticket', label: 'Ticket' },
+  { id: 'summary', label: 'Summary' },
+  { id: 'company', label: 'Company' },
+  { id: 'entries', label: 'Entries' },
+  { id: 'minutes', label: 'Minutes' },
+  { id: 'hours', label: 'Hours' },
+  { id: 'notes', label: 'Notes' },
+];
+
+const formats: Record<TimesheetOptions['format'], { label: string; extension: string }> = {
+  csv: { label: 'CSV', extension: 'csv' },
+  xlsx: { label: 'Excel (XLSX)', extension: 'xlsx' },
+  ics: { label: 'Calendar (ICS)', extension: 'ics' },
+};
+
+interface TimesheetExportProps {
+  startDate: string;
+  endDate: string;
+}
+
+// The user's booked time for the period, summed up per day, ticket and
+// company, or one calendar event per stretch of work
+export function TimesheetExport({ startDate, endDate }: TimesheetExportProps) {
+  const [format, setFormat] = useState<TimesheetOptions['format']>('xlsx');
+  const [selected, setSelected] = useState<TimesheetColumn[]>(['date', 'ticket', 'summary', 'company', 'minutes', 'hours']);
+  const [roundTo, setRoundTo] = useState('0');
+  const [roundMode, setRoundMode] = useState<'nearest' | 'up' | 'down'>('nearest');
+  const [isExporting, setIsExporting] = useState(false);
+  const [result, setResult] = useState<TimesheetSummary | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const toggleColumn = (column: TimesheetColumn) => {
+    setSelected((current) =>
+      current.includes(column)
+        ? current.filter((id) => id !== column)
+        // Keep the columns in their usual order
+        : columns.map((c) => c.id).filter((id) => id === column || current.includes(id))
+    );
+  };
+
+  const handleExport = async () => {
+    if (!startDate || !endDate) {
+      setError('Please select both start and end dates');
+      return;
+    }
+    const { extension, label } = formats[format];
+    const path = await save({
+      defaultPath: `timesheet_${startDate}_${endDate}.${extension}`,
+      filters: [{ name: label, extensions: [extension] }],
+    });
+    if (!path) {
+      return;
+    }
+
+    setIsExporting(true);
+    setError(null);
+    setResult(null);
+    try {
+      setResult(await apiClient.exportTimesheet({
+        startDate,
+        endDate,
+        format,
+        path,
+        columns: selected,
+        rounding: { minutes: parseInt(roundTo, 10), mode: roundMode },
+      }));
+    } catch (error) {
+      console.error('Failed to export timesheet:', error);
+      setError(String(error));
+    } finally {
+      setIsExporting(false);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Clock className="h-5 w-5" />
+          Timesheet
+        </CardTitle>
+        <p className="text-sm text-muted-foreground">
+          Your booked time for the selected period, per day, ticket and company
+        </p>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="grid grid-cols-1 md:grid-cols-3 gap-4">
+          <div className="space-y-2">
+            <Label>Format</Label>
+            <Select value={format} onValueChange={(value) => setFormat(value as TimesheetOptions['format'])}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {Object.entries(formats).map(([id, { label }]) => (
+                  <SelectItem key={id} value={id}>{label}</SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="space-y-2">
+            <Label>Round to</Label>
+            <Select value={roundTo} onValueChange={setRoundTo}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="0">Whole minutes</SelectItem>
+                <SelectItem value="5">5 minutes</SelectItem>
+                <SelectItem value="10">10 minutes</SelectItem>
+                <SelectItem value="15">15 minutes</SelectItem>
+                <SelectItem value="30">30 minutes</SelectItem>
+                <SelectItem value="60">1 hour</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="space-y-2">
+            <Label>Rounding</Label>
+            <Select value={roundMode} onValueChange={(value) => setRoundMode(value as 'nearest' | 'up' | 'down')}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="nearest">Nearest</SelectItem>
+                <SelectItem value="up">Up</SelectItem>
+                <SelectItem value="down">Down</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+        </div>
+
+        {format !== 'ics' && (
+          <div className="space-y-2">
+            <Label>Columns</Label>
+            <div className="flex flex-wrap gap-2">
+              {columns.map(({ id, label }) => (
+                <Button
+                  key={id}
+                  type="button"
+                  size="sm"
+                  variant={selected.includes(id) ? 'default' : 'outline'}
+                  onClick={() => toggleColumn(id)}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+          </div>
+        )}
+
+        {error && <p className="text-sm text-destructive">{error}</p>}
+        {result && (
+          <p className="text-sm text-muted-foreground">
+            Exported {result.rows} {format === 'ics' ? 'events' : 'rows'} with {(result.minutes / 60).toFixed(2)} hours.
+          </p>
+        )}
+
+        <Button
+          onClick={handleExport}
+          disabled={isExporting || (format !== 'ics' && selected.length === 0)}
+          className="flex items-center gap-2"
+        >
+          {isExporting ? <Loader2 className="h-4 w-4 animate-spin" /> : <Download className="h-4 w-4" />}
+          Export Timesheet
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,153 @@
+import { useState, useEffect } from 'react';
+import { save } from '@tauri-apps/plugin-dialog';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import { Alert, AlertDescription } from '@/components/ui/alert';
+import { apiClient } from '@/lib/api';
+import type { CalendarFeedStatus } from '@/types/api';
+import { CalendarDays, Copy, Download, RefreshCw, CheckCircle, AlertCircle } from 'lucide-react';
+
+// Scheduled tickets for Thunderbird, Evolution and other calendar apps:
+// subscribed to on this computer, or saved once as a file
+export function CalendarFeedCard() {
+  const [status, setStatus] = useState<CalendarFeedStatus | null>(null);
+  const [port, setPort] = useState('');
+  const [isBusy, setIsBusy] = useState(false);
+  const [message, setMessage] = useState<{ success: boolean; text: string } | null>(null);
+
+  const apply = (status: CalendarFeedStatus) => {
+    setStatus(status);
+    setPort(status.port ? String(status.port) : '');
+  };
+
+  useEffect(() => {
+    apiClient.getCalendarFeed()
+      .then(apply)
+      .catch((error) => setMessage({ success: false, text: String(error) }));
+  }, []);
+
+  if (!status) return null;
+
+  const run = async (action: () => Promise<string | void>) => {
+    setIsBusy(true);
+    setMessage(null);
+    try {
+      const text = await action();
+      if (text) {
+        setMessage({ success: true, text });
+      }
+    } catch (error) {
+      setMessage({ success: false, text: String(error) });
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  const configure = (enabled: boolean) =>
+    run(async () => apply(await apiClient.setCalendarFeed(enabled, parseInt(port, 10) || 0)));
+
+  const handleCopy = () =>
+    run(async () => {
+      await navigator.clipboard.writeText(status.url ?? '');
+      return 'Feed URL copied';
+    });
+
+  const handleReset = () =>
+    run(async () => {
+      apply(await apiClient.resetCalendarFeedUrl());
+      return 'New feed URL created. Subscriptions to the old one no longer update.';
+    });
+
+  const handleExport = () =>
+    run(async () => {
+      const path = await save({
+        defaultPath: 'appointments.ics',
+        filters: [{ name: 'Calendar (ICS)', extensions: ['ics'] }],
+      });
+      if (!path) return;
+      const count = await apiClient.exportAppointments(path);
+      return `Saved ${count} appointment${count === 1 ? '' : 's'}`;
+    });
+
+  const portChanged = status.enabled && port !== String(status.port);
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <CalendarDays className="h-5 w-5" />
+          Appointment Calendar
+        </CardTitle>
+        <CardDescription>
+          Your scheduled tickets, with company, location and phone, in your calendar app
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex items-center justify-between">
+          <div className="space-y-0.5">
+            <Label htmlFor="calendar-feed">Calendar feed</Label>
+            <p className="text-sm text-muted-foreground">
+              Served on this computer only, while the app is running
+            </p>
+          </div>
+          <Switch
+            id="calendar-feed"
+            checked={status.enabled}
+            disabled={isBusy}
+            onCheckedChange={configure}
+          />
+        </div>
+
+        <div className="space-y-2">
+          <Label htmlFor="calendar-port">Port</Label>
+          <div className="flex gap-2">
+            <Input
+              id="calendar-port"
+              type="number"
+              min={1024}
+              max={65535}
+              value={port}
+              onChange={(e) => setPort(e.target.value)}
+              placeholder="Any free port"
+            />
+            {portChanged && (
+              <Button type="button" variant="outline" disabled={isBusy} onClick={() => configure(true)}>
+                Apply
+              </Button>
+            )}
+          </div>
+        </div>
+
+        {status.url && (
+          <div className="space-y-2">
+            <Label htmlFor="calendar-url">Subscribe to</Label>
+            <div className="flex gap-2">
+              <Input id="calendar-url" value={status.url} readOnly className="font-mono text-xs" />
+              <Button type="button" variant="outline" size="icon" onClick={handleCopy} title="Copy">
+                <Copy className="h-4 w-4" />
+              </Button>
+              <Button type="button" variant="outline" size="icon" disabled={isBusy} onClick={handleReset} title="New URL">
+                <RefreshCw className="h-4 w-4" />
+              </Button>
+            </div>
+          </div>
+        )}
+
+        {message && (
+          <Alert variant={message.success ? 'default' : 'destructive'}>
+            {message.success ? <CheckCircle className="h-4 w-4" /> : <AlertCircle className="h-4 w-4" />}
+            <AlertDescription>{message.text}</AlertDescription>
+          </Alert>
+        )}
+
+        <Button type="button" variant="outline" disabled={isBusy} onClick={handleExport}>
+          <Download className="mr-2 h-4 w-4" />
+          Save as .ics File
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
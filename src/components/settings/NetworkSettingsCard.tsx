@@ -0,0 +1,182 @@
+import { useState, useEffect } from 'react';
+import { open } from '@tauri-apps/plugin-dialog';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Alert, AlertDescription } from '@/components/ui/alert';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import { apiClient } from '@/lib/api';
+import type { NetworkSettings, ProxyMode } from '@/types/api';
+import { Globe, Loader2, CheckCircle, AlertCircle } from 'lucide-react';
+
+export function NetworkSettingsCard() {
+  const [settings, setSettings] = useState<NetworkSettings | null>(null);
+  const [isSaving, setIsSaving] = useState(false);
+  const [message, setMessage] = useState<{ success: boolean; text: string } | null>(null);
+
+  useEffect(() => {
+    apiClient.getNetworkSettings()
+      .then(setSettings)
+      .catch((error) => setMessage({ success: false, text: (error as Error).message }));
+  }, []);
+
+  if (!settings) return null;
+
+  const update = (changes: Partial<NetworkSettings>) => setSettings({ ...settings, ...changes });
+
+  const handleBrowseBundle = async () => {
+    const selected = await open({
+      multiple: false,
+      directory: false,
+      filters: [{ name: 'PEM certificates', extensions: ['pem', 'crt', 'cer'] }],
+    });
+    if (typeof selected === 'string') {
+      update({ caBundle: selected });
+    }
+  };
+
+  const handleSave = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setIsSaving(true);
+    setMessage(null);
+    try {
+      await apiClient.setNetworkSettings(settings);
+      // The password is write-only; keep the stored one from now on
+      setSettings({ ...settings, proxyPassword: undefined });
+      setMessage({ success: true, text: 'Network settings saved' });
+    } catch (error) {
+      setMessage({ success: false, text: (error as Error).message });
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  const usesProxy = settings.proxyMode === 'manual' || settings.proxyMode === 'pac';
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Globe className="h-5 w-5" />
+          Network
+        </CardTitle>
+        <CardDescription>
+          Proxy and certificates for server requests, attachments and updates
+        </CardDescription>
+      </CardHeader>
+      <CardContent>
+        <form onSubmit={handleSave} className="space-y-4">
+          <div className="space-y-2">
+            <Label>Proxy</Label>
+            <Select
+              value={settings.proxyMode}
+              onValueChange={(value) => update({ proxyMode: value as ProxyMode })}
+            >
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="system">From environment (HTTPS_PROXY, NO_PROXY)</SelectItem>
+                <SelectItem value="none">No proxy</SelectItem>
+                <SelectItem value="manual">Manual</SelectItem>
+                <SelectItem value="pac">Automatic configuration (PAC)</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+
+          {settings.proxyMode === 'manual' && (
+            <>
+              <div className="space-y-2">
+                <Label htmlFor="proxy-url">Proxy URL</Label>
+                <Input
+                  id="proxy-url"
+                  value={settings.proxyUrl}
+                  onChange={(e) => update({ proxyUrl: e.target.value })}
+                  placeholder="http://proxy.example.com:8080 or socks5://host:1080"
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="no-proxy">Bypass proxy for</Label>
+                <Input
+                  id="no-proxy"
+                  value={settings.noProxy}
+                  onChange={(e) => update({ noProxy: e.target.value })}
+                  placeholder="localhost, .intranet.example.com, 10.0.0.0/8"
+                />
+              </div>
+            </>
+          )}
+
+          {settings.proxyMode === 'pac' && (
+            <div className="space-y-2">
+              <Label htmlFor="pac-url">PAC file</Label>
+              <Input
+                id="pac-url"
+                value={settings.pacUrl}
+                onChange={(e) => update({ pacUrl: e.target.value })}
+                placeholder="http://wpad.example.com/proxy.pac or a file path"
+              />
+            </div>
+          )}
+
+          {usesProxy && (
+            <div className="grid grid-cols-2 gap-4">
+              <div className="space-y-2">
+                <Label htmlFor="proxy-username">Proxy username (optional)</Label>
+                <Input
+                  id="proxy-username"
+                  value={settings.proxyUsername}
+                  onChange={(e) => update({ proxyUsername: e.target.value })}
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="proxy-password">Proxy password</Label>
+                <Input
+                  id="proxy-password"
+                  type="password"
+                  value={settings.proxyPassword ?? ''}
+                  onChange={(e) => update({ proxyPassword: e.target.value })}
+                  placeholder="Unchanged"
+                />
+              </div>
+            </div>
+          )}
+
+          <div className="space-y-2">
+            <Label htmlFor="ca-bundle">Extra trusted certificates (optional)</Label>
+            <div className="flex gap-2">
+              <Input
+                id="ca-bundle"
+                value={settings.caBundle ?? ''}
+                onChange={(e) => update({ caBundle: e.target.value || null })}
+                placeholder="Path to a PEM bundle"
+              />
+              <Button type="button" variant="outline" onClick={handleBrowseBundle}>
+                Browse
+              </Button>
+            </div>
+          </div>
+
+          {message && (
+            <Alert variant={message.success ? 'default' : 'destructive'}>
+              {message.success ? <CheckCircle className="h-4 w-4" /> : <AlertCircle className="h-4 w-4" />}
+              <AlertDescription>{message.text}</AlertDescription>
+            </Alert>
+          )}
+
+          <Button type="submit" disabled={isSaving}>
+            {isSaving ? (
+              <>
+                <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+                Saving...
+              </>
+            ) : (
+              'Save Network Settings'
+            )}
+          </Button>
+        </form>
+      </CardContent>
+    </Card>
+  );
+}
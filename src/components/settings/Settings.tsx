@@ -12,6 +12,9 @@ import { useAuth } from '@/contexts/AuthContext';
 import { useUpdater } from '@/contexts/UpdaterContext';
 import { useNotifications } from '@/contexts/NotificationContext';
 import { apiClient } from '@/lib/api';
+import { NetworkSettingsCard } from './NetworkSettingsCard';
+import { CalendarFeedCard } from './CalendarFeedCard';
+import { ShortcutsCard } from './ShortcutsCard';
 import {
   User,
   Mail,
@@ -24,7 +27,9 @@ import {
   AlertCircle,
   Volume2,
   Monitor,
-  Clock
+  Clock,
+  Globe,
+  Keyboard
 } from 'lucide-react';
 
 export function Settings() {
@@ -179,7 +184,7 @@ export function Settings() {
       )}
 
       <Tabs defaultValue="profile" className="space-y-4">
-        <TabsList className="grid w-full grid-cols-4">
+        <TabsList className="grid w-full grid-cols-6">
           <TabsTrigger value="profile">
             <User className="w-4 h-4 mr-2" />
             Profile
@@ -192,6 +197,14 @@ export function Settings() {
             <Bell className="w-4 h-4 mr-2" />
             Notifications
           </TabsTrigger>
+          <TabsTrigger value="network">
+            <Globe className="w-4 h-4 mr-2" />
+            Network
+          </TabsTrigger>
+          <TabsTrigger value="shortcuts">
+            <Keyboard className="w-4 h-4 mr-2" />
+            Shortcuts
+          </TabsTrigger>
           <TabsTrigger value="about">
             <Info className="w-4 h-4 mr-2" />
             About
@@ -548,6 +561,15 @@ export function Settings() {
           </Card>
         </TabsContent>
 
+        <TabsContent value="network" className="space-y-4">
+          <NetworkSettingsCard />
+          <CalendarFeedCard />
+        </TabsContent>
+
+        <TabsContent value="shortcuts">
+          <ShortcutsCard />
+        </TabsContent>
+
         <TabsContent value="about">
           <Card>
             <CardHeader>
@@ -0,0 +1,154 @@
+import { useState, useEffect, KeyboardEvent } from 'react';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Alert, AlertDescription } from '@/components/ui/alert';
+import { apiClient } from '@/lib/api';
+import type { ShortcutAction, ShortcutSettings, ShortcutStatus } from '@/types/api';
+import { Keyboard, CheckCircle, AlertCircle, X } from 'lucide-react';
+
+const actions: { action: ShortcutAction; label: string; description: string }[] = [
+  { action: 'pauseResume', label: 'Pause/resume timer', description: 'The running ticket timer, or the one paused last' },
+  { action: 'newTicket', label: 'New ticket', description: 'Opens the new ticket form in a small window' },
+  { action: 'openTicket', label: 'Open ticket', description: 'Find a ticket by number or text and open it' },
+];
+
+const modifierKeys = ['Control', 'Alt', 'Shift', 'Meta'];
+
+// Turns a key press into an accelerator the Rust side understands
+const toAccelerator = (event: KeyboardEvent) => {
+  if (modifierKeys.includes(event.key)) return null;
+  const parts = [];
+  if (event.ctrlKey) parts.push('CommandOrControl');
+  if (event.metaKey) parts.push('Super');
+  if (event.altKey) parts.push('Alt');
+  if (event.shiftKey) parts.push('Shift');
+  // Shortcuts without a modifier would take the key away from every other app
+  if (parts.length === 0) return null;
+  parts.push(event.code.replace(/^Key/, '').replace(/^Digit/, ''));
+  return parts.join('+');
+};
+
+const toSettings = (status: ShortcutStatus[]) =>
+  Object.fromEntries(status.map((entry) => [entry.action, entry.accelerator])) as ShortcutSettings;
+
+// System-wide shortcuts, registered by the app even while it is in the
+// background, so the timer can be paused from within a remote session
+export function ShortcutsCard() {
+  const [status, setStatus] = useState<ShortcutStatus[]>([]);
+  const [settings, setSettings] = useState<ShortcutSettings | null>(null);
+  const [isSaving, setIsSaving] = useState(false);
+  const [message, setMessage] = useState<{ success: boolean; text: string } | null>(null);
+
+  const apply = (status: ShortcutStatus[]) => {
+    setStatus(status);
+    setSettings(toSettings(status));
+  };
+
+  useEffect(() => {
+    apiClient.getShortcuts()
+      .then(apply)
+      .catch((error) => setMessage({ success: false, text: String(error) }));
+  }, []);
+
+  if (!settings) return null;
+
+  const update = (action: ShortcutAction, accelerator: string) => {
+    setSettings({ ...settings, [action]: accelerator });
+    setMessage(null);
+  };
+
+  const handleKeyDown = (action: ShortcutAction, event: KeyboardEvent) => {
+    if (event.key === 'Tab') return;
+    event.preventDefault();
+    if ((event.key === 'Backspace' || event.key === 'Delete') && !event.ctrlKey && !event.altKey) {
+      update(action, '');
+      return;
+    }
+    const accelerator = toAccelerator(event);
+    if (accelerator) {
+      update(action, accelerator);
+    }
+  };
+
+  const handleSave = async () => {
+    setIsSaving(true);
+    setMessage(null);
+    try {
+      const status = await apiClient.setShortcuts(settings);
+      apply(status);
+      const failed = status.filter((entry) => entry.error).length;
+      setMessage(failed
+        ? { success: false, text: `${failed} shortcut${failed === 1 ? '' : 's'} could not be registered` }
+        : { success: true, text: 'Shortcuts saved' });
+    } catch (error) {
+      setMessage({ success: false, text: String(error) });
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  const changed = JSON.stringify(settings) !== JSON.stringify(toSettings(status));
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Keyboard className="h-5 w-5" />
+          Keyboard Shortcuts
+        </CardTitle>
+        <CardDescription>
+          Work from any application, even inside a remote session. Click a field and press the keys.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        {actions.map(({ action, label, description }) => {
+          const entry = status.find((entry) => entry.action === action);
+          const saved = entry?.accelerator === settings[action];
+          return (
+            <div key={action} className="space-y-2">
+              <Label htmlFor={`shortcut-${action}`}>{label}</Label>
+              <div className="flex gap-2">
+                <Input
+                  id={`shortcut-${action}`}
+                  value={settings[action]}
+                  onKeyDown={(e) => handleKeyDown(action, e)}
+                  onChange={() => {}}
+                  placeholder="Off"
+                  className="font-mono text-sm"
+                />
+                <Button
+                  type="button"
+                  variant="outline"
+                  size="icon"
+                  disabled={!settings[action]}
+                  onClick={() => update(action, '')}
+                  title="Turn off"
+                >
+                  <X className="h-4 w-4" />
+                </Button>
+              </div>
+              {saved && entry?.error ? (
+                <p className="text-sm text-destructive">{entry.error}</p>
+              ) : (
+                <p className="text-sm text-muted-foreground">{description}</p>
+              )}
+            </div>
+          );
+        })}
+
+        {message && (
+          <Alert variant={message.success ? 'default' : 'destructive'}>
+            {message.success ? <CheckCircle className="h-4 w-4" /> : <AlertCircle className="h-4 w-4" />}
+            <AlertDescription>{message.text}</AlertDescription>
+          </Alert>
+        )}
+
+        <Button type="button" disabled={isSaving || !changed} onClick={handleSave}>
+          Save Shortcuts
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
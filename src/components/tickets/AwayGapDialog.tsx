@@ -0,0 +1,79 @@
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { Button } from '@/components/ui/button';
+import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle, DialogFooter } from '@/components/ui/dialog';
+import { apiClient } from '@/lib/api';
+import { AwayGap } from '@/types/api';
+
+const reasons: Record<AwayGap['reason'], string> = {
+  locked: 'the screen was locked',
+  sleep: 'the computer was asleep',
+  idle: 'you were inactive',
+};
+
+const formatDuration = (milliseconds: number) => {
+  const minutes = Math.max(1, Math.round(milliseconds / 60000));
+  const hours = Math.floor(minutes / 60);
+  return hours > 0 ? `${hours} h ${minutes % 60} min` : `${minutes} min`;
+};
+
+// Asks whether the time away counts once the user is back. Every window shows
+// it; the first answer closes it everywhere.
+export function AwayGapDialog() {
+  const [gap, setGap] = useState<AwayGap | null>(null);
+  const [isLoading, setIsLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    apiClient.getAwayGap().then(setGap).catch(() => {});
+    const unlisten = listen<AwayGap | null>('timer://away', (event) => {
+      setGap(event.payload);
+      setError(null);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const resolve = async (keep: boolean) => {
+    setIsLoading(true);
+    try {
+      await apiClient.resolveAwayGap(keep);
+      setGap(null);
+    } catch (error) {
+      setError(String(error));
+    } finally {
+      setIsLoading(false);
+    }
+  };
+
+  if (!gap) {
+    return null;
+  }
+
+  const tickets = gap.timers.map((timer) => `#${timer.ticketId}`).join(', ');
+
+  return (
+    <Dialog open onOpenChange={() => {}}>
+      <DialogContent className="sm:max-w-[425px]" showCloseButton={false}>
+        <DialogHeader>
+          <DialogTitle>Welcome back</DialogTitle>
+          <DialogDescription>
+            The timer of {gap.timers.length === 1 ? 'ticket' : 'tickets'} {tickets} was paused
+            because {reasons[gap.reason]} for {formatDuration(gap.returnedAt - gap.since)}.
+            Should that time count as work?
+          </DialogDescription>
+        </DialogHeader>
+        {error && <p className="text-sm text-destructive">{error}</p>}
+        <DialogFooter>
+          <Button variant="outline" onClick={() => resolve(false)} disabled={isLoading}>
+            Discard time away
+          </Button>
+          <Button onClick={() => resolve(true)} disabled={isLoading}>
+            Keep time away
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -8,7 +8,9 @@ import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { useAuth } from '@/contexts/AuthContext';
 import { apiClient } from '@/lib/api';
-import { Company, Location, User, Template } from '@/types/api';
+import { Company, Location, User, Template, TicketAttachment, UploadProgress, NewTicketDraft } from '@/types/api';
+import { open } from '@tauri-apps/plugin-dialog';
+import { listen } from '@tauri-apps/api/event';
 import { 
   Plus, 
   Building, 
@@ -19,7 +21,8 @@ import {
   Loader2,
   Upload,
   X,
-  Image as ImageIcon
+  Image as ImageIcon,
+  File as FileIcon
 } from 'lucide-react';
 
 export function NewTicketForm() {
@@ -31,7 +34,7 @@ export function NewTicketForm() {
     location_id: '',
     for_user_id: '',
     template_id: '',
-    attachments: [] as File[]
+    attachments: [] as TicketAttachment[]
   });
   
   const [companies, setCompanies] = useState<Company[]>([]);
@@ -46,6 +49,8 @@ export function NewTicketForm() {
   const [successMessage, setSuccessMessage] = useState('');
   const [showCompanyDropdown, setShowCompanyDropdown] = useState(false);
   const [companySearchTerm, setCompanySearchTerm] = useState('');
+  const [uploadProgress, setUploadProgress] = useState<Record<number, UploadProgress>>({});
+  const [draftCompany, setDraftCompany] = useState<string | null>(null);
 
   const companyDropdownRef = useRef<HTMLDivElement>(null);
 
@@ -53,6 +58,49 @@ export function NewTicketForm() {
     fetchInitialData();
   }, []);
 
+  // Prefills the form from a ticketbase://new link, also when it is already open
+  useEffect(() => {
+    const applyDraft = (draft: NewTicketDraft | null) => {
+      if (!draft) return;
+      setFormData(prev => ({
+        ...prev,
+        description: draft.description ?? prev.description,
+        priority: draft.priority ?? prev.priority
+      }));
+      if (draft.company) {
+        setDraftCompany(draft.company);
+      }
+    };
+
+    apiClient.takeNewTicketDraft().then(applyDraft).catch(console.error);
+    const unlisten = listen('deeplink://new-ticket', () => {
+      apiClient.takeNewTicketDraft().then(applyDraft).catch(console.error);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // The linked company, once the companies are loaded; by id, customer number
+  // or name, and left to the search when more than one fits
+  useEffect(() => {
+    if (!draftCompany || companies.length === 0) return;
+    const wanted = draftCompany.toLowerCase();
+    const matches = companies.filter(company =>
+      company.id.toString() === wanted ||
+      company.number?.toLowerCase() === wanted ||
+      company.name.toLowerCase() === wanted
+    );
+    if (matches.length === 1) {
+      handleCompanySelect(matches[0]);
+    } else {
+      handleCompanyClear();
+      setCompanySearchTerm(draftCompany);
+      setShowCompanyDropdown(true);
+    }
+    setDraftCompany(null);
+  }, [draftCompany, companies]);
+
   useEffect(() => {
     if (formData.company_id) {
       fetchLocations(parseInt(formData.company_id));
@@ -154,14 +202,24 @@ export function NewTicketForm() {
     setSuccess(false);
   };
 
-  const handleFileChange = (e: React.ChangeEvent<HTMLInputElement>) => {
-    const files = e.target.files;
-    if (files && files.length > 0) {
-      const newFiles = Array.from(files);
+  const handleAddFiles = async () => {
+    const selected = await open({ multiple: true, directory: false });
+    if (!selected) return;
+    const paths = (Array.isArray(selected) ? selected : [selected])
+      .filter(path => !formData.attachments.some(attachment => attachment.path === path));
+    if (paths.length === 0) return;
+
+    try {
+      // Checked before they are listed, so a file that is too large or of the
+      // wrong type is refused right away and not on submit
+      const checked = await apiClient.inspectAttachments(paths);
       setFormData(prev => ({
         ...prev,
-        attachments: [...prev.attachments, ...newFiles]
+        attachments: [...prev.attachments, ...checked]
       }));
+      setError('');
+    } catch (error) {
+      setError(error instanceof Error ? error.message : String(error));
     }
   };
 
@@ -235,6 +293,7 @@ export function NewTicketForm() {
 
     setIsSubmitting(true);
     setError('');
+    setUploadProgress({});
 
     try {
       // Ensure all IDs are properly converted to numbers
@@ -254,52 +313,50 @@ export function NewTicketForm() {
         location_id: locationId,
         for_user_id: forUserId,
         dyn_template_id: formData.template_id ? parseInt(formData.template_id) : undefined,
-        attachments: formData.attachments.length > 0 ? formData.attachments : undefined,
+        attachments: formData.attachments.map(attachment => attachment.path),
       };
 
       console.log('Submitting ticket data:', {
         ...ticketData,
-        attachments: ticketData.attachments ? `${ticketData.attachments.length} file(s)` : 'none'
+        attachments: `${ticketData.attachments.length} file(s)`
+      });
+
+      const created = await apiClient.createTicket(ticketData, (progress) => {
+        setUploadProgress(prev => ({ ...prev, [progress.file]: progress }));
       });
 
-      const response = await apiClient.createTicket(ticketData);
+      setSuccess(true);
+      const companyName = companies.find(c => c.id.toString() === formData.company_id)?.name || 'the company';
+      const priorityLabel = formData.priority === 'VERY_HIGH' ? 'High (8 hours)' : 
+                            formData.priority === 'HIGH' ? 'Medium (2 days)' : 'Low (4 days)';
+      const attachmentInfo = formData.attachments.length > 0 
+        ? ` with ${formData.attachments.length} attachment${formData.attachments.length > 1 ? 's' : ''}`
+        : '';
       
-      if (response.status === 'success') {
-        setSuccess(true);
-        const companyName = companies.find(c => c.id.toString() === formData.company_id)?.name || 'the company';
-        const priorityLabel = formData.priority === 'VERY_HIGH' ? 'High (8 hours)' : 
-                              formData.priority === 'HIGH' ? 'Medium (2 days)' : 'Low (4 days)';
-        const attachmentInfo = formData.attachments.length > 0 
-          ? ` with ${formData.attachments.length} attachment${formData.attachments.length > 1 ? 's' : ''}`
-          : '';
-        
-        setSuccessMessage(
-          `Ticket created successfully for ${companyName} with ${priorityLabel} priority${attachmentInfo}. ` +
-          `It will be assigned to a technician soon.`
-        );
-        
-        // Reset form
-        setFormData({
-          description: '',
-          priority: 'HIGH',
-          company_id: '',
-          location_id: '',
-          for_user_id: '',
-          template_id: '',
-          attachments: []
-        });
-        setCompanySearchTerm('');
-        setLocations([]);
-        setUsers([]);
-        
-        // Auto-hide success message after 10 seconds
-        setTimeout(() => {
-          setSuccess(false);
-          setSuccessMessage('');
-        }, 10000);
-      } else {
-        setError(response.message || 'Failed to create ticket');
-      }
+      setSuccessMessage(
+        `Ticket${created.ticketId ? ` #${created.ticketId}` : ''} created successfully for ${companyName} with ${priorityLabel} priority${attachmentInfo}. ` +
+        `It will be assigned to a technician soon.`
+      );
+      
+      // Reset form
+      setFormData({
+        description: '',
+        priority: 'HIGH',
+        company_id: '',
+        location_id: '',
+        for_user_id: '',
+        template_id: '',
+        attachments: []
+      });
+      setCompanySearchTerm('');
+      setLocations([]);
+      setUsers([]);
+      
+      // Auto-hide success message after 10 seconds
+      setTimeout(() => {
+        setSuccess(false);
+        setSuccessMessage('');
+      }, 10000);
     } catch (error) {
       console.error('Failed to create ticket:', error);
       
@@ -320,6 +377,7 @@ export function NewTicketForm() {
       }
     } finally {
       setIsSubmitting(false);
+      setUploadProgress({});
     }
   };
 
@@ -562,26 +620,33 @@ export function NewTicketForm() {
                 Attachments
               </Label>
               <div className="space-y-2">
-                <Input
+                <Button
                   id="attachments"
-                  type="file"
-                  onChange={handleFileChange}
-                  accept="image/*"
-                  multiple
+                  type="button"
+                  variant="outline"
+                  onClick={handleAddFiles}
                   disabled={isSubmitting}
-                  className="cursor-pointer"
-                />
+                >
+                  <Upload className="h-4 w-4 mr-2" />
+                  Add files
+                </Button>
                 {formData.attachments.length > 0 && (
                   <div className="space-y-2">
                     {formData.attachments.map((file, index) => (
                       <div
-                        key={index}
+                        key={file.path}
                         className="flex items-center gap-2 p-2 border rounded-md bg-muted/50"
                       >
-                        <ImageIcon className="h-4 w-4 text-muted-foreground flex-shrink-0" />
-                        <span className="text-sm flex-1 truncate">{file.name}</span>
+                        {file.mime.startsWith('image/') ? (
+                          <ImageIcon className="h-4 w-4 text-muted-foreground flex-shrink-0" />
+                        ) : (
+                          <FileIcon className="h-4 w-4 text-muted-foreground flex-shrink-0" />
+                        )}
+                        <span className="text-sm flex-1 truncate" title={file.path}>{file.name}</span>
                         <span className="text-xs text-muted-foreground">
-                          {(file.size / 1024).toFixed(1)} KB
+                          {uploadProgress[index]
+                            ? `${Math.round((uploadProgress[index].sent / uploadProgress[index].total) * 100)}%`
+                            : `${(file.size / 1024).toFixed(1)} KB`}
                         </span>
                         <button
                           type="button"
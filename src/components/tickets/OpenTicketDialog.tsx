@@ -0,0 +1,64 @@
+import { useState, useEffect, FormEvent } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Button } from '@/components/ui/button';
+import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle, DialogFooter } from '@/components/ui/dialog';
+import { Input } from '@/components/ui/input';
+
+// Asks for a ticket number when "Other ticket #…" is chosen in the tray, and
+// opens that ticket in its own window
+export function OpenTicketDialog() {
+  const [isOpen, setIsOpen] = useState(false);
+  const [ticketId, setTicketId] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen('tray://open-ticket', () => {
+      setTicketId('');
+      setError(null);
+      setIsOpen(true);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleSubmit = async (event: FormEvent) => {
+    event.preventDefault();
+    const id = parseInt(ticketId.replace('#', ''), 10);
+    if (!id) return;
+
+    try {
+      await invoke('open_ticket_window', { ticketId: id });
+      setIsOpen(false);
+    } catch (error) {
+      setError(String(error));
+    }
+  };
+
+  return (
+    <Dialog open={isOpen} onOpenChange={setIsOpen}>
+      <DialogContent className="sm:max-w-[360px]">
+        <form onSubmit={handleSubmit} className="space-y-4">
+          <DialogHeader>
+            <DialogTitle>Open ticket</DialogTitle>
+            <DialogDescription>Opens the ticket in its own window.</DialogDescription>
+          </DialogHeader>
+          <Input
+            autoFocus
+            inputMode="numeric"
+            placeholder="Ticket number"
+            value={ticketId}
+            onChange={(e) => setTicketId(e.target.value)}
+          />
+          {error && <p className="text-sm text-destructive">{error}</p>}
+          <DialogFooter>
+            <Button type="submit" disabled={!parseInt(ticketId.replace('#', ''), 10)}>
+              Open
+            </Button>
+          </DialogFooter>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+}
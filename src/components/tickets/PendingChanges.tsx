@@ -0,0 +1,169 @@
+import { useState, useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Card, CardContent } from '@/components/ui/card';
+import { Badge } from '@/components/ui/badge';
+import { Button } from '@/components/ui/button';
+import { Textarea } from '@/components/ui/textarea';
+import { AlertTriangle, CloudOff, RotateCcw, Trash2 } from 'lucide-react';
+
+type Mutation =
+  | { kind: 'history'; ticket_id: number; user_id: number; verlauf_text: string; status_id: number }
+  | { kind: 'new_todo'; ticket_id: number; user_id: number; todo: string }
+  | { kind: 'check_todo'; ticket_id: number | null; todo_id: number; type: number }
+  | { kind: 'message'; ticket_id: number; user_id: number; message: string };
+
+export interface OutboxEntry {
+  id: number;
+  idempotencyKey: string;
+  ticketId: number | null;
+  mutation: Mutation;
+  createdAt: number;
+  error: string | null;
+}
+
+// The text the user typed, which is what they can correct after a rejection
+const textOf = (mutation: Mutation): string | null => {
+  switch (mutation.kind) {
+    case 'history': return mutation.verlauf_text;
+    case 'new_todo': return mutation.todo;
+    case 'message': return mutation.message;
+    case 'check_todo': return null;
+  }
+};
+
+const withText = (mutation: Mutation, text: string): Mutation => {
+  switch (mutation.kind) {
+    case 'history': return { ...mutation, verlauf_text: text };
+    case 'new_todo': return { ...mutation, todo: text };
+    case 'message': return { ...mutation, message: text };
+    case 'check_todo': return mutation;
+  }
+};
+
+const describe = (mutation: Mutation): string => {
+  switch (mutation.kind) {
+    case 'history': return 'History entry';
+    case 'new_todo': return 'New todo';
+    case 'message': return 'Message';
+    case 'check_todo': return `Mark todo #${mutation.todo_id} as ${mutation.type ? 'done' : 'open'}`;
+  }
+};
+
+export function useOutbox(ticketId: number) {
+  const [entries, setEntries] = useState<OutboxEntry[]>([]);
+
+  useEffect(() => {
+    const load = () => {
+      invoke<OutboxEntry[]>('get_outbox', { ticketId })
+        .then(setEntries)
+        .catch((error) => console.error('Failed to load outbox:', error));
+    };
+
+    load();
+    const unlisten = listen('outbox://changed', load);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [ticketId]);
+
+  return entries;
+}
+
+interface PendingChangesProps {
+  ticketId: number;
+  kinds: Mutation['kind'][];
+  /** Called when entries left the outbox, so the real list can be reloaded. */
+  onSent?: () => void;
+}
+
+/** Writes for this ticket that have not reached the server yet. */
+export function PendingChanges({ ticketId, kinds, onSent }: PendingChangesProps) {
+  const entries = useOutbox(ticketId).filter((entry) => kinds.includes(entry.mutation.kind));
+  const previousCount = useRef(entries.length);
+
+  useEffect(() => {
+    if (entries.length < previousCount.current) {
+      onSent?.();
+    }
+    previousCount.current = entries.length;
+  }, [entries.length]);
+
+  if (entries.length === 0) return null;
+
+  return (
+    <div className="space-y-2">
+      {entries.map((entry) => (
+        <PendingEntry key={entry.id} entry={entry} />
+      ))}
+    </div>
+  );
+}
+
+function PendingEntry({ entry }: { entry: OutboxEntry }) {
+  const text = textOf(entry.mutation);
+  const [draft, setDraft] = useState(text ?? '');
+
+  const handleRetry = async () => {
+    try {
+      await invoke('retry_outbox_entry', {
+        id: entry.id,
+        mutation: text === null ? null : withText(entry.mutation, draft),
+      });
+    } catch (error) {
+      console.error('Failed to retry outbox entry:', error);
+    }
+  };
+
+  const handleDiscard = async () => {
+    try {
+      await invoke('discard_outbox_entry', { id: entry.id });
+    } catch (error) {
+      console.error('Failed to discard outbox entry:', error);
+    }
+  };
+
+  return (
+    <Card className={`border-dashed ${entry.error ? 'border-destructive' : ''}`}>
+      <CardContent className="p-4 space-y-2">
+        <div className="flex items-center justify-between gap-2">
+          <span className="text-sm font-medium">{describe(entry.mutation)}</span>
+          {entry.error ? (
+            <Badge variant="destructive" className="gap-1">
+              <AlertTriangle className="h-3 w-3" />
+              Rejected
+            </Badge>
+          ) : (
+            <Badge variant="secondary" className="gap-1">
+              <CloudOff className="h-3 w-3" />
+              Pending
+            </Badge>
+          )}
+        </div>
+
+        {entry.error ? (
+          <>
+            <p className="text-xs text-destructive">{entry.error}</p>
+            {text !== null && (
+              <Textarea value={draft} onChange={(e) => setDraft(e.target.value)} rows={3} />
+            )}
+            <div className="flex justify-end gap-2">
+              <Button variant="outline" size="sm" onClick={handleDiscard}>
+                <Trash2 className="h-4 w-4 mr-1" />
+                Discard
+              </Button>
+              <Button size="sm" onClick={handleRetry} disabled={text !== null && !draft.trim()}>
+                <RotateCcw className="h-4 w-4 mr-1" />
+                Retry
+              </Button>
+            </div>
+          </>
+        ) : (
+          text !== null && (
+            <p className="text-sm text-muted-foreground whitespace-pre-wrap">{text}</p>
+          )
+        )}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,121 @@
+import { useState, useEffect, KeyboardEvent } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { Input } from '@/components/ui/input';
+import { apiClient } from '@/lib/api';
+import { SearchHit } from '@/types/api';
+import { Search, Loader2 } from 'lucide-react';
+
+const ticketNumber = (query: string) => {
+  const match = query.trim().match(/^#?(\d+)$/);
+  return match ? parseInt(match[1], 10) : null;
+};
+
+// The small window of the "Open ticket" shortcut: a ticket number opens that
+// ticket, anything else searches the tickets
+export function QuickOpenWindow() {
+  const [query, setQuery] = useState('');
+  const [hits, setHits] = useState<SearchHit[]>([]);
+  const [selected, setSelected] = useState(0);
+  const [isSearching, setIsSearching] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setSelected(0);
+    if (ticketNumber(query) !== null || query.trim().length < 2) {
+      setHits([]);
+      return;
+    }
+    const timeout = setTimeout(async () => {
+      setIsSearching(true);
+      try {
+        setHits(await apiClient.search(query, { kinds: ['ticket'], limit: 8 }));
+        setError(null);
+      } catch (error) {
+        setError(String(error));
+      } finally {
+        setIsSearching(false);
+      }
+    }, 200);
+    return () => clearTimeout(timeout);
+  }, [query]);
+
+  const openTicket = async (ticketId: number) => {
+    try {
+      await invoke('open_ticket_window', { ticketId });
+      await getCurrentWindow().close();
+    } catch (error) {
+      setError(String(error));
+    }
+  };
+
+  const handleKeyDown = (event: KeyboardEvent) => {
+    switch (event.key) {
+      case 'Escape':
+        getCurrentWindow().close();
+        break;
+      case 'ArrowDown':
+        event.preventDefault();
+        setSelected((index) => Math.min(index + 1, hits.length - 1));
+        break;
+      case 'ArrowUp':
+        event.preventDefault();
+        setSelected((index) => Math.max(index - 1, 0));
+        break;
+      case 'Enter': {
+        const hit = hits[selected];
+        const ticketId = ticketNumber(query) ?? (hit ? hit.ticketId ?? hit.id : null);
+        if (ticketId) {
+          openTicket(ticketId);
+        }
+        break;
+      }
+    }
+  };
+
+  const number = ticketNumber(query);
+
+  return (
+    <div className="min-h-screen bg-background p-4 space-y-3">
+      <div className="relative">
+        <Search className="absolute left-3 top-1/2 h-4 w-4 -translate-y-1/2 text-muted-foreground" />
+        <Input
+          autoFocus
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          onKeyDown={handleKeyDown}
+          placeholder="Ticket number or search"
+          className="pl-9"
+        />
+        {isSearching && (
+          <Loader2 className="absolute right-3 top-1/2 h-4 w-4 -translate-y-1/2 animate-spin text-muted-foreground" />
+        )}
+      </div>
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+
+      {number !== null && (
+        <p className="text-sm text-muted-foreground">Press Enter to open ticket #{number}</p>
+      )}
+
+      <ul className="space-y-1">
+        {hits.map((hit, index) => (
+          <li key={`${hit.kind}-${hit.id}`}>
+            <button
+              type="button"
+              onClick={() => openTicket(hit.ticketId ?? hit.id)}
+              onMouseEnter={() => setSelected(index)}
+              className={`w-full rounded-md px-3 py-2 text-left text-sm ${index === selected ? 'bg-accent' : ''}`}
+            >
+              <div className="font-medium truncate">#{hit.ticketId ?? hit.id} {hit.title}</div>
+              <div
+                className="text-xs text-muted-foreground truncate"
+                dangerouslySetInnerHTML={{ __html: hit.snippet }}
+              />
+            </button>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+}
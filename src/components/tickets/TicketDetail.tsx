@@ -11,6 +11,7 @@ import { apiClient } from '@/lib/api';
 import { WindowManager } from '@/lib/windowManager';
 import { Ticket, TicketHistory, TodoItem } from '@/types/api';
 import { TicketPlayerControls } from './TicketPlayerControls';
+import { PendingChanges } from './PendingChanges';
 import { TicketMessages } from './TicketMessages';
 import { FilePreviewModal } from '@/components/ui/FilePreviewModal';
 import {
@@ -51,6 +52,7 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
   const [newHistoryTime, setNewHistoryTime] = useState<string>(''); // Custom time in minutes
   const [isAddingHistory, setIsAddingHistory] = useState(false);
   const [downloadingFiles, setDownloadingFiles] = useState<Set<string>>(new Set());
+  const [downloadProgress, setDownloadProgress] = useState<Record<string, number>>({});
   const [previewFile, setPreviewFile] = useState<{ filename: string; ticketId: number } | null>(null);
 
   const handleOpenInNewWindow = async () => {
@@ -99,6 +101,8 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
       if (response.status === 'sucess' && response.check_list) {
         setTodos(response.check_list);
         setNewTodo('');
+      } else if (response.status === 'pending') {
+        setNewTodo('');
       }
     } catch (error) {
       console.error('Failed to add todo:', error);
@@ -109,8 +113,8 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
 
   const handleToggleTodo = async (todoId: number, checked: number) => {
     try {
-      const response = await apiClient.checkTodo(todoId, checked ? 0 : 1);
-      if (response.status === 'success') {
+      const response = await apiClient.checkTodo(todoId, checked ? 0 : 1, ticket.id);
+      if (response.status === 'success' || response.status === 'pending') {
         setTodos(todos.map(todo => 
           todo.id === todoId ? { ...todo, checked: checked ? 0 : 1 } : todo
         ));
@@ -144,7 +148,7 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
         sendMail: 0, // Don't send email by default
       });
 
-      if (response.status === 'success') {
+      if (response.status === 'success' || response.status === 'pending') {
         setNewHistoryText('');
         setNewHistoryTime('');
         // Refresh ticket history
@@ -255,17 +259,12 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
     setDownloadingFiles(prev => new Set(prev).add(filename));
 
     try {
-      const blob = await apiClient.downloadAttachment(ticket.id, filename);
-
-      // Create download link
-      const url = window.URL.createObjectURL(blob);
-      const link = document.createElement('a');
-      link.href = url;
-      link.download = filename;
-      document.body.appendChild(link);
-      link.click();
-      document.body.removeChild(link);
-      window.URL.revokeObjectURL(url);
+      const file = await apiClient.downloadAttachment(ticket.id, filename, (received, total) => {
+        if (total) {
+          setDownloadProgress(prev => ({ ...prev, [filename]: Math.round((received / total) * 100) }));
+        }
+      });
+      await WindowManager.openFileExternally(file.path);
     } catch (error) {
       console.error('Failed to download attachment:', error);
     } finally {
@@ -274,6 +273,11 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
         newSet.delete(filename);
         return newSet;
       });
+      setDownloadProgress(prev => {
+        const next = { ...prev };
+        delete next[filename];
+        return next;
+      });
     }
   };
 
@@ -400,7 +404,11 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
                                   title="Download file"
                                 >
                                   {downloadingFiles.has(filename) ? (
-                                    <Loader2 className="h-4 w-4 animate-spin" />
+                                    downloadProgress[filename] !== undefined ? (
+                                      <span className="text-[10px] tabular-nums">{downloadProgress[filename]}%</span>
+                                    ) : (
+                                      <Loader2 className="h-4 w-4 animate-spin" />
+                                    )
                                   ) : (
                                     <Download className="h-4 w-4" />
                                   )}
@@ -533,6 +541,7 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
                 </div>
               ) : (
                 <>
+                  <PendingChanges ticketId={ticket.id} kinds={['history']} onSent={fetchTicketData} />
                   {ticketHistory.map((entry) => (
                     <Card key={entry.id} className="transition-all duration-200 hover:shadow-md border-l-4 border-l-primary/20 hover:border-l-primary/50">
                       <CardHeader className="pb-3">
@@ -609,6 +618,8 @@ export function TicketDetail({ ticket, onBack }: TicketDetailProps) {
                 </CardContent>
               </Card>
 
+              <PendingChanges ticketId={ticket.id} kinds={['new_todo', 'check_todo']} onSent={fetchTodos} />
+
               <div className="space-y-3">
                 {todos.map((todo) => (
                   <Card 
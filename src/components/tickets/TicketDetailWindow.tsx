@@ -8,8 +8,10 @@ import { Textarea } from '@/components/ui/textarea';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { useAuth } from '@/contexts/AuthContext';
 import { apiClient } from '@/lib/api';
+import { WindowManager } from '@/lib/windowManager';
 import { Ticket, TicketHistory, TodoItem } from '@/types/api';
 import { TicketPlayerControls } from './TicketPlayerControls';
+import { PendingChanges } from './PendingChanges';
 import { 
   Calendar,
   Building,
@@ -42,6 +44,7 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
   const [newHistoryStatus, setNewHistoryStatus] = useState('3'); // Default to "In Progress"
   const [isAddingHistory, setIsAddingHistory] = useState(false);
   const [downloadingFiles, setDownloadingFiles] = useState<Set<string>>(new Set());
+  const [downloadProgress, setDownloadProgress] = useState<Record<string, number>>({});
 
   useEffect(() => {
     fetchTicketData();
@@ -81,6 +84,8 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
       if (response.status === 'sucess' && response.check_list) {
         setTodos(response.check_list);
         setNewTodo('');
+      } else if (response.status === 'pending') {
+        setNewTodo('');
       }
     } catch (error) {
       console.error('Failed to add todo:', error);
@@ -91,8 +96,8 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
 
   const handleToggleTodo = async (todoId: number, checked: number) => {
     try {
-      const response = await apiClient.checkTodo(todoId, checked ? 0 : 1);
-      if (response.status === 'success') {
+      const response = await apiClient.checkTodo(todoId, checked ? 0 : 1, ticket.id);
+      if (response.status === 'success' || response.status === 'pending') {
         setTodos(todos.map(todo => 
           todo.id === todoId ? { ...todo, checked: checked ? 0 : 1 } : todo
         ));
@@ -115,7 +120,7 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
         sendMail: 0, // Don't send email by default
       });
 
-      if (response.status === 'success') {
+      if (response.status === 'success' || response.status === 'pending') {
         setNewHistoryText('');
         // Refresh ticket history
         await fetchTicketData();
@@ -208,17 +213,12 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
     setDownloadingFiles(prev => new Set(prev).add(filename));
     
     try {
-      const blob = await apiClient.downloadAttachment(ticket.id, filename);
-      
-      // Create download link
-      const url = window.URL.createObjectURL(blob);
-      const link = document.createElement('a');
-      link.href = url;
-      link.download = filename;
-      document.body.appendChild(link);
-      link.click();
-      document.body.removeChild(link);
-      window.URL.revokeObjectURL(url);
+      const file = await apiClient.downloadAttachment(ticket.id, filename, (received, total) => {
+        if (total) {
+          setDownloadProgress(prev => ({ ...prev, [filename]: Math.round((received / total) * 100) }));
+        }
+      });
+      await WindowManager.openFileExternally(file.path);
     } catch (error) {
       console.error('Failed to download attachment:', error);
     } finally {
@@ -227,6 +227,11 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
         newSet.delete(filename);
         return newSet;
       });
+      setDownloadProgress(prev => {
+        const next = { ...prev };
+        delete next[filename];
+        return next;
+      });
     }
   };
 
@@ -308,7 +313,11 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
                                 disabled={downloadingFiles.has(filename)}
                               >
                                 {downloadingFiles.has(filename) ? (
-                                  <Loader2 className="h-4 w-4 animate-spin" />
+                                  downloadProgress[filename] !== undefined ? (
+                                    <span className="text-[10px] tabular-nums">{downloadProgress[filename]}%</span>
+                                  ) : (
+                                    <Loader2 className="h-4 w-4 animate-spin" />
+                                  )
                                 ) : (
                                   <Download className="h-4 w-4" />
                                 )}
@@ -409,6 +418,7 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
                 </div>
               ) : (
                 <>
+                  <PendingChanges ticketId={ticket.id} kinds={['history']} onSent={fetchTicketData} />
                   {ticketHistory.map((entry) => (
                     <Card key={entry.id}>
                       <CardHeader>
@@ -470,6 +480,8 @@ export function TicketDetailWindow({ ticket, onBack }: TicketDetailWindowProps)
                 </CardContent>
               </Card>
 
+              <PendingChanges ticketId={ticket.id} kinds={['new_todo', 'check_todo']} onSent={fetchTodos} />
+
               <div className="space-y-2">
                 {todos.map((todo) => (
                   <Card key={todo.id}>
@@ -65,6 +65,8 @@ export function TicketList({ onTicketSelect }: TicketListProps) {
   const [searchedTicket, setSearchedTicket] = useState<Ticket | null>(null);
   const [isSearchingTicket, setIsSearchingTicket] = useState(false);
   const [isLoadingAdvancedSearch, setIsLoadingAdvancedSearch] = useState(false);
+  // Tickets whose text, history or messages match in the local search index
+  const [indexMatches, setIndexMatches] = useState<Set<number> | null>(null);
   const [visibleItemCounts, setVisibleItemCounts] = useState({
     my: 50,
     new: 50,
@@ -126,6 +128,31 @@ export function TicketList({ onTicketSelect }: TicketListProps) {
     loadCustomers();
   }, [setCustomers]);
 
+  // Ask the full-text index once typing pauses
+  useEffect(() => {
+    const term = filterState.searchTerm.trim();
+    if (!term) {
+      setIndexMatches(null);
+      return;
+    }
+
+    let cancelled = false;
+    const handler = setTimeout(() => {
+      apiClient.search(term, { kinds: ['ticket', 'history', 'message'], limit: 500 })
+        .then((hits) => {
+          if (!cancelled) {
+            setIndexMatches(new Set(hits.flatMap((hit) => hit.ticketId === null ? [] : [hit.ticketId])));
+          }
+        })
+        .catch((error) => console.error('Failed to search index:', error));
+    }, 300);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(handler);
+    };
+  }, [filterState.searchTerm]);
+
   // Load advanced search data when customer filter is applied
   useEffect(() => {
     if (filterState.customerFilter && !allTicketsForSearch && !isLoadingAdvancedSearch) {
@@ -264,44 +291,9 @@ export function TicketList({ onTicketSelect }: TicketListProps) {
   const searchTicketById = async (ticketId: number) => {
     setIsSearchingTicket(true);
     try {
-      const response = await apiClient.getTicketById(ticketId);
-      if (response.result === 'success' && response.tickets) {
-        const rawTicket = response.tickets;
-
-        const transformedTicket: Ticket = {
-          id: rawTicket.id,
-          description: rawTicket.description || '',
-          status: rawTicket.status?.name || '',
-          status_id: rawTicket.status_id || 0,
-          summary: rawTicket.summary || '',
-          ticketCreator: rawTicket.userone?.name || '',
-          ticketUser: rawTicket.ticketuser?.name || '',
-          ticketUserPhone: rawTicket.ticketuser?.phone || '',
-          ticketTerminatedUser: '',
-          attachments: [],
-          subject: rawTicket.servicedetail?.name || '',
-          priority: rawTicket.priority || '',
-          index: rawTicket.priority_index || 0,
-          my_ticket_id: rawTicket.my_ticket_id || 0,
-          location_id: rawTicket.location_id || 0,
-          company: {
-            id: rawTicket.companyone?.id || 0,
-            name: rawTicket.companyone?.name || '',
-            number: rawTicket.companyone?.number || '',
-            companyMail: rawTicket.companyone?.email || '',
-            companyPhone: rawTicket.companyone?.phone || '',
-            companyZip: rawTicket.companyone?.zip || '',
-            companyAdress: rawTicket.companyone?.address || '',
-          },
-          dyn_template_id: rawTicket.dyn_template_id || 0,
-          created_at: rawTicket.created_at || '',
-          ticket_start: '',
-          ticketMessagesCount: 0,
-          template_data: rawTicket.template_data || '',
-          pool_name: '',
-        };
-
-        setSearchedTicket(transformedTicket);
+      const ticket = await apiClient.getTicketById(ticketId);
+      if (ticket) {
+        setSearchedTicket(ticket);
       } else {
         setSearchedTicket(null);
       }
@@ -322,7 +314,8 @@ export function TicketList({ onTicketSelect }: TicketListProps) {
         ticket.summary.toLowerCase().includes(filterState.searchTerm.toLowerCase()) ||
         effectiveDescription.toLowerCase().includes(filterState.searchTerm.toLowerCase()) ||
         ticket.company.name.toLowerCase().includes(filterState.searchTerm.toLowerCase()) ||
-        ticket.id.toString().includes(filterState.searchTerm);
+        ticket.id.toString().includes(filterState.searchTerm) ||
+        indexMatches?.has(ticket.id) === true;
 
       // Status filter
       const matchesStatus = filterState.statusFilter === 'all' || ticket.status.toLowerCase() === filterState.statusFilter;
@@ -371,7 +364,7 @@ export function TicketList({ onTicketSelect }: TicketListProps) {
 
       return matchesSearch && matchesStatus && matchesPriority && matchesCustomer && matchesDate;
     });
-  }, [filterState, customers]);
+  }, [filterState, customers, indexMatches]);
 
   // Sort tickets based on sortBy value
   const sortTickets = useCallback((ticketList: Ticket[]) => {
@@ -5,6 +5,7 @@ import { Textarea } from '@/components/ui/textarea';
 import { ScrollArea } from '@/components/ui/scroll-area';
 import { useAuth } from '@/contexts/AuthContext';
 import { apiClient } from '@/lib/api';
+import { PendingChanges } from './PendingChanges';
 import { MessageSquare, Send, Loader2, RefreshCw } from 'lucide-react';
 
 interface Message {
@@ -68,7 +69,7 @@ export function TicketMessages({ ticketId }: TicketMessagesProps) {
     setIsSending(true);
     try {
       const response = await apiClient.sendMessage(ticketId, user.id, newMessage);
-      if (response.status === 'success') {
+      if (response.status === 'success' || response.status === 'pending') {
         setNewMessage('');
         await fetchMessages();
       }
@@ -168,6 +169,8 @@ export function TicketMessages({ ticketId }: TicketMessagesProps) {
           )}
         </ScrollArea>
 
+        <PendingChanges ticketId={ticketId} kinds={['message']} onSent={fetchMessages} />
+
         {/* Message Input */}
         <div className="flex gap-2">
           <Textarea
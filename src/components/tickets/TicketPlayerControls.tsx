@@ -1,4 +1,6 @@
 import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Badge } from '@/components/ui/badge';
@@ -8,7 +10,7 @@ import { Input } from '@/components/ui/input';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { useAuth } from '@/contexts/AuthContext';
 import { apiClient } from '@/lib/api';
-import { Ticket } from '@/types/api';
+import { Ticket, TimerState, TimerTick } from '@/types/api';
 import {
   Play,
   Pause,
@@ -18,6 +20,12 @@ import {
   Loader2
 } from 'lucide-react';
 
+interface ThrottleState {
+  throttled: boolean;
+  retryInMs: number;
+  endpoint: string | null;
+}
+
 interface TicketPlayerControlsProps {
   ticket: Ticket;
   onStatusChange?: () => void;
@@ -25,108 +33,81 @@ interface TicketPlayerControlsProps {
 
 export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerControlsProps) {
   const { user } = useAuth();
-  const [playerStatus, setPlayerStatus] = useState<string>('stopped');
+  const [timer, setTimer] = useState<TimerState | null>(null);
   const [isLoading, setIsLoading] = useState(false);
-  const [startTime, setStartTime] = useState<Date | null>(null);
-  const [elapsedTime, setElapsedTime] = useState(0);
   const [isStopDialogOpen, setIsStopDialogOpen] = useState(false);
   const [stopMessage, setStopMessage] = useState('');
   const [stopStatus, setStopStatus] = useState('4'); // Default to "Abgeschlossen"
   const [customTime, setCustomTime] = useState<string>(''); // Custom time in minutes
+  const [throttle, setThrottle] = useState<ThrottleState | null>(null);
+
+  const playerStatus = !timer ? 'stopped' : timer.running ? 'playing' : 'paused';
+  const elapsedTime = timer?.elapsedMs ?? 0;
 
+  // The Rust scheduler holds requests back while Ticketbase is rate limiting us
   useEffect(() => {
-    fetchPlayerStatus();
-    let timerInterval: NodeJS.Timeout;
-    let statusInterval: NodeJS.Timeout;
+    invoke<ThrottleState>('get_throttle_state').then(setThrottle).catch(() => {});
+    const unlisten = listen<ThrottleState>('api://throttle', (event) => {
+      setThrottle(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
-    // Update elapsed time every second when playing (only when we have a startTime)
-    if (playerStatus === 'playing' && startTime) {
-      timerInterval = setInterval(() => {
-        setElapsedTime(Date.now() - startTime.getTime());
-      }, 1000);
-    }
-    // When paused, don't update elapsed time - it should stay at the current value
+  // The Rust time tracker counts, even with this window closed, and sends
+  // the same ticks to every window
+  useEffect(() => {
+    apiClient.getTimer(ticket.id).then(setTimer).catch(() => {});
+    const unlisten = listen<TimerTick>('timer://tick', (event) => {
+      setTimer(event.payload.timers.find((t) => t.ticketId === ticket.id) ?? null);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [ticket.id]);
+
+  // "Stop…" in the tray opens this window, or asks an open one, to save the work
+  useEffect(() => {
+    const openIfRequested = async () => {
+      if (await apiClient.takeStopRequest(ticket.id).catch(() => false)) {
+        const current = await apiClient.getTimer(ticket.id).catch(() => null);
+        openStopDialog(current?.elapsedMs ?? 0);
+      }
+    };
+    openIfRequested();
+    const unlisten = listen<number>('tray://stop-timer', (event) => {
+      if (event.payload === ticket.id) {
+        openIfRequested();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [ticket.id]);
+
+  useEffect(() => {
+    fetchPlayerStatus();
 
     // Poll player status every 30 seconds to detect external changes
-    statusInterval = setInterval(() => {
+    const statusInterval = setInterval(() => {
       fetchPlayerStatus();
     }, 30000);
 
     return () => {
-      if (timerInterval) {
-        clearInterval(timerInterval);
-      }
-      if (statusInterval) {
-        clearInterval(statusInterval);
-      }
+      clearInterval(statusInterval);
     };
-  }, [playerStatus, startTime, ticket.id, user?.id]);
+  }, [ticket.id, user?.id]);
 
+  // The tracker takes over what the server reports and sends a tick
   const fetchPlayerStatus = async () => {
     if (!user) return;
 
     try {
-      const response = await apiClient.getPlayerStatus(ticket.id, user.id);
-      if (response.status === 'success') {
-        // Fix: The API returns playerStatus directly, not nested under data
-        const playerStatusData = response.playerStatus;
-        if (playerStatusData) {
-          const newStatus = getPlayerStatusString(playerStatusData.play_status);
-          setPlayerStatus(newStatus);
-          
-          // Set elapsed time from API response (total_time is in minutes, convert to milliseconds)
-          const totalTimeMs = (playerStatusData.total_time || 0) * 60 * 1000;
-          setElapsedTime(totalTimeMs);
-          
-          // If the timer is running, set start time based on elapsed time
-          if (newStatus === 'playing') {
-            setStartTime(new Date(Date.now() - totalTimeMs));
-          } else if (newStatus === 'paused') {
-            // For paused state, we keep the elapsed time but no active start time
-            setStartTime(null);
-          } else if (newStatus === 'stopped') {
-            setStartTime(null);
-          }
-        } else {
-          setPlayerStatus('stopped');
-          setElapsedTime(0);
-          setStartTime(null);
-        }
-      }
-    } catch (error: any) {
+      await apiClient.getPlayerStatus(ticket.id, user.id);
+    } catch (error) {
+      // Offline the tracker's own record keeps counting
       console.error('Failed to fetch player status:', error);
-      
-      // Handle rate limiting gracefully
-      if (error.message?.includes('429') || error.message?.includes('Too Many Requests')) {
-        console.warn('Rate limited - will retry on next interval');
-        return; // Don't reset status on rate limit, keep current state
-      }
-      
-      // Only reset status on actual errors, not rate limiting
-      setPlayerStatus('stopped');
-      setElapsedTime(0);
-      setStartTime(null);
-    }
-  };
-
-  // Helper function to convert numeric status to string
-  const getPlayerStatusString = (status: number): string => {
-    // API status constants from PHP
-    const PLAY = 1;
-    const PAUSE = 2;
-    const RESUME = 3;
-    const STOP = 4;
-    
-    switch (status) {
-      case PLAY:
-      case RESUME:
-        return 'playing';  // Both PLAY and RESUME mean timer is actively running
-      case PAUSE:
-        return 'paused';   // Timer is paused, can be resumed
-      case STOP:
-        return 'stopped';  // Timer is stopped/completed
-      default:
-        return 'stopped';
     }
   };
 
@@ -135,14 +116,8 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
     
     setIsLoading(true);
     try {
-      // First check current status to prevent conflicts
-      await fetchPlayerStatus();
-      
       const response = await apiClient.play(ticket.id, user.id);
       if (response.status === 'success') {
-        setPlayerStatus('playing');
-        setStartTime(new Date());
-        setElapsedTime(0);
         onStatusChange?.();
       } else if (response.status === 'exists') {
         // Ticket is already being worked on by someone else
@@ -165,12 +140,9 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
     
     setIsLoading(true);
     try {
-      // Pass current state (1 = PLAY) to pause API
-      const response = await apiClient.pause(ticket.id, user.id, 1);
+      // The API wants the state we pause from: 1 = PLAY, 3 = RESUME
+      const response = await apiClient.pause(ticket.id, user.id, timer?.playStatus ?? 1);
       if (response.status === 'success') {
-        setPlayerStatus('paused');
-        // Stop the timer but keep elapsed time
-        setStartTime(null);
         onStatusChange?.();
       }
     } catch (error) {
@@ -188,12 +160,7 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
       // Pass current state (2 = PAUSE) to resume API
       const response = await apiClient.resume(ticket.id, user.id, 2);
       if (response.status === 'success') {
-        setPlayerStatus('playing');
-        // Use the current elapsed time to set the start time correctly
-        setStartTime(new Date(Date.now() - elapsedTime));
         onStatusChange?.();
-        // Refresh status immediately to get updated state from server
-        setTimeout(() => fetchPlayerStatus(), 1000);
       }
     } catch (error) {
       console.error('Failed to resume ticket:', error);
@@ -230,10 +197,8 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
         sendMail: 0,
       });
 
+      // The tracker stops the timer once the history entry is saved
       if (response.status === 'success') {
-        setPlayerStatus('stopped');
-        setStartTime(null);
-        setElapsedTime(0);
         setIsStopDialogOpen(false);
         setStopMessage('');
         setCustomTime('');
@@ -246,13 +211,15 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
     }
   };
 
-  const handleStopClick = () => {
+  const openStopDialog = (elapsedMs: number) => {
     // Initialize custom time with current elapsed time in minutes
-    const elapsedMinutes = Math.ceil(elapsedTime / 60000);
+    const elapsedMinutes = Math.ceil(elapsedMs / 60000);
     setCustomTime(elapsedMinutes.toString());
     setIsStopDialogOpen(true);
   };
 
+  const handleStopClick = () => openStopDialog(elapsedTime);
+
   const formatTime = (milliseconds: number) => {
     const seconds = Math.floor(milliseconds / 1000);
     const minutes = Math.floor(seconds / 60);
@@ -303,6 +270,11 @@ export function TicketPlayerControls({ ticket, onStatusChange }: TicketPlayerCon
             {playerStatus === 'playing' ? 'Timer running' : 
              playerStatus === 'paused' ? 'Timer paused' : 'Timer stopped'}
           </p>
+          {throttle?.throttled && (
+            <p className="text-xs text-amber-600">
+              Rate limited by the server, retrying in {Math.ceil(throttle.retryInMs / 1000)}s
+            </p>
+          )}
         </div>
 
         {/* Control Buttons */}
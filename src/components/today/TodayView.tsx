@@ -47,7 +47,7 @@ export function TodayView({ onTicketSelect }: TodayViewProps) {
 
     // Try to get from cache first
     if (!forceRefresh) {
-      const cachedData = cache.get<Ticket[]>(cacheKey);
+      const cachedData = await cache.get<Ticket[]>(cacheKey);
       if (cachedData) {
         setTodayTickets(cachedData);
 
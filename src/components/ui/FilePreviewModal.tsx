@@ -1,4 +1,4 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription } from '@/components/ui/dialog';
 import { Button } from '@/components/ui/button';
 import { Badge } from '@/components/ui/badge';
@@ -16,6 +16,7 @@ import {
   Loader2,
   ExternalLink
 } from 'lucide-react';
+import { readFile } from '@tauri-apps/plugin-fs';
 import { apiClient } from '@/lib/api';
 import { WindowManager } from '@/lib/windowManager';
 
@@ -79,6 +80,9 @@ export function FilePreviewModal({
   isDownloading = false
 }: FilePreviewModalProps) {
   const [fileBlob, setFileBlob] = useState<Blob | null>(null);
+  const [filePath, setFilePath] = useState<string | null>(null);
+  const [progress, setProgress] = useState<number | null>(null);
+  const downloadId = useRef<string | null>(null);
   const [fileUrl, setFileUrl] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -93,6 +97,10 @@ export function FilePreviewModal({
       loadFilePreview();
     }
     return () => {
+      // Closing the preview stops a download that is still running
+      if (downloadId.current) {
+        apiClient.cancelDownload(downloadId.current).catch(() => {});
+      }
       if (fileUrl) {
         URL.revokeObjectURL(fileUrl);
       }
@@ -102,9 +110,16 @@ export function FilePreviewModal({
   const loadFilePreview = async () => {
     setIsLoading(true);
     setError(null);
+    setProgress(null);
 
     try {
-      const blob = await apiClient.downloadAttachment(ticketId, filename);
+      downloadId.current = crypto.randomUUID();
+      const file = await apiClient.downloadAttachment(ticketId, filename, (received, total) => {
+        setProgress(total ? Math.round((received / total) * 100) : null);
+      }, downloadId.current);
+      setFilePath(file.path);
+
+      const blob = new Blob([await readFile(file.path)]);
       setFileBlob(blob);
 
       const url = URL.createObjectURL(blob);
@@ -132,10 +147,10 @@ export function FilePreviewModal({
   };
 
   const handleOpenExternally = async () => {
-    if (!fileBlob) return;
+    if (!filePath) return;
 
     try {
-      await WindowManager.openFileExternally(filename, fileBlob);
+      await WindowManager.openFileExternally(filePath);
       onClose(); // Close the modal after opening externally
     } catch (error) {
       console.error('Failed to open file externally:', error);
@@ -148,7 +163,9 @@ export function FilePreviewModal({
         <div className="flex items-center justify-center h-96">
           <div className="flex flex-col items-center gap-4">
             <Loader2 className="h-8 w-8 animate-spin" />
-            <p className="text-muted-foreground">Loading preview...</p>
+            <p className="text-muted-foreground">
+              Loading preview...{progress !== null && ` ${progress}%`}
+            </p>
           </div>
         </div>
       );
@@ -288,7 +305,7 @@ export function FilePreviewModal({
             </div>
           </div>
           <div className="flex items-center gap-2">
-            {fileBlob && (
+            {filePath && (
               <Button variant="outline" size="sm" onClick={handleOpenExternally} title="Open with default application">
                 <ExternalLink className="h-4 w-4" />
               </Button>
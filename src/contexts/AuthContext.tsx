@@ -1,11 +1,55 @@
 import { createContext, useContext, useState, useEffect, ReactNode } from 'react';
-import { User } from '@/types/api';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ask } from '@tauri-apps/plugin-dialog';
+import { AccountInfo, User } from '@/types/api';
 import { apiClient } from '@/lib/api';
 
+interface StoredSession {
+  token: string;
+  user: User;
+  // Server profile the token was issued by
+  profile_id?: string;
+}
+
+// Sessions used to live in localStorage; move them into the credential store once
+async function migrateLegacySession(): Promise<StoredSession | null> {
+  const token = localStorage.getItem('auth_token');
+  const savedUser = localStorage.getItem('user');
+  if (!token || !savedUser) return null;
+
+  const session: StoredSession = { token, user: JSON.parse(savedUser) };
+  await invoke('store_session', { session });
+  localStorage.removeItem('auth_token');
+  localStorage.removeItem('user');
+  return session;
+}
+
+// Signing out deletes the account's outbox too, so ask before unsent changes
+// go with it. Resolves to whether they may be discarded, or null to keep the
+// account.
+async function confirmDiscard(account?: AccountInfo): Promise<boolean | null> {
+  if (!account?.unsent) return false;
+  const changes = account.unsent === 1 ? '1 change' : `${account.unsent} changes`;
+  const discard = await ask(
+    `${changes} of ${account.user.name} could not be sent yet and will be lost. Sign out anyway?`,
+    { title: 'Unsent changes', kind: 'warning' }
+  );
+  return discard ? true : null;
+}
+
 interface AuthContextType {
   user: User | null;
-  login: (email: string, password: string) => Promise<boolean>;
+  // Every signed in account; the active one is what the windows show
+  accounts: AccountInfo[];
+  activeAccount: AccountInfo | null;
+  login: (email: string, password: string, profileId?: string) => Promise<boolean>;
   logout: () => void;
+  switchAccount: (id: string) => Promise<void>;
+  removeAccount: (id: string) => Promise<void>;
+  // Shows the login form on top of the signed in accounts
+  isAddingAccount: boolean;
+  setIsAddingAccount: (adding: boolean) => void;
   isLoading: boolean;
   isAuthenticated: boolean;
 }
@@ -14,28 +58,55 @@ const AuthContext = createContext<AuthContextType | undefined>(undefined);
 
 export function AuthProvider({ children }: { children: ReactNode }) {
   const [user, setUser] = useState<User | null>(null);
+  const [accounts, setAccounts] = useState<AccountInfo[]>([]);
+  const [isAddingAccount, setIsAddingAccount] = useState(false);
   const [isLoading, setIsLoading] = useState(true);
 
+  const activeAccount = accounts.find(account => account.active) ?? null;
+
+  const refreshAccounts = async () => {
+    const loaded = await invoke<AccountInfo[]>('get_accounts');
+    const active = loaded.find(account => account.active);
+    setAccounts(loaded);
+    setUser(active?.user ?? null);
+  };
+
   useEffect(() => {
     // Check if user is already logged in
-    const savedUser = localStorage.getItem('user');
-    if (savedUser && apiClient.isAuthenticated()) {
-      setUser(JSON.parse(savedUser));
-    }
-    setIsLoading(false);
+    const restoreSession = async () => {
+      try {
+        const active = await invoke<AccountInfo | null>('load_session');
+        if (!active) await migrateLegacySession();
+        await refreshAccounts();
+      } catch (error) {
+        console.error('Failed to restore session:', error);
+      } finally {
+        setIsLoading(false);
+      }
+    };
+
+    restoreSession();
+
+    // Another window or a notification click switched accounts
+    const unlisten = listen('accounts://changed', () => {
+      refreshAccounts().catch(console.error);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
   }, []);
 
-  const login = async (email: string, password: string): Promise<boolean> => {
+  const login = async (email: string, password: string, profileId?: string): Promise<boolean> => {
     try {
       setIsLoading(true);
-      const response = await apiClient.login(email, password);
-      
-      if (response.status === 'success') {
-        setUser(response.user);
-        localStorage.setItem('user', JSON.stringify(response.user));
-        return true;
-      }
-      return false;
+      const profile = await apiClient.selectServerProfile(
+        profileId ?? (await apiClient.getServerProfiles()).active
+      );
+      // Rust signs in and keeps the token; we only get the new account back
+      await invoke<AccountInfo>('login', { email, password, profileId: profile.id });
+      await refreshAccounts();
+      setIsAddingAccount(false);
+      return true;
     } catch (error) {
       console.error('Login failed:', error);
       return false;
@@ -44,18 +115,45 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     }
   };
 
+  // Signs the active account out; the next signed in account takes over
   const logout = () => {
-    apiClient.logout();
-    setUser(null);
-    localStorage.removeItem('user');
+    const signOut = async () => {
+      const loaded = await invoke<AccountInfo[]>('get_accounts');
+      const discardUnsent = await confirmDiscard(loaded.find(account => account.active));
+      if (discardUnsent === null) return;
+      setUser(null);
+      await invoke('clear_session', { discardUnsent });
+    };
+    signOut()
+      .catch(console.error)
+      .finally(() => refreshAccounts().catch(console.error));
+  };
+
+  const switchAccount = async (id: string) => {
+    await invoke('switch_account', { id });
+    await refreshAccounts();
+  };
+
+  const removeAccount = async (id: string) => {
+    const loaded = await invoke<AccountInfo[]>('get_accounts');
+    const discardUnsent = await confirmDiscard(loaded.find(account => account.id === id));
+    if (discardUnsent === null) return;
+    await invoke('remove_account', { id, discardUnsent });
+    await refreshAccounts();
   };
 
   const value: AuthContextType = {
     user,
+    accounts,
+    activeAccount,
     login,
     logout,
+    switchAccount,
+    removeAccount,
+    isAddingAccount,
+    setIsAddingAccount,
     isLoading,
-    isAuthenticated: !!user && apiClient.isAuthenticated(),
+    isAuthenticated: !!user,
   };
 
   return (
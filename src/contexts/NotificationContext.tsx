@@ -1,10 +1,9 @@
 import React, { createContext, useContext, useState, useEffect, useRef } from 'react';
 import { toast } from 'sonner';
-import { sendNotification, isPermissionGranted, requestPermission, onAction, registerActionTypes } from '@tauri-apps/plugin-notification';
+import { isPermissionGranted, requestPermission } from '@tauri-apps/plugin-notification';
 import { invoke } from '@tauri-apps/api/core';
-import { useAuth } from './AuthContext';
-import { useTickets } from './TicketsContext';
-import { Ticket } from '@/types/api';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 
 interface NotificationSettings {
   enableNewTicketNotifications: boolean;
@@ -26,10 +25,17 @@ const NotificationContext = createContext<NotificationContextType | undefined>(u
 
 const STORAGE_KEY = 'notification-settings';
 
+// Raised by the Rust notifier after it has shown the native notification
+interface TicketNotificationEvent {
+  title: string;
+  message: string;
+  ticketId: number | null;
+  accountId: string | null;
+  // Set for accounts in the background, the title already names it
+  account: string | null;
+}
+
 export function NotificationProvider({ children }: { children: React.ReactNode }) {
-  const { user } = useAuth();
-  const { tickets, lastUpdated } = useTickets();
-  
   const [settings, setSettings] = useState<NotificationSettings>(() => {
     const defaults = {
       enableNewTicketNotifications: true,
@@ -52,18 +58,14 @@ export function NotificationProvider({ children }: { children: React.ReactNode }
     return defaults;
   });
 
-  const previousTicketsRef = useRef<{
-    new_tickets: Ticket[];
-    my_tickets: Ticket[];
-  }>({ new_tickets: [], my_tickets: [] });
-  
   const audioRef = useRef<HTMLAudioElement | null>(null);
-  const isInitialLoad = useRef(true);
 
   useEffect(() => {
     localStorage.setItem(STORAGE_KEY, JSON.stringify(settings));
     // Dispatch custom event for same-window updates
     window.dispatchEvent(new Event('notification-settings-changed'));
+    // The Rust notifier decides on ticket notifications, keep it in sync
+    invoke('set_notification_settings', { settings }).catch(console.error);
   }, [settings]);
 
   useEffect(() => {
@@ -80,11 +82,9 @@ export function NotificationProvider({ children }: { children: React.ReactNode }
     });
   }, [settings.soundVolume]);
 
-  // Request Tauri notification permission and set up action handlers on mount
+  // Request notification permission on mount. Clicks on native
+  // notifications are handled in Rust, which opens the ticket window.
   useEffect(() => {
-    // eslint-disable-next-line @typescript-eslint/no-explicit-any
-    let unlistenAction: any;
-
     const setup = async () => {
       try {
         const permissionGranted = await isPermissionGranted();
@@ -94,35 +94,9 @@ export function NotificationProvider({ children }: { children: React.ReactNode }
       } catch (error) {
         console.warn('Could not request notification permission:', error);
       }
-
-      try {
-        // Register action type so clicking the notification can open the ticket
-        await registerActionTypes([{
-          id: 'ticket-notification',
-          actions: [{
-            id: 'open',
-            title: 'Open Ticket',
-            foreground: true,
-          }],
-        }]);
-
-        // Listen for notification clicks — open the related ticket window
-        unlistenAction = await onAction((action: any) => {
-          const ticketId = action.notification?.extra?.ticketId;
-          if (ticketId) {
-            invoke('open_ticket_window', { ticketId: parseInt(ticketId) }).catch(console.error);
-          }
-        });
-      } catch (error) {
-        console.warn('Could not set up notification action handlers:', error);
-      }
     };
 
     setup();
-
-    return () => {
-      if (unlistenAction) unlistenAction.unregister();
-    };
   }, []);
 
   const updateSettings = (newSettings: Partial<NotificationSettings>) => {
@@ -137,22 +111,9 @@ export function NotificationProvider({ children }: { children: React.ReactNode }
   };
 
   const showNotification = async (title: string, message: string, ticketId?: number) => {
-    // Try to use Tauri native notifications first
+    // Try native notifications first; with a ticketId, a click opens that ticket
     try {
-      const permissionGranted = await isPermissionGranted();
-      if (permissionGranted) {
-        await sendNotification({
-          title,
-          body: message,
-          icon: 'icon.png',
-          // When a ticketId is provided, attach the action type and extra data
-          // so clicking the notification opens that ticket
-          ...(ticketId ? {
-            actionTypeId: 'ticket-notification',
-            extra: { ticketId: String(ticketId) },
-          } : {}),
-        });
-      }
+      await invoke('show_notification', { title, message, ticketId: ticketId ?? null });
     } catch (error) {
       console.warn('Could not send Tauri notification, falling back to browser notification:', error);
 
@@ -176,96 +137,26 @@ export function NotificationProvider({ children }: { children: React.ReactNode }
     }
   };
 
-  // Monitor for new tickets and assignments
+  // New and assigned tickets are detected and natively notified by the Rust
+  // poller; while this window is alive we add the toast and sound on top.
   useEffect(() => {
-    if (isInitialLoad.current || !lastUpdated) {
-      isInitialLoad.current = false;
-      previousTicketsRef.current = {
-        new_tickets: tickets.new_tickets,
-        my_tickets: tickets.my_tickets,
-      };
-      return;
-    }
-
-    const previousTickets = previousTicketsRef.current;
-    const currentNewTickets = tickets.new_tickets;
-    const currentMyTickets = tickets.my_tickets;
-
-    // Check for new tickets in pool
-    if (settings.enableNewTicketNotifications) {
-      const newTicketsAdded = currentNewTickets.filter(ticket =>
-        !previousTickets.new_tickets.some(prev => prev.id === ticket.id)
-      );
-
-      if (newTicketsAdded.length > 0) {
-        const title = newTicketsAdded.length === 1
-          ? 'New Ticket Available'
-          : `${newTicketsAdded.length} New Tickets Available`;
-
-        let message: string;
-        let ticketId: number | undefined;
-
-        if (newTicketsAdded.length === 1) {
-          const t = newTicketsAdded[0];
-          const parts = [
-            `#${t.id}`,
-            t.subject && `[${t.subject}]`,
-            t.priority && `Priority: ${t.priority}`,
-            t.company?.name && `Customer: ${t.company.name}`,
-            t.summary,
-          ].filter(Boolean);
-          message = parts.join(' · ');
-          ticketId = t.id;
-        } else {
-          message = `${newTicketsAdded.length} new tickets are now available in your pool`;
-        }
-
-        showNotification(title, message, ticketId);
-        playSound();
+    // Ticket windows get the event too; one toast and sound is enough
+    if (getCurrentWebviewWindow().label !== 'main') return;
+
+    const unlisten = listen<TicketNotificationEvent>('notifications://ticket', (event) => {
+      const { title, message } = event.payload;
+      if (title.toLowerCase().includes('assigned')) {
+        toast.success(title, { description: message });
+      } else {
+        toast.info(title, { description: message });
       }
-    }
-
-    // Check for newly assigned tickets
-    if (settings.enableAssignedTicketNotifications && user) {
-      const newAssignments = currentMyTickets.filter(ticket =>
-        !previousTickets.my_tickets.some(prev => prev.id === ticket.id) &&
-        ticket.my_ticket_id === user.id
-      );
-
-      if (newAssignments.length > 0) {
-        const title = newAssignments.length === 1
-          ? 'New Ticket Assigned'
-          : `${newAssignments.length} New Tickets Assigned`;
-
-        let message: string;
-        let ticketId: number | undefined;
-
-        if (newAssignments.length === 1) {
-          const t = newAssignments[0];
-          const parts = [
-            `#${t.id}`,
-            t.subject && `[${t.subject}]`,
-            t.priority && `Priority: ${t.priority}`,
-            t.company?.name && `Customer: ${t.company.name}`,
-            'has been assigned to you',
-          ].filter(Boolean);
-          message = parts.join(' · ');
-          ticketId = t.id;
-        } else {
-          message = `${newAssignments.length} tickets have been assigned to you`;
-        }
-
-        showNotification(title, message, ticketId);
-        playSound();
-      }
-    }
+      playSound();
+    });
 
-    // Update previous tickets reference
-    previousTicketsRef.current = {
-      new_tickets: currentNewTickets,
-      my_tickets: currentMyTickets,
+    return () => {
+      unlisten.then((fn) => fn());
     };
-  }, [tickets, lastUpdated, settings, user]);
+  }, [settings.enableSound]);
 
   const requestNotificationPermission = async (): Promise<boolean> => {
     try {
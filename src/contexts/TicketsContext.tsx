@@ -1,7 +1,9 @@
 import React, { createContext, useContext, useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAuth } from './AuthContext';
 import { apiClient } from '@/lib/api';
-import { Ticket, Company } from '@/types/api';
+import { Ticket, Company, TicketsResponse } from '@/types/api';
 import { performanceMonitor } from '@/utils/performanceMonitor';
 
 // Helper hook to get notification settings without circular dependency
@@ -163,7 +165,15 @@ export function TicketsProvider({ children }: { children: React.ReactNode }) {
     const timerLabel = isBackground ? 'fetchTickets.background' : 'fetchTickets.initial';
     performanceMonitor.startTimer(timerLabel);
 
-    if (!isBackground) setIsLoading(true);
+    if (!isBackground) {
+      // Show the offline cache right away, the request below replaces it
+      const cached = await invoke<TicketsResponse | null>('get_tickets_snapshot').catch(() => null);
+      if (cached) {
+        setTickets(cached);
+      } else {
+        setIsLoading(true);
+      }
+    }
     setIsRefreshing(true);
 
     try {
@@ -194,8 +204,9 @@ export function TicketsProvider({ children }: { children: React.ReactNode }) {
   }, [user]);
 
   const refreshTickets = useCallback(async () => {
-    await fetchTickets(true); // Background refresh
-  }, [fetchTickets]);
+    // The Rust poller fetches and broadcasts the result to every window
+    await invoke('refresh_tickets_now');
+  }, []);
 
   const updateTicket = useCallback((updatedTicket: Ticket) => {
     setTickets(prevTickets => {
@@ -348,17 +359,31 @@ export function TicketsProvider({ children }: { children: React.ReactNode }) {
     }
   }, [user, fetchTickets]);
 
-  // Background refresh based on user setting
+  // Background refresh runs in the Rust poller, we only pass on the user setting
+  useEffect(() => {
+    invoke('set_poll_interval', { seconds: notificationSettings.ticketRefreshInterval }).catch(console.error);
+  }, [notificationSettings.ticketRefreshInterval]);
+
+  // Pick up the poller's latest snapshot whenever it reports a change
   useEffect(() => {
     if (!user) return;
 
-    const intervalMs = notificationSettings.ticketRefreshInterval * 1000;
-    const interval = setInterval(() => {
-      refreshTickets();
-    }, intervalMs);
+    const applySnapshot = async () => {
+      const snapshot = await invoke<TicketsResponse | null>('get_tickets_snapshot');
+      if (snapshot) {
+        setTickets(snapshot);
+        setLastUpdated(new Date());
+      }
+    };
+
+    const unlisteners = Promise.all(
+      ['tickets://added', 'tickets://changed', 'tickets://removed'].map((event) => listen(event, applySnapshot))
+    );
 
-    return () => clearInterval(interval);
-  }, [user, refreshTickets, notificationSettings.ticketRefreshInterval]);
+    return () => {
+      unlisteners.then((fns) => fns.forEach((unlisten) => unlisten()));
+    };
+  }, [user]);
 
   // Refresh when window comes back into focus
   useEffect(() => {
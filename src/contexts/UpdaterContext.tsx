@@ -1,9 +1,18 @@
 import React, { createContext, useContext, useState, useEffect, useRef, ReactNode } from 'react';
-import { check, Update, DownloadEvent } from '@tauri-apps/plugin-updater';
+import { Update, DownloadEvent } from '@tauri-apps/plugin-updater';
+import { invoke } from '@tauri-apps/api/core';
 import { relaunch } from '@tauri-apps/plugin-process';
 import { getVersion } from '@tauri-apps/api/app';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 
+type UpdateMetadata = ConstructorParameters<typeof Update>[0];
+
+// Checked from Rust so the request takes the configured proxy and certificates
+async function check(): Promise<Update | null> {
+  const metadata = await invoke<UpdateMetadata | null>('check_for_update');
+  return metadata ? new Update(metadata) : null;
+}
+
 interface UpdaterContextType {
   currentVersion: string;
   availableUpdate: Update | null;
@@ -1,5 +1,5 @@
 import type {
-  LoginResponse,
+  Ticket,
   TicketsResponse,
   ApiResponse,
   User,
@@ -9,426 +9,239 @@ import type {
   Location,
   TodoItem,
   TicketHistory,
-  PlayerStatus
+  PlayerStatus,
+  TimerState,
+  AwayGap,
+  TimesheetOptions,
+  TimesheetSummary,
+  ReportExportOptions,
+  ReportExportSummary,
+  SearchHit,
+  SearchKind,
+  DownloadedFile,
+  TicketAttachment,
+  UploadProgress,
+  CreatedTicket,
+  ServerProfile,
+  ServerProfiles,
+  NetworkSettings,
+  CalendarFeedStatus,
+  ShortcutSettings,
+  ShortcutStatus,
+  NewTicketDraft
 } from '@/types/api';
-import { fetch } from '@tauri-apps/plugin-http';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
+// Tokens stay in Rust: every request is sent there by the active account
 class ApiClient {
-  private baseUrl: string;
-  private token: string | null = null;
-
-  constructor(baseUrl: string = 'https://itm.ticketbase.net/api') {
-    this.baseUrl = baseUrl;
-    this.loadToken();
-  }
-
-  private loadToken() {
-    this.token = localStorage.getItem('auth_token');
-  }
-
-  private saveToken(token: string) {
-    this.token = token;
-    localStorage.setItem('auth_token', token);
-  }
-
-  private clearToken() {
-    this.token = null;
-    localStorage.removeItem('auth_token');
+  // Goes through the Rust request scheduler, which merges duplicate polls and
+  // backs off on 429 instead of failing right away
+  private async command<T>(name: string, args: Record<string, unknown>): Promise<T> {
+    try {
+      return await invoke<T>(name, args);
+    } catch (error) {
+      throw new Error(String(error));
+    }
   }
 
-  private async request<T>(
-    endpoint: string, 
-    options: RequestInit = {}
+  // Like command(), but answers from the offline cache when the server
+  // cannot be reached
+  private async commandWithOfflineFallback<T>(
+    name: string,
+    args: Record<string, unknown>
   ): Promise<T> {
-    const url = `${this.baseUrl}${endpoint}`;
-    
-    const headers: Record<string, string> = {
-      'Content-Type': 'application/json',
-      'Accept': 'application/json',
-      ...(options.headers as Record<string, string>),
-    };
-
-    if (this.token) {
-      headers.Authorization = `Bearer ${this.token}`;
-    }
-
-    const response = await fetch(url, {
-      ...options,
-      headers,
-    });
-
-    if (!response.ok) {
-      let errorBody = '';
-      try {
-        errorBody = await response.text();
-        console.error('❌ Server error response body:', errorBody);
-      } catch (e) {
-        console.error('Could not read error response body');
-      }
-      throw new Error(`HTTP error! status: ${response.status}${errorBody ? ' - ' + errorBody.substring(0, 500) : ''}`);
+    try {
+      return await this.command<T>(name, args);
+    } catch (error) {
+      if (!(error as Error).message.startsWith('Network error')) throw error;
+      const cached = await invoke<T | null>(`get_cached_${name.replace(/^get_/, '')}`, args).catch(() => null);
+      if (cached) return cached;
+      throw error;
     }
-
-    return response.json();
   }
 
-  // Authentication
-  async login(email: string, password: string): Promise<LoginResponse> {
-    const response = await this.request<LoginResponse>('/login', {
-      method: 'POST',
-      body: JSON.stringify({ email, password }),
+  // Writes that must not get lost go through the Rust outbox. When offline
+  // they resolve with status 'pending' and are replayed later.
+  private async mutate(mutation: Record<string, unknown>): Promise<ApiResponse<any>> {
+    return this.command<ApiResponse<any>>('submit_mutation', {
+      mutation,
+      idempotencyKey: crypto.randomUUID(),
     });
-
-    if (response.status === 'success' && response.authorisation.token) {
-      this.saveToken(response.authorisation.token);
-    }
-
-    return response;
-  }
-
-  logout() {
-    this.clearToken();
   }
 
   // Tickets
   async getTickets(userId: number, userGroupId: number, companyId?: number, locationId?: number, forUserId?: number, subUserGroupId?: number): Promise<TicketsResponse> {
-    return this.request<TicketsResponse>('/getTickets', {
-      method: 'POST',
-      body: JSON.stringify({
+    return this.command<TicketsResponse>('get_tickets', {
+      filter: {
         user_id: userId,
         user_group_id: userGroupId,
         company_id: companyId,
         location_id: locationId,
         for_user_id: forUserId,
         sub_user_group_id: subUserGroupId,
-      }),
+      },
     });
   }
 
   async getTicketsToday(userId: number, datum: string): Promise<ApiResponse<{todayTickets: any[]}>> {
-    return this.request<ApiResponse<{todayTickets: any[]}>>('/getTicketsToday', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-        datum,
-      }),
-    });
+    return this.command<ApiResponse<{todayTickets: any[]}>>('get_tickets_today', { userId, datum });
   }
 
   async getTicketData(ticketId: number): Promise<ApiResponse<{ticket_data: TicketHistory[]}>> {
-    return this.request<ApiResponse<{ticket_data: TicketHistory[]}>>('/getTicketData', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-      }),
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{ticket_data: TicketHistory[]}>>('get_ticket_data', { ticketId });
   }
 
-  async getTicketById(ticketId: number): Promise<ApiResponse<{tickets: any}>> {
-    return this.request<ApiResponse<{tickets: any}>>(`/getTicketById?ticket_id=${ticketId}`, {
-      method: 'GET',
-    });
+  // Already in the shape of the ticket lists; null when there is no such ticket
+  async getTicketById(ticketId: number): Promise<Ticket | null> {
+    return this.command<Ticket | null>('get_ticket_by_id', { ticketId });
   }
 
   // User Status
   async getUserStatus(userId: number): Promise<ApiResponse<{activity: UserStatus}>> {
-    return this.request<ApiResponse<{activity: UserStatus}>>('/getUserStatus', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-      }),
-    });
+    return this.command<ApiResponse<{activity: UserStatus}>>('get_user_status', { userId });
   }
 
   async changeUserStatus(userId: number, type: number): Promise<ApiResponse<{activity: UserStatus}>> {
-    return this.request<ApiResponse<{activity: UserStatus}>>('/changeUserStatus', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-        type,
-      }),
-    });
+    return this.command<ApiResponse<{activity: UserStatus}>>('change_user_status', { userId, kind: type });
   }
 
   // Templates and Data
   async getTemplates(companyId?: number): Promise<ApiResponse<{templates: Template[]}>> {
-    return this.request<ApiResponse<{templates: Template[]}>>(`/getTemplates${companyId ? `/${companyId}` : ''}`, {
-      method: 'POST',
-      body: JSON.stringify({
-        company_id: companyId,
-      }),
-    });
+    return this.command<ApiResponse<{templates: Template[]}>>('get_templates', { companyId });
   }
 
   async getCustomers(): Promise<ApiResponse<{customers: Company[]}>> {
-    return this.request<ApiResponse<{customers: Company[]}>>('/getCustomers', {
-      method: 'POST',
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{customers: Company[]}>>('get_customers', {});
   }
 
   async getCustomerLocations(customerId: number): Promise<ApiResponse<{locations: Location[]}>> {
-    return this.request<ApiResponse<{locations: Location[]}>>('/getCustomerLocations', {
-      method: 'POST',
-      body: JSON.stringify({
-        customer_id: customerId,
-      }),
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{locations: Location[]}>>('get_customer_locations', { customerId });
   }
 
   async getLocationUsers(locationId: number): Promise<ApiResponse<{users: User[]}>> {
-    return this.request<ApiResponse<{users: User[]}>>('/getLocationUsers', {
-      method: 'POST',
-      body: JSON.stringify({
-        location_id: locationId,
-      }),
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{users: User[]}>>('get_location_users', { locationId });
   }
 
   // Todo List
   async getCheckList(ticketId: number): Promise<ApiResponse<{check_list: TodoItem[]}>> {
-    return this.request<ApiResponse<{check_list: TodoItem[]}>>('/getCheckList', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-      }),
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{check_list: TodoItem[]}>>('get_check_list', { ticketId });
   }
 
   async newTodo(ticketId: number, userId: number, todo: string): Promise<ApiResponse<{check_list: TodoItem[]}>> {
-    return this.request<ApiResponse<{check_list: TodoItem[]}>>('/newTodo', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        todo,
-      }),
-    });
+    return this.mutate({ kind: 'new_todo', ticket_id: ticketId, user_id: userId, todo });
   }
 
-  async checkTodo(todoId: number, type: number): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/checkTodo', {
-      method: 'POST',
-      body: JSON.stringify({
-        todo_id: todoId,
-        type,
-      }),
-    });
+  async checkTodo(todoId: number, type: number, ticketId?: number): Promise<ApiResponse> {
+    return this.mutate({ kind: 'check_todo', ticket_id: ticketId, todo_id: todoId, type });
   }
 
   // Ticket Player Controls
   async play(ticketId: number, userId: number): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/play', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-      }),
-    });
+    return this.command<ApiResponse>('play', { ticketId, userId });
   }
 
   async pause(ticketId: number, userId: number, currentState: number = 1): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/pause', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        current_state: currentState, // 1 for PLAY, 3 for RESUME
-      }),
-    });
+    // currentState: 1 for PLAY, 3 for RESUME
+    return this.command<ApiResponse>('pause', { ticketId, userId, currentState });
   }
 
   async resume(ticketId: number, userId: number, currentState: number = 2): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/resume', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        current_state: currentState, // 2 for PAUSE state
-      }),
-    });
+    return this.command<ApiResponse>('resume', { ticketId, userId, currentState });
   }
 
   async stop(ticketId: number, userId: number): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/stop', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-      }),
-    });
+    return this.command<ApiResponse>('stop', { ticketId, userId });
   }
 
   async getPlayerStatus(ticketId: number, userId: number): Promise<ApiResponse<PlayerStatus>> {
-    return this.request<ApiResponse<PlayerStatus>>('/getPlayerStatus', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-      }),
-    });
+    return this.command<ApiResponse<PlayerStatus>>('get_player_status', { ticketId, userId });
   }
 
-  // Ticket Management
-  async createTicket(data: {
-    user_id: number;
-    description: string;
-    priority: string;
-    company_id: number;
-    location_id: number;
-    for_user_id: number;
-    dyn_template_id?: number;
-    attachments?: File[];
-  }): Promise<ApiResponse> {
-    // If there are attachments, use FormData
-    if (data.attachments && data.attachments.length > 0) {
-      const formData = new FormData();
-      formData.append('user_id', data.user_id.toString());
-      formData.append('description', data.description);
-      formData.append('priority', data.priority);
-      formData.append('company_id', data.company_id.toString());
-      formData.append('location_id', data.location_id.toString());
-      formData.append('for_user_id', data.for_user_id.toString());
-      
-      if (data.dyn_template_id) {
-        formData.append('dyn_template_id', data.dyn_template_id.toString());
-      }
-      
-      // Append all files with the same key 'ticket_image'
-      data.attachments.forEach((file) => {
-        formData.append('ticket_image', file);
-      });
-
-      const url = `${this.baseUrl}/createTicket`;
-      const headers: Record<string, string> = {
-        'Accept': 'application/json',
-      };
-
-      if (this.token) {
-        headers.Authorization = `Bearer ${this.token}`;
-      }
+  // The time tracker's record, which keeps counting while windows are closed.
+  // Updates arrive as 'timer://tick' events.
+  async getTimer(ticketId: number): Promise<TimerState | null> {
+    return this.command<TimerState | null>('get_timer', { ticketId });
+  }
 
-      console.log('Creating ticket with FormData and', data.attachments.length, 'attachment(s)');
-
-      const response = await fetch(url, {
-        method: 'POST',
-        headers,
-        body: formData,
-      });
-
-      if (!response.ok) {
-        let errorText = '';
-        try {
-          errorText = await response.text();
-          console.error('Server error response:', errorText);
-          console.error('Response headers:', Object.fromEntries(response.headers.entries()));
-        } catch (e) {
-          console.error('Could not read error response');
-        }
-        throw new Error(`HTTP error! status: ${response.status} - ${errorText.substring(0, 200)}`);
-      }
+  async getTimers(): Promise<TimerState[]> {
+    return this.command<TimerState[]>('get_timers', {});
+  }
 
-      return response.json();
-    }
+  async getAwayGap(): Promise<AwayGap | null> {
+    return this.command<AwayGap | null>('get_away_gap', {});
+  }
 
-    // Otherwise use JSON - ensure all numeric fields are numbers
-    const payload: any = {
-      user_id: Number(data.user_id),
-      description: data.description,
-      priority: data.priority,
-      company_id: Number(data.company_id),
-      location_id: Number(data.location_id),
-      for_user_id: Number(data.for_user_id),
-    };
-
-    if (data.dyn_template_id) {
-      payload.dyn_template_id = Number(data.dyn_template_id);
-    }
+  // Resumes the timers paused while the user was away, with or without the
+  // time away counted
+  async resolveAwayGap(keep: boolean): Promise<void> {
+    return this.command<void>('resolve_away_gap', { keep });
+  }
 
-    console.log('Creating ticket without attachments:', payload);
-    console.log('Request URL:', `${this.baseUrl}/createTicket`);
-    console.log('Authorization token present:', !!this.token);
-    
-    try {
-      const response = await this.request<ApiResponse>('/createTicket', {
-        method: 'POST',
-        body: JSON.stringify(payload),
-      });
-      console.log('✅ Ticket creation SUCCESS:', response);
-      return response;
-    } catch (error) {
-      console.error('❌ Error creating ticket:', error);
-      // Try to get more details from the error
-      if (error instanceof Error) {
-        console.error('Error message:', error.message);
-        console.error('Error stack:', error.stack);
+  // Whether "Stop…" was chosen for this ticket in the tray; only answers yes once
+  async takeStopRequest(ticketId: number): Promise<boolean> {
+    return this.command<boolean>('take_stop_request', { ticketId });
+  }
+
+  // Ticket Management
+  // Attachments are paths on disk; the backend checks their size and type
+  // and streams them, reporting progress per file
+  async createTicket(
+    data: {
+      user_id: number;
+      description: string;
+      priority: string;
+      company_id: number;
+      location_id: number;
+      for_user_id: number;
+      dyn_template_id?: number;
+      attachments?: string[];
+    },
+    onProgress?: (progress: UploadProgress) => void,
+    uploadId: string = crypto.randomUUID()
+  ): Promise<CreatedTicket> {
+    const { attachments = [], ...ticket } = data;
+    const unlisten = await listen<UploadProgress>('upload://progress', (event) => {
+      if (event.payload.id === uploadId) {
+        onProgress?.(event.payload);
       }
-      throw error;
+    });
+
+    try {
+      return await this.command<CreatedTicket>('create_ticket', { uploadId, ticket, attachments });
+    } finally {
+      unlisten();
     }
   }
 
+  async inspectAttachments(paths: string[]): Promise<TicketAttachment[]> {
+    return this.command<TicketAttachment[]>('inspect_attachments', { paths });
+  }
+
   async ticketTerminieren(ticketId: number, userId: number, date: string): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/TicketTerminieren', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        date,
-      }),
-    });
+    return this.command<ApiResponse>('ticket_terminieren', { ticketId, userId, date });
   }
 
   async ticketTerminierenApi(ticketId: number, userId: number, ticketStart: string, mode: number = 1): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/ticketTerminierenApi', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        ticket_start: ticketStart,
-        mode,
-      }),
-    });
+    return this.command<ApiResponse>('ticket_terminieren_api', { ticketId, userId, ticketStart, mode });
   }
 
   // Profile Management
   async editProfile(userId: number, name: string, phone: string): Promise<ApiResponse<{user: User}>> {
-    return this.request<ApiResponse<{user: User}>>('/editProfile', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-        name,
-        phone,
-      }),
-    });
+    return this.command<ApiResponse<{user: User}>>('edit_profile', { userId, name, phone });
   }
 
   async changePassword(userId: number, newPassword: string): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/changePassword', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-        new_password: newPassword,
-      }),
-    });
+    return this.command<ApiResponse>('change_password', { userId, newPassword });
   }
 
   // Mail Settings
   async getUsersMailSettings(userId: number): Promise<ApiResponse<{user_mail_settings_arr: any}>> {
-    return this.request<ApiResponse<{user_mail_settings_arr: any}>>('/getUsersMailSettings', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-      }),
-    });
+    return this.command<ApiResponse<{user_mail_settings_arr: any}>>('get_users_mail_settings', { userId });
   }
 
   async userMailSettings(userId: number, value: number, type: number): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/userMailSettings', {
-      method: 'POST',
-      body: JSON.stringify({
-        user_id: userId,
-        value,
-        type,
-      }),
-    });
+    return this.command<ApiResponse>('user_mail_settings', { userId, value, kind: type });
   }
 
   async saveTicketHistory(data: {
@@ -440,10 +253,7 @@ class ApiClient {
     retermUserId?: number;
     retermDate?: string;
   }): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/saveVerlaufApi', {
-      method: 'POST',
-      body: JSON.stringify(data),
-    });
+    return this.mutate({ kind: 'history', ...data });
   }
 
   async correctWatch(data: {
@@ -452,50 +262,44 @@ class ApiClient {
     old_time: number;
     new_time: number;
   }): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/correctWatch', {
-      method: 'POST',
-      body: JSON.stringify(data),
-    });
-  }
-
-  async downloadAttachment(ticketId: number, filename: string): Promise<Blob> {
-    const response = await fetch(`${this.baseUrl}/getDataUrl`, {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'Accept': 'application/octet-stream',
-        ...(this.token && { Authorization: `Bearer ${this.token}` }),
-      },
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        filename: filename,
-      }),
+    return this.command<ApiResponse>('correct_watch', { correction: data });
+  }
+
+  // Streams the attachment to a temp file in Rust. Resolves once its size and hash checked out.
+  async downloadAttachment(
+    ticketId: number,
+    filename: string,
+    onProgress?: (received: number, total: number | null) => void,
+    downloadId: string = crypto.randomUUID()
+  ): Promise<DownloadedFile> {
+    const unlisten = await listen<{ id: string; received: number; total: number | null }>('download://progress', (event) => {
+      if (event.payload.id === downloadId) {
+        onProgress?.(event.payload.received, event.payload.total);
+      }
     });
 
-    if (!response.ok) {
-      throw new Error(`Failed to download attachment: ${response.status}`);
+    try {
+      return await this.command<DownloadedFile>('download_attachment', { downloadId, ticketId, filename });
+    } finally {
+      unlisten();
     }
+  }
 
-    return response.blob();
+  async cancelDownload(downloadId: string): Promise<void> {
+    await invoke('cancel_download', { downloadId });
   }
 
   // Reports
   async getReport4(startDate: string, endDate: string): Promise<ApiResponse<{report: any[]}>> {
-    return this.request<ApiResponse<{report: any[]}>>(`/Report4?start_date=${startDate}&end_date=${endDate}`, {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{report: any[]}>>('get_report4', { startDate, endDate });
   }
 
   async getReport5(startDate: string, endDate: string): Promise<ApiResponse<{report: any[]}>> {
-    return this.request<ApiResponse<{report: any[]}>>(`/Report5?start_date=${startDate}&end_date=${endDate}`, {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{report: any[]}>>('get_report5', { startDate, endDate });
   }
 
   async getTopUsers(month: number): Promise<ApiResponse<{top_users: any[]}>> {
-    return this.request<ApiResponse<{top_users: any[]}>>(`/getTopUsers?month=${month}`, {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{top_users: any[]}>>('get_top_users', { month });
   }
 
   // Enhanced ticket search with additional filters (like web interface)
@@ -512,92 +316,138 @@ class ApiClient {
     status?: string;
     priority?: string;
   }): Promise<TicketsResponse> {
-    return this.request<TicketsResponse>('/getTickets', {
-      method: 'POST',
-      body: JSON.stringify({
+    return this.command<TicketsResponse>('get_tickets', {
+      filter: {
         user_id: filters.user_id,
         user_group_id: filters.user_group_id,
         company_id: filters.company_id,
         location_id: filters.location_id,
         for_user_id: filters.for_user_id,
         sub_user_group_id: filters.sub_user_group_id,
-      }),
+      },
     });
   }
 
   // Get all tickets without pool filtering using test endpoint
   async getTicketsUnfiltered(userId: number, userGroupId: number, companyId?: number, locationId?: number, forUserId?: number, subUserGroupId?: number): Promise<TicketsResponse> {
-    return this.request<TicketsResponse>('/testGetTickets', {
-      method: 'POST',
-      body: JSON.stringify({
+    return this.command<TicketsResponse>('get_tickets_unfiltered', {
+      filter: {
         user_id: userId,
         user_group_id: userGroupId,
         company_id: companyId,
         location_id: locationId,
         for_user_id: forUserId,
         sub_user_group_id: subUserGroupId,
-      }),
+      },
     });
   }
 
   // Ticket Messaging
   async getTicketMessages(ticketId: number): Promise<ApiResponse<{messages: any[]}>> {
-    return this.request<ApiResponse<{messages: any[]}>>(`/getTicketMessages?ticket_id=${ticketId}`, {
-      method: 'GET',
-    });
+    return this.commandWithOfflineFallback<ApiResponse<{messages: any[]}>>('get_ticket_messages', { ticketId });
   }
 
   async sendMessage(ticketId: number, userId: number, message: string): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/sendMessage', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        message,
-      }),
-    });
+    return this.mutate({ kind: 'message', ticket_id: ticketId, user_id: userId, message });
   }
 
   // Wiki / Knowledge Base
   async getWikiData(): Promise<ApiResponse<{wikiData: any[]}>> {
-    return this.request<ApiResponse<{wikiData: any[]}>>('/getWikiData', {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{wikiData: any[]}>>('get_wiki_data', {});
+  }
+
+  // Full-text search over tickets, history, messages and wiki articles synced so far
+  async search(query: string, options: { kinds?: SearchKind[]; ticketId?: number; limit?: number } = {}): Promise<SearchHit[]> {
+    return this.command<SearchHit[]>('search', { query, ...options });
   }
 
   // Ticket Rating
   async getClosedConfirmedTickets(userId: number): Promise<ApiResponse<{tickets: any[]}>> {
-    return this.request<ApiResponse<{tickets: any[]}>>(`/getClosedConfirmedTickets?user_id=${userId}`, {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{tickets: any[]}>>('get_closed_confirmed_tickets', { userId });
   }
 
   async rateTicket(ticketId: number, userId: number, rating: number, feedback?: string): Promise<ApiResponse> {
-    return this.request<ApiResponse>('/rateTicket', {
-      method: 'POST',
-      body: JSON.stringify({
-        ticket_id: ticketId,
-        user_id: userId,
-        rating,
-        feedback,
-      }),
-    });
+    return this.command<ApiResponse>('rate_ticket', { ticketId, userId, rating, feedback });
   }
 
   // Time Tracking History
   async getCorrectWatchHistoriesForPeriod(userId: number, startDate: string, endDate: string): Promise<ApiResponse<{histories: any[]}>> {
-    return this.request<ApiResponse<{histories: any[]}>>(`/getCorrectWatchHistoriesForPeriod?user_id=${userId}&start_date=${startDate}&end_date=${endDate}`, {
-      method: 'GET',
-    });
+    return this.command<ApiResponse<{histories: any[]}>>('get_correct_watch_histories_for_period', { userId, startDate, endDate });
+  }
+
+  // Writes the signed in user's timesheet for a period to a file
+  async exportTimesheet(options: TimesheetOptions): Promise<TimesheetSummary> {
+    return this.command<TimesheetSummary>('export_timesheet', { options });
+  }
+
+  // Fetches a report and writes it to a file
+  async exportReport(options: ReportExportOptions): Promise<ReportExportSummary> {
+    return this.command<ReportExportSummary>('export_report', { options });
+  }
+
+  // Server profiles
+  async getServerProfiles(): Promise<ServerProfiles> {
+    return this.command<ServerProfiles>('get_server_profiles', {});
+  }
+
+  async saveServerProfile(profile: ServerProfile): Promise<ServerProfile> {
+    return this.command<ServerProfile>('save_server_profile', { profile });
+  }
+
+  async deleteServerProfile(id: string): Promise<void> {
+    await this.command<void>('delete_server_profile', { id });
+  }
+
+  // Remembered as the login screen's default; requests follow the active account
+  async selectServerProfile(id: string): Promise<ServerProfile> {
+    return this.command<ServerProfile>('select_server_profile', { id });
+  }
+
+  // Network
+  async getNetworkSettings(): Promise<NetworkSettings> {
+    return this.command<NetworkSettings>('get_network_settings', {});
+  }
+
+  async setNetworkSettings(settings: NetworkSettings): Promise<void> {
+    await this.command<void>('set_network_settings', { settings });
+  }
+
+  // Calendar of scheduled tickets
+  async getCalendarFeed(): Promise<CalendarFeedStatus> {
+    return this.command<CalendarFeedStatus>('get_calendar_feed', {});
+  }
+
+  // `port` 0 lets the system pick one
+  async setCalendarFeed(enabled: boolean, port: number): Promise<CalendarFeedStatus> {
+    return this.command<CalendarFeedStatus>('set_calendar_feed', { enabled, port });
+  }
+
+  async resetCalendarFeedUrl(): Promise<CalendarFeedStatus> {
+    return this.command<CalendarFeedStatus>('reset_calendar_feed_url', {});
+  }
+
+  // Saves the appointments as an .ics file and returns how many there are
+  async exportAppointments(path: string): Promise<number> {
+    return this.command<number>('export_appointments', { path });
+  }
+
+  // System-wide shortcuts, with why one couldn't be registered
+  async getShortcuts(): Promise<ShortcutStatus[]> {
+    return this.command<ShortcutStatus[]>('get_shortcuts', {});
+  }
+
+  async setShortcuts(settings: ShortcutSettings): Promise<ShortcutStatus[]> {
+    return this.command<ShortcutStatus[]>('set_shortcuts', { settings });
   }
 
-  // Utility
-  isAuthenticated(): boolean {
-    return !!this.token;
+  // Opens a ticketbase:// link; fails on one that isn't valid
+  async openDeepLink(link: string): Promise<void> {
+    return this.command<void>('open_deep_link', { link });
   }
 
-  setBaseUrl(url: string) {
-    this.baseUrl = url;
+  // The draft of the last ticketbase://new link, once
+  async takeNewTicketDraft(): Promise<NewTicketDraft | null> {
+    return this.command<NewTicketDraft | null>('take_new_ticket_draft', {});
   }
 }
 
@@ -1,26 +1,17 @@
-interface CacheEntry<T> {
-  data: T;
-  timestamp: number;
-  ttl: number;
-}
+import { invoke } from '@tauri-apps/api/core';
 
+// Entries live in the Rust-managed SQLite cache in the app data dir, so they
+// survive profile resets and are not bound by the localStorage quota.
 class CacheManager {
-  private prefix = 'ticket_cache_';
-
   /**
    * Set a cache entry with TTL (Time To Live)
    * @param key Cache key
    * @param data Data to cache
    * @param ttl Time to live in milliseconds (default: 5 minutes)
    */
-  set<T>(key: string, data: T, ttl: number = 5 * 60 * 1000): void {
+  async set<T>(key: string, data: T, ttl: number = 5 * 60 * 1000): Promise<void> {
     try {
-      const entry: CacheEntry<T> = {
-        data,
-        timestamp: Date.now(),
-        ttl,
-      };
-      localStorage.setItem(this.prefix + key, JSON.stringify(entry));
+      await invoke('cache_set', { key, data, ttlMs: ttl });
     } catch (error) {
       console.warn('Cache set failed:', error);
     }
@@ -31,21 +22,9 @@ class CacheManager {
    * @param key Cache key
    * @returns Cached data or null if not found or expired
    */
-  get<T>(key: string): T | null {
+  async get<T>(key: string): Promise<T | null> {
     try {
-      const item = localStorage.getItem(this.prefix + key);
-      if (!item) return null;
-
-      const entry: CacheEntry<T> = JSON.parse(item);
-      const now = Date.now();
-
-      // Check if cache is expired
-      if (now - entry.timestamp > entry.ttl) {
-        this.delete(key);
-        return null;
-      }
-
-      return entry.data;
+      return await invoke<T | null>('cache_get', { key });
     } catch (error) {
       console.warn('Cache get failed:', error);
       return null;
@@ -56,9 +35,9 @@ class CacheManager {
    * Delete a cache entry
    * @param key Cache key
    */
-  delete(key: string): void {
+  async delete(key: string): Promise<void> {
     try {
-      localStorage.removeItem(this.prefix + key);
+      await invoke('cache_delete', { key });
     } catch (error) {
       console.warn('Cache delete failed:', error);
     }
@@ -67,17 +46,8 @@ class CacheManager {
   /**
    * Clear all cache entries
    */
-  clear(): void {
-    try {
-      const keys = Object.keys(localStorage);
-      keys.forEach(key => {
-        if (key.startsWith(this.prefix)) {
-          localStorage.removeItem(key);
-        }
-      });
-    } catch (error) {
-      console.warn('Cache clear failed:', error);
-    }
+  async clear(): Promise<void> {
+    await this.invalidateByPattern('');
   }
 
   /**
@@ -85,22 +55,17 @@ class CacheManager {
    * @param key Cache key
    * @returns True if cache exists and is not expired
    */
-  has(key: string): boolean {
-    return this.get(key) !== null;
+  async has(key: string): Promise<boolean> {
+    return (await this.get(key)) !== null;
   }
 
   /**
    * Invalidate cache entries by pattern
    * @param pattern String pattern to match cache keys
    */
-  invalidateByPattern(pattern: string): void {
+  async invalidateByPattern(pattern: string): Promise<void> {
     try {
-      const keys = Object.keys(localStorage);
-      keys.forEach(key => {
-        if (key.startsWith(this.prefix) && key.includes(pattern)) {
-          localStorage.removeItem(key);
-        }
-      });
+      await invoke('cache_invalidate', { pattern });
     } catch (error) {
       console.warn('Cache invalidate failed:', error);
     }
@@ -109,6 +74,15 @@ class CacheManager {
 
 export const cache = new CacheManager();
 
+// Drop what the previous localStorage based cache left behind
+try {
+  Object.keys(localStorage)
+    .filter(key => key.startsWith('ticket_cache_'))
+    .forEach(key => localStorage.removeItem(key));
+} catch {
+  // localStorage unavailable, nothing to clean up
+}
+
 /**
  * Debounce function for search inputs
  * @param func Function to debounce
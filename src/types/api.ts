@@ -16,15 +16,6 @@ export interface User {
   };
 }
 
-export interface LoginResponse {
-  status: string;
-  user: User;
-  authorisation: {
-    token: string;
-    type: string;
-  };
-}
-
 export interface Company {
   id: number;
   name: string;
@@ -106,6 +97,110 @@ export interface PlayerStatus {
   ticket_status_id: number;
 }
 
+// A running or paused ticket timer as the Rust time tracker records it
+export interface TimerState {
+  ticketId: number;
+  playStatus: number;
+  running: boolean;
+  // Milliseconds since the epoch
+  startedAt: number;
+  elapsedMs: number;
+}
+
+// Sent as 'timer://tick' every second while a timer runs and after changes
+export interface TimerTick {
+  accountId: string;
+  timers: TimerState[];
+}
+
+export interface PausedTimer {
+  accountId: string;
+  ticketId: number;
+  pausedAt: number;
+}
+
+// Sent as 'timer://away' once the user is back from a locked, sleeping or
+// idle session the timers were paused for, and as null once answered
+export interface AwayGap {
+  reason: 'locked' | 'sleep' | 'idle';
+  since: number;
+  returnedAt: number;
+  timers: PausedTimer[];
+}
+
+export type TimesheetColumn =
+  | 'date'
+  | 'ticket'
+  | 'summary'
+  | 'company'
+  | 'entries'
+  | 'minutes'
+  | 'hours'
+  | 'notes';
+
+export interface TimesheetOptions {
+  // YYYY-MM-DD, both inclusive
+  startDate: string;
+  endDate: string;
+  format: 'csv' | 'xlsx' | 'ics';
+  path: string;
+  columns?: TimesheetColumn[];
+  // Each row, or event, rounded to a multiple of `minutes`; 0 keeps whole minutes
+  rounding?: { minutes: number; mode: 'nearest' | 'up' | 'down' };
+}
+
+export interface TimesheetSummary {
+  // Rows in a table, events in a calendar
+  rows: number;
+  minutes: number;
+}
+
+export type CsvEncoding = 'utf-8-bom' | 'utf-8' | 'windows-1252';
+
+export interface ReportExportOptions {
+  report: 'report4' | 'report5' | 'topUsers';
+  // YYYY-MM-DD, for Report 4 and 5
+  startDate?: string;
+  endDate?: string;
+  // 1 to 12, for the top users
+  month?: number;
+  format: 'csv' | 'xlsx' | 'pdf';
+  path: string;
+  csv?: { delimiter: string; encoding: CsvEncoding };
+}
+
+export interface ReportExportSummary {
+  rows: number;
+}
+
+export interface CalendarFeedStatus {
+  enabled: boolean;
+  port: number;
+  // What calendar apps subscribe to, while the feed is served
+  url: string | null;
+}
+
+export type ShortcutAction = 'pauseResume' | 'newTicket' | 'openTicket';
+
+// Accelerators like "CommandOrControl+Alt+Shift+P"; an empty one is off
+export type ShortcutSettings = Record<ShortcutAction, string>;
+
+export interface ShortcutStatus {
+  action: ShortcutAction;
+  accelerator: string;
+  registered: boolean;
+  // Why it isn't registered, unless it is turned off
+  error: string | null;
+}
+
+// What a ticketbase://new link fills into the new ticket form
+export interface NewTicketDraft {
+  // Id, customer number or name of the company
+  company: string | null;
+  description: string | null;
+  priority: 'NORMAL' | 'HIGH' | 'VERY_HIGH' | null;
+}
+
 export interface ApiResponse<T = any> {
   status: string;
   result?: string;
@@ -135,4 +230,86 @@ export interface Location {
   id: number;
   name: string;
   company_id: number;
-}
\ No newline at end of file
+}
+export type SearchKind = 'ticket' | 'history' | 'message' | 'wiki';
+
+export interface SearchHit {
+  kind: SearchKind;
+  id: number;
+  ticketId: number | null;
+  title: string;
+  // HTML escaped by the backend, matches wrapped in <b>
+  snippet: string;
+  score: number;
+}
+
+export interface DownloadedFile {
+  path: string;
+  size: number;
+  sha256: string;
+}
+
+export interface TicketAttachment {
+  path: string;
+  name: string;
+  size: number;
+  mime: string;
+}
+
+export interface UploadProgress {
+  id: string;
+  // Index into the attachments of the upload
+  file: number;
+  name: string;
+  sent: number;
+  total: number;
+}
+
+export interface CreatedTicket {
+  // null if the server did not report it
+  ticketId: number | null;
+  message: string | null;
+  attachments: string[];
+}
+
+export interface ServerProfile {
+  // Empty for a new profile; the backend derives one from the name
+  id: string;
+  name: string;
+  baseUrl: string;
+  // Path to a PEM bundle with extra trusted roots
+  caCertificate?: string;
+}
+
+export interface ServerProfiles {
+  profiles: ServerProfile[];
+  active: string;
+}
+
+export type ProxyMode = 'system' | 'none' | 'manual' | 'pac';
+
+// How the app reaches the network, see `network/mod.rs`
+export interface NetworkSettings {
+  proxyMode: ProxyMode;
+  proxyUrl: string;
+  // Comma separated, like NO_PROXY
+  noProxy: string;
+  pacUrl: string;
+  proxyUsername: string;
+  // Never sent back; leave undefined to keep the stored password
+  proxyPassword?: string;
+  caBundle: string | null;
+}
+
+// A signed in account, see `accounts.rs`
+export interface AccountInfo {
+  id: string;
+  profileId: string;
+  // Name of the server profile
+  server: string;
+  baseUrl: string;
+  user: User;
+  active: boolean;
+  // Changes in the outbox that signing out would throw away
+  unsent: number;
+}